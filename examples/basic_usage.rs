@@ -23,7 +23,7 @@ impl Model for User {
         vec!["name", "email"]
     }
 
-    async fn find(id: i64) -> Result<Option<Self>, sqlx::Error> {
+    async fn find(_id: i64) -> Result<Option<Self>, sqlx::Error> {
         // Implementation would query the database
         Ok(None)
     }
@@ -62,11 +62,6 @@ impl Model for User {
         Ok(())
     }
 
-    // Provide instance helper to access primary key value for relations
-    pub fn get_id_value(&self) -> Option<serde_json::Value> {
-        self.id.map(|v| serde_json::Value::Number(serde_json::Number::from(v)))
-    }
-
     // override get_key_value to use get_id_value
     fn get_key_value(&self) -> Option<serde_json::Value> {
         self.get_id_value()
@@ -97,7 +92,7 @@ impl Model for Post {
         vec!["title", "content", "user_id"]
     }
 
-    async fn find(id: i64) -> Result<Option<Self>, sqlx::Error> {
+    async fn find(_id: i64) -> Result<Option<Self>, sqlx::Error> {
         Ok(None)
     }
 
@@ -137,10 +132,6 @@ impl Model for Post {
         Ok(())
     }
 
-    pub fn get_id_value(&self) -> Option<serde_json::Value> {
-        self.id.map(|v| serde_json::Value::Number(serde_json::Number::from(v)))
-    }
-
     fn get_key_value(&self) -> Option<serde_json::Value> {
         self.get_id_value()
     }
@@ -151,6 +142,11 @@ impl HasTimestamps for Post {}
 
 // Example usage
 impl User {
+    // Provide instance helper to access primary key value for relations
+    pub fn get_id_value(&self) -> Option<serde_json::Value> {
+        self.id.map(|v| serde_json::Value::Number(serde_json::Number::from(v)))
+    }
+
     // Define relationship methods
     pub fn posts(&self) -> RustEloquent::orm::relations::HasMany<User, Post> {
         self.has_many(None, None)
@@ -158,6 +154,10 @@ impl User {
 }
 
 impl Post {
+    pub fn get_id_value(&self) -> Option<serde_json::Value> {
+        self.id.map(|v| serde_json::Value::Number(serde_json::Number::from(v)))
+    }
+
     // Define relationship methods
     pub fn user(&self) -> RustEloquent::orm::relations::BelongsTo<Post, User> {
         self.belongs_to(None, None)
@@ -0,0 +1,59 @@
+// Times DatabaseConnection::fetch_all on a 50-column, 10k-row table in in-memory SQLite,
+// and counts the heap allocations it makes, which are steadier than the timing.
+// Run with: cargo run --release --example fetch_all_bench
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use RustEloquent::db::connection::{DatabaseConnection, SqliteConnection};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const COLUMNS: usize = 50;
+const ROWS: usize = 10_000;
+const RUNS: u32 = 10;
+
+#[tokio::main]
+async fn main() -> Result<(), sqlx::Error> {
+    let connection = SqliteConnection::new("sqlite::memory:").await?;
+
+    let columns: Vec<String> = (1..=COLUMNS).map(|i| format!("c{}", i)).collect();
+    let definitions: Vec<String> = columns.iter().map(|column| format!("{} TEXT", column)).collect();
+    connection.execute(&format!("CREATE TABLE wide ({})", definitions.join(", "))).await?;
+    let values: Vec<String> = columns.iter().map(|column| format!("'{}-' || x", column)).collect();
+    connection.execute(&format!(
+        "INSERT INTO wide WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < {}) SELECT {} FROM n",
+        ROWS, values.join(", ")
+    )).await?;
+
+    // One untimed run to warm the statement cache and the allocator
+    connection.fetch_all("SELECT * FROM wide").await?;
+
+    let mut total = Duration::ZERO;
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let (names, rows) = connection.fetch_all("SELECT * FROM wide").await?;
+        total += start.elapsed();
+        assert_eq!((names.len(), rows.len()), (COLUMNS, ROWS));
+    }
+    let allocations = (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / RUNS as usize;
+    println!("fetch_all {} columns x {} rows: {:?} and {} allocations per run over {} runs",
+        COLUMNS, ROWS, total / RUNS, allocations, RUNS);
+    Ok(())
+}
@@ -1,19 +1,184 @@
-use sqlx::{Pool, MySql, Postgres, Sqlite, Row, Column};
+use sqlx::{Pool, MySql, Postgres, Sqlite, Row, Column, ColumnIndex, Decode, Executor, Transaction, Type, TypeInfo, ValueRef};
+use sqlx::mysql::MySqlRow;
+use sqlx::postgres::{PgRow, PgTypeInfo, PgTypeKind};
+use sqlx::sqlite::SqliteRow;
+use sqlx::types::chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
+use crate::orm::diagnostics::log_warning;
+
+// SQL dialect of a connection, for rendering that differs between databases
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Driver {
+    #[default]
+    MySql,
+    Postgres,
+    Sqlite,
+}
+
+// Transaction isolation levels. SQLite transactions are always serializable,
+// so the level is ignored there
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsolationLevel {
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+impl IsolationLevel {
+    pub fn sql(&self) -> &'static str {
+        match self {
+            IsolationLevel::ReadCommitted => "READ COMMITTED",
+            IsolationLevel::RepeatableRead => "REPEATABLE READ",
+            IsolationLevel::Serializable => "SERIALIZABLE",
+        }
+    }
+}
 
 // Database connection trait
 #[async_trait::async_trait]
 pub trait DatabaseConnection: Send + Sync {
+    fn driver(&self) -> Driver;
+    async fn execute(&self, sql: &str) -> Result<u64, sqlx::Error>;
+    // Run sql as-is over the driver's text protocol instead of preparing it, for DDL, SET
+    // commands and multi-statement scripts. Nothing is parameterized, so never pass it
+    // user input. Implementations without a separate path fall back to execute
+    async fn unprepared(&self, sql: &str) -> Result<u64, sqlx::Error> {
+        self.execute(sql).await
+    }
+    async fn fetch_one(&self, sql: &str) -> Result<Vec<(String, serde_json::Value)>, sqlx::Error>;
+    // Column names are shared by every row, so they're returned once alongside the row values
+    async fn fetch_all(&self, sql: &str) -> Result<(Arc<[String]>, Vec<Vec<serde_json::Value>>), sqlx::Error>;
+    async fn begin(&self) -> Result<Box<dyn DatabaseTransaction>, sqlx::Error>;
+    async fn begin_with_isolation(&self, level: IsolationLevel) -> Result<Box<dyn DatabaseTransaction>, sqlx::Error>;
+    async fn close(&self);
+    // Whether the server runs window functions (`OVER (...)`); false on MySQL before 8.0,
+    // MariaDB before 10.2 and SQLite before 3.25
+    fn supports_window_functions(&self) -> bool {
+        true
+    }
+}
+
+// An open transaction - statements run on the single connection it holds
+#[async_trait::async_trait]
+pub trait DatabaseTransaction: Send + Sync {
     async fn execute(&self, sql: &str) -> Result<u64, sqlx::Error>;
+    // As DatabaseConnection::unprepared, inside the transaction
+    async fn unprepared(&self, sql: &str) -> Result<u64, sqlx::Error> {
+        self.execute(sql).await
+    }
     async fn fetch_one(&self, sql: &str) -> Result<Vec<(String, serde_json::Value)>, sqlx::Error>;
-    async fn fetch_all(&self, sql: &str) -> Result<Vec<Vec<(String, serde_json::Value)>>, sqlx::Error>;
+    async fn fetch_all(&self, sql: &str) -> Result<(Arc<[String]>, Vec<Vec<serde_json::Value>>), sqlx::Error>;
+    async fn commit(&self) -> Result<(), sqlx::Error>;
+    async fn rollback(&self) -> Result<(), sqlx::Error>;
+    // As DatabaseConnection::supports_window_functions, for the connection it was begun on
+    fn supports_window_functions(&self) -> bool {
+        true
+    }
+}
+
+// Lifecycle events for monitoring connection churn and open transactions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    Connected { name: String },
+    Disconnected { name: String },
+    TransactionBeginning { name: String },
+    TransactionCommitted { name: String },
+    TransactionRolledBack { name: String },
+}
+
+pub type ConnectionListener = Arc<dyn Fn(&ConnectionEvent) + Send + Sync>;
+
+// A ConnectionManager's listeners. Registered alongside each of its connections, so
+// transactions opened without the manager (transaction(), savepoints) reach them too
+#[derive(Clone, Default)]
+pub(crate) struct Listeners(Arc<std::sync::RwLock<Vec<ConnectionListener>>>);
+
+impl Listeners {
+    // The listeners of the manager that registered name; none when nothing is registered
+    pub(crate) fn for_name(name: &str) -> Self {
+        let connections = REGISTERED_CONNECTIONS.read().unwrap_or_else(|e| e.into_inner());
+        connections.as_ref()
+            .and_then(|connections| connections.get(name))
+            .map(|(_, listeners)| listeners.clone())
+            .unwrap_or_default()
+    }
+
+    fn push(&self, listener: ConnectionListener) {
+        self.0.write().unwrap_or_else(|e| e.into_inner()).push(listener);
+    }
+
+    pub(crate) fn dispatch(&self, event: ConnectionEvent) {
+        let listeners = self.0.read().unwrap_or_else(|e| e.into_inner()).clone();
+        for listener in listeners.iter() {
+            listener(&event);
+        }
+    }
+}
+
+// Run body in an open transaction, then commit on Ok or roll back on Err, telling the
+// listeners as it begins and ends. The body's error is returned even when the rollback
+// fails too, as it says why the transaction failed; the rollback error goes to the query log.
+// Under a savepoint, Ok releases it and Err rolls back to it. A failed release is logged and
+// the body's result kept, as the work stays in the outer transaction, whose commit decides
+pub(crate) async fn run_transaction<R>(
+    listeners: &Listeners,
+    name: &str,
+    transaction: &Arc<dyn DatabaseTransaction>,
+    savepoint: Option<&str>,
+    body: impl Future<Output = Result<R, sqlx::Error>>,
+) -> Result<R, sqlx::Error> {
+    listeners.dispatch(ConnectionEvent::TransactionBeginning { name: name.to_string() });
+
+    match body.await {
+        Ok(result) => {
+            match savepoint {
+                Some(savepoint) => {
+                    if let Err(release) = transaction.unprepared(&format!("RELEASE SAVEPOINT {}", savepoint)).await {
+                        log_warning(&format!("releasing savepoint {} on [{}] failed: {}", savepoint, name, release));
+                    }
+                }
+                None => transaction.commit().await?,
+            }
+            listeners.dispatch(ConnectionEvent::TransactionCommitted { name: name.to_string() });
+            Ok(result)
+        }
+        Err(error) => {
+            let rollback = match savepoint {
+                Some(savepoint) => transaction.unprepared(&format!("ROLLBACK TO SAVEPOINT {}", savepoint)).await.map(drop),
+                None => transaction.rollback().await,
+            };
+            if let Err(rollback) = rollback {
+                log_warning(&format!("rolling back the transaction on [{}] failed: {}", name, rollback));
+            }
+            listeners.dispatch(ConnectionEvent::TransactionRolledBack { name: name.to_string() });
+            Err(error)
+        }
+    }
+}
+
+// Every registered connection by name, so queries for a model render for the database
+// behind Model::connection() and top-level transactions can find it. Shared by every
+// ConnectionManager; a later registration under the same name replaces the earlier one
+type ConnectionRegistry = HashMap<String, (Arc<dyn DatabaseConnection>, Listeners)>;
+static REGISTERED_CONNECTIONS: std::sync::RwLock<Option<ConnectionRegistry>> = std::sync::RwLock::new(None);
+
+// The driver registered under a connection name, or the default when there is none
+pub fn driver_for(name: &str) -> Driver {
+    registered_connection(name).map(|connection| connection.driver()).unwrap_or_default()
+}
+
+pub fn registered_connection(name: &str) -> Option<Arc<dyn DatabaseConnection>> {
+    let connections = REGISTERED_CONNECTIONS.read().unwrap_or_else(|e| e.into_inner());
+    connections.as_ref().and_then(|connections| connections.get(name)).map(|(connection, _)| connection.clone())
 }
 
 // Connection manager - similar to Laravel's DB facade
 pub struct ConnectionManager {
-    connections: Arc<RwLock<HashMap<String, Box<dyn DatabaseConnection>>>>,
+    connections: Arc<RwLock<HashMap<String, Arc<dyn DatabaseConnection>>>>,
+    listeners: Listeners,
     default_connection: String,
 }
 
@@ -21,86 +186,431 @@ impl ConnectionManager {
     pub fn new() -> Self {
         Self {
             connections: Arc::new(RwLock::new(HashMap::new())),
+            listeners: Listeners::default(),
             default_connection: "default".to_string(),
         }
     }
 
-    pub async fn add_connection<T>(&self, name: &str, connection: T) 
+    pub async fn add_connection<T>(&self, name: &str, connection: T)
     where
         T: DatabaseConnection + 'static,
     {
-        let mut connections = self.connections.write().await;
-        connections.insert(name.to_string(), Box::new(connection));
+        self.register(name, Arc::new(connection)).await;
+    }
+
+    // Open a connection with the driver picked from the URL scheme and register it
+    pub async fn connect(&self, name: &str, url: &str) -> Result<Arc<dyn DatabaseConnection>, sqlx::Error> {
+        let scheme = url.split_once(':').map(|(scheme, _)| scheme).unwrap_or_default();
+        let connection: Arc<dyn DatabaseConnection> = match scheme {
+            "mysql" | "mariadb" => Arc::new(MySqlConnection::new(url).await?),
+            "postgres" | "postgresql" => Arc::new(PostgresConnection::new(url).await?),
+            "sqlite" => Arc::new(SqliteConnection::new(url).await?),
+            _ => return Err(sqlx::Error::Configuration(
+                format!("Unsupported database URL scheme [{}] for connection [{}]", scheme, name).into(),
+            )),
+        };
+        self.register(name, connection.clone()).await;
+        Ok(connection)
     }
 
-    pub async fn get_connection(&self, name: Option<&str>) -> Option<Box<dyn DatabaseConnection>> {
+    async fn register(&self, name: &str, connection: Arc<dyn DatabaseConnection>) {
+        REGISTERED_CONNECTIONS.write().unwrap_or_else(|e| e.into_inner())
+            .get_or_insert_with(HashMap::new)
+            .insert(name.to_string(), (connection.clone(), self.listeners.clone()));
+        self.connections.write().await.insert(name.to_string(), connection);
+        self.listeners.dispatch(ConnectionEvent::Connected { name: name.to_string() });
+    }
+
+    pub async fn get_connection(&self, name: Option<&str>) -> Option<Arc<dyn DatabaseConnection>> {
         let connections = self.connections.read().await;
         let conn_name = name.unwrap_or(&self.default_connection);
-        connections.get(conn_name).map(|conn| {
-            // This is a placeholder - in a real implementation you'd clone the connection
-            // For now we'll return None to fix compilation
-            None
-        }).flatten()
+        connections.get(conn_name).cloned()
+    }
+
+    // Close a connection's pool and forget it
+    pub async fn disconnect(&self, name: &str) {
+        let removed = self.connections.write().await.remove(name);
+        if let Some(connections) = REGISTERED_CONNECTIONS.write().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            connections.remove(name);
+        }
+        if let Some(connection) = removed {
+            connection.close().await;
+            self.listeners.dispatch(ConnectionEvent::Disconnected { name: name.to_string() });
+        }
     }
 
     pub fn set_default(&mut self, name: &str) {
         self.default_connection = name.to_string();
     }
+
+    pub fn default_connection(&self) -> &str {
+        &self.default_connection
+    }
+
+    // Register a listener for connection and transaction events on this manager's
+    // connections, including transactions opened with transaction()/transaction_on()
+    pub async fn listen<F>(&self, listener: F)
+    where
+        F: Fn(&ConnectionEvent) + Send + Sync + 'static,
+    {
+        self.listeners.push(Arc::new(listener));
+    }
+
+    // Run the closure in a transaction, committing on Ok and rolling back on Err
+    pub async fn transaction<F, Fut, R>(&self, name: Option<&str>, callback: F) -> Result<R, sqlx::Error>
+    where
+        F: FnOnce(Arc<dyn DatabaseTransaction>) -> Fut,
+        Fut: Future<Output = Result<R, sqlx::Error>>,
+    {
+        self.run_transaction(name, None, callback).await
+    }
+
+    // As transaction, with the isolation level set before any work runs
+    pub async fn transaction_with_isolation<F, Fut, R>(
+        &self,
+        name: Option<&str>,
+        level: IsolationLevel,
+        callback: F,
+    ) -> Result<R, sqlx::Error>
+    where
+        F: FnOnce(Arc<dyn DatabaseTransaction>) -> Fut,
+        Fut: Future<Output = Result<R, sqlx::Error>>,
+    {
+        self.run_transaction(name, Some(level), callback).await
+    }
+
+    async fn run_transaction<F, Fut, R>(
+        &self,
+        name: Option<&str>,
+        level: Option<IsolationLevel>,
+        callback: F,
+    ) -> Result<R, sqlx::Error>
+    where
+        F: FnOnce(Arc<dyn DatabaseTransaction>) -> Fut,
+        Fut: Future<Output = Result<R, sqlx::Error>>,
+    {
+        let conn_name = name.unwrap_or(&self.default_connection).to_string();
+        let connection = self.get_connection(Some(&conn_name)).await.ok_or_else(|| {
+            sqlx::Error::Configuration(format!("Database connection [{}] not configured", conn_name).into())
+        })?;
+
+        let transaction: Arc<dyn DatabaseTransaction> = match level {
+            Some(level) => Arc::from(connection.begin_with_isolation(level).await?),
+            None => Arc::from(connection.begin().await?),
+        };
+        run_transaction(&self.listeners, &conn_name, &transaction, None, callback(transaction.clone())).await
+    }
+}
+
+impl Default for ConnectionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Turns one column of a row into JSON
+type Decoder<R> = fn(&R, usize) -> Result<serde_json::Value, sqlx::Error>;
+
+// Column names and row values, as fetch_all returns them
+type DecodedRows = (Arc<[String]>, Vec<Vec<serde_json::Value>>);
+
+// Row decoding shared by every driver
+fn decode_row<R>(row: &R, decode: Decoder<R>) -> Result<Vec<(String, serde_json::Value)>, sqlx::Error>
+where
+    R: Row,
+{
+    row.columns().iter()
+        .enumerate()
+        .map(|(i, column)| Ok((column.name().to_string(), decode(row, i)?)))
+        .collect()
+}
+
+fn decode_rows<R>(rows: Vec<R>, decode: Decoder<R>) -> Result<DecodedRows, sqlx::Error>
+where
+    R: Row,
+{
+    let columns: Arc<[String]> = match rows.first() {
+        Some(row) => row.columns().iter().map(|column| column.name().to_string()).collect(),
+        None => Arc::from(Vec::new()),
+    };
+    let mut results = Vec::with_capacity(rows.len());
+
+    for row in rows {
+        let mut row_data = Vec::with_capacity(columns.len());
+        for i in 0..columns.len() {
+            row_data.push(decode(&row, i)?);
+        }
+        results.push(row_data);
+    }
+
+    Ok((columns, results))
+}
+
+// Columns decode as strings; NULL, and types that don't decode as text, become null
+fn decode_text<R>(row: &R, index: usize) -> serde_json::Value
+where
+    R: Row,
+    usize: ColumnIndex<R>,
+    for<'r> Option<String>: Decode<'r, R::Database> + Type<R::Database>,
+{
+    match row.try_get::<Option<String>, _>(index).unwrap_or(None) {
+        Some(v) => serde_json::Value::String(v),
+        None => serde_json::Value::Null,
+    }
+}
+
+// MySQL columns decode by their type: integers, floats, booleans and dates are read as
+// such, anything else (DECIMAL and JSON included, which MySQL sends as text) as a string
+fn decode_mysql(row: &MySqlRow, index: usize) -> Result<serde_json::Value, sqlx::Error> {
+    let type_info = row.column(index).type_info();
+    if <i64 as Type<MySql>>::compatible(type_info) {
+        decode_unchecked::<i64, _>(row, index)
+    } else if <u64 as Type<MySql>>::compatible(type_info) {
+        decode_unchecked::<u64, _>(row, index)
+    } else if <f64 as Type<MySql>>::compatible(type_info) {
+        decode_unchecked::<f64, _>(row, index)
+    } else if <bool as Type<MySql>>::compatible(type_info) {
+        decode_unchecked::<bool, _>(row, index)
+    } else if <NaiveDateTime as Type<MySql>>::compatible(type_info) {
+        decode_display::<NaiveDateTime, _>(row, index)
+    } else if <NaiveDate as Type<MySql>>::compatible(type_info) {
+        decode_display::<NaiveDate, _>(row, index)
+    } else if <NaiveTime as Type<MySql>>::compatible(type_info) {
+        decode_display::<NaiveTime, _>(row, index)
+    } else {
+        decode_string(row, index)
+    }
+}
+
+// SQLite columns decode by the value's storage class rather than the declared type, which
+// doesn't constrain what a column holds (and expressions like COUNT(*) have none).
+// BOOLEAN columns hold integers, so they are read back as booleans
+fn decode_sqlite(row: &SqliteRow, index: usize) -> Result<serde_json::Value, sqlx::Error> {
+    let value = row.try_get_raw(index)?;
+    if value.is_null() {
+        return Ok(serde_json::Value::Null);
+    }
+    let type_info = value.type_info().into_owned();
+    if row.column(index).type_info().name() == "BOOLEAN" {
+        decode_unchecked::<bool, _>(row, index)
+    } else if <i64 as Type<Sqlite>>::compatible(&type_info) {
+        decode_unchecked::<i64, _>(row, index)
+    } else if <f64 as Type<Sqlite>>::compatible(&type_info) {
+        decode_unchecked::<f64, _>(row, index)
+    } else {
+        decode_string(row, index)
+    }
+}
+
+// Postgres enums decode as their label and domains as their underlying type. Postgres
+// mostly reports domain columns by their base type already, so scalar base types are
+// decoded here too rather than failing decode_text's text type check
+fn decode_postgres(row: &PgRow, index: usize) -> Result<serde_json::Value, sqlx::Error> {
+    decode_postgres_type(row, index, row.column(index).type_info())
+}
+
+fn decode_postgres_type(row: &PgRow, index: usize, type_info: &PgTypeInfo) -> Result<serde_json::Value, sqlx::Error> {
+    match type_info.kind() {
+        PgTypeKind::Enum(_) => return decode_string(row, index),
+        PgTypeKind::Domain(base) => return decode_postgres_type(row, index, base),
+        _ => {}
+    }
+
+    if <String as Type<Postgres>>::compatible(type_info) {
+        decode_string(row, index)
+    } else if <i16 as Type<Postgres>>::compatible(type_info) {
+        decode_unchecked::<i16, _>(row, index)
+    } else if <i32 as Type<Postgres>>::compatible(type_info) {
+        decode_unchecked::<i32, _>(row, index)
+    } else if <i64 as Type<Postgres>>::compatible(type_info) {
+        decode_unchecked::<i64, _>(row, index)
+    } else if <f32 as Type<Postgres>>::compatible(type_info) {
+        decode_unchecked::<f32, _>(row, index)
+    } else if <f64 as Type<Postgres>>::compatible(type_info) {
+        decode_unchecked::<f64, _>(row, index)
+    } else if <bool as Type<Postgres>>::compatible(type_info) {
+        decode_unchecked::<bool, _>(row, index)
+    } else {
+        Ok(decode_text(row, index))
+    }
+}
+
+// Numbers and booleans keep their JSON type, so a text column holding digits still
+// hydrates into a String field. The caller has checked the type
+fn decode_unchecked<T, R>(row: &R, index: usize) -> Result<serde_json::Value, sqlx::Error>
+where
+    R: Row,
+    usize: ColumnIndex<R>,
+    T: Into<serde_json::Value> + for<'r> Decode<'r, R::Database>,
+{
+    Ok(row.try_get_unchecked::<Option<T>, _>(index)?.map_or(serde_json::Value::Null, Into::into))
+}
+
+// Dates and times are rendered as strings. The caller has checked the type
+fn decode_display<T, R>(row: &R, index: usize) -> Result<serde_json::Value, sqlx::Error>
+where
+    R: Row,
+    usize: ColumnIndex<R>,
+    T: ToString + for<'r> Decode<'r, R::Database>,
+{
+    Ok(row.try_get_unchecked::<Option<T>, _>(index)?.map_or(serde_json::Value::Null, |v| serde_json::Value::String(v.to_string())))
+}
+
+// As decode_unchecked for text, moving the decoded string instead of copying it
+fn decode_string<R>(row: &R, index: usize) -> Result<serde_json::Value, sqlx::Error>
+where
+    R: Row,
+    usize: ColumnIndex<R>,
+    for<'r> String: Decode<'r, R::Database>,
+{
+    Ok(row.try_get_unchecked::<Option<String>, _>(index)?.map_or(serde_json::Value::Null, serde_json::Value::String))
+}
+
+fn transaction_finished() -> sqlx::Error {
+    sqlx::Error::Protocol("transaction has already been committed or rolled back".to_string())
+}
+
+// Whether a server version string such as "8.0.36", "10.11.6-MariaDB" or "3.45.1" is at
+// least major.minor
+fn version_at_least(version: &str, major: u32, minor: u32) -> bool {
+    let mut parts = version.split(|c: char| !c.is_ascii_digit()).map(|part| part.parse::<u32>().unwrap_or(0));
+    (parts.next().unwrap_or(0), parts.next().unwrap_or(0)) >= (major, minor)
 }
 
 // MySQL connection
 pub struct MySqlConnection {
     pool: Pool<MySql>,
+    cache_statements: bool,
+    window_functions: bool,
 }
 
 impl MySqlConnection {
     pub async fn new(url: &str) -> Result<Self, sqlx::Error> {
         let pool = sqlx::MySqlPool::connect(url).await?;
-        Ok(Self { pool })
+        let version: String = sqlx::query_scalar("SELECT VERSION()").fetch_one(&pool).await?;
+        let window_functions = match version.contains("MariaDB") {
+            true => version_at_least(&version, 10, 2),
+            false => version_at_least(&version, 8, 0),
+        };
+        Ok(Self { pool, cache_statements: true, window_functions })
+    }
+
+    // Whether statements are prepared once per pooled connection and reused when the same
+    // SQL text runs again (sqlx's statement cache, on by default). Values are inlined, so only
+    // queries with identical text share an entry; turn it off when most SQL is unique, to skip
+    // caching statements that never run twice. Transactions begun here inherit the setting
+    pub fn cache_statements(mut self, enabled: bool) -> Self {
+        self.cache_statements = enabled;
+        self
     }
 }
 
 #[async_trait::async_trait]
 impl DatabaseConnection for MySqlConnection {
+    fn driver(&self) -> Driver {
+        Driver::MySql
+    }
+
     async fn execute(&self, sql: &str) -> Result<u64, sqlx::Error> {
-        let result = sqlx::query(sql).execute(&self.pool).await?;
+        let result = sqlx::query(sql).persistent(self.cache_statements).execute(&self.pool).await?;
+        Ok(result.rows_affected())
+    }
+
+    // A bare &str has no arguments, so sqlx sends it unprepared
+    async fn unprepared(&self, sql: &str) -> Result<u64, sqlx::Error> {
+        let result = self.pool.execute(sql).await?;
         Ok(result.rows_affected())
     }
 
     async fn fetch_one(&self, sql: &str) -> Result<Vec<(String, serde_json::Value)>, sqlx::Error> {
-        let row = sqlx::query(sql).fetch_one(&self.pool).await?;
-        let mut result = Vec::new();
-        
-        for (i, column) in row.columns().iter().enumerate() {
-            let value: Option<String> = row.try_get(i).unwrap_or(None);
-            let json_value = match value {
-                Some(v) => serde_json::Value::String(v),
-                None => serde_json::Value::Null,
-            };
-            result.push((column.name().to_string(), json_value));
-        }
-        
-        Ok(result)
+        let row = sqlx::query(sql).persistent(self.cache_statements).fetch_one(&self.pool).await?;
+        decode_row(&row, decode_mysql)
     }
 
-    async fn fetch_all(&self, sql: &str) -> Result<Vec<Vec<(String, serde_json::Value)>>, sqlx::Error> {
-        let rows = sqlx::query(sql).fetch_all(&self.pool).await?;
-        let mut results = Vec::new();
-        
-        for row in rows {
-            let mut row_data = Vec::new();
-            for (i, column) in row.columns().iter().enumerate() {
-                let value: Option<String> = row.try_get(i).unwrap_or(None);
-                let json_value = match value {
-                    Some(v) => serde_json::Value::String(v),
-                    None => serde_json::Value::Null,
-                };
-                row_data.push((column.name().to_string(), json_value));
-            }
-            results.push(row_data);
-        }
-        
-        Ok(results)
+    async fn fetch_all(&self, sql: &str) -> Result<(Arc<[String]>, Vec<Vec<serde_json::Value>>), sqlx::Error> {
+        let rows = sqlx::query(sql).persistent(self.cache_statements).fetch_all(&self.pool).await?;
+        decode_rows(rows, decode_mysql)
+    }
+
+    async fn begin(&self) -> Result<Box<dyn DatabaseTransaction>, sqlx::Error> {
+        let tx = self.pool.begin().await?;
+        Ok(Box::new(MySqlTransaction {
+            tx: Mutex::new(Some(tx)),
+            cache_statements: self.cache_statements,
+            window_functions: self.window_functions,
+        }))
+    }
+
+    // MySQL applies SET TRANSACTION to the next transaction on the session, so it's
+    // issued on the pooled connection before BEGIN
+    async fn begin_with_isolation(&self, level: IsolationLevel) -> Result<Box<dyn DatabaseTransaction>, sqlx::Error> {
+        let mut conn = self.pool.acquire().await?;
+        sqlx::query(&format!("SET TRANSACTION ISOLATION LEVEL {}", level.sql())).execute(&mut *conn).await?;
+        let tx = Transaction::begin(conn).await?;
+        Ok(Box::new(MySqlTransaction {
+            tx: Mutex::new(Some(tx)),
+            cache_statements: self.cache_statements,
+            window_functions: self.window_functions,
+        }))
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+
+    fn supports_window_functions(&self) -> bool {
+        self.window_functions
+    }
+}
+
+pub struct MySqlTransaction {
+    tx: Mutex<Option<Transaction<'static, MySql>>>,
+    cache_statements: bool,
+    window_functions: bool,
+}
+
+#[async_trait::async_trait]
+impl DatabaseTransaction for MySqlTransaction {
+    async fn execute(&self, sql: &str) -> Result<u64, sqlx::Error> {
+        let mut guard = self.tx.lock().await;
+        let tx = guard.as_mut().ok_or_else(transaction_finished)?;
+        let result = sqlx::query(sql).persistent(self.cache_statements).execute(&mut **tx).await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn unprepared(&self, sql: &str) -> Result<u64, sqlx::Error> {
+        let mut guard = self.tx.lock().await;
+        let tx = guard.as_mut().ok_or_else(transaction_finished)?;
+        let result = tx.execute(sql).await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn fetch_one(&self, sql: &str) -> Result<Vec<(String, serde_json::Value)>, sqlx::Error> {
+        let mut guard = self.tx.lock().await;
+        let tx = guard.as_mut().ok_or_else(transaction_finished)?;
+        let row = sqlx::query(sql).persistent(self.cache_statements).fetch_one(&mut **tx).await?;
+        decode_row(&row, decode_mysql)
+    }
+
+    async fn fetch_all(&self, sql: &str) -> Result<(Arc<[String]>, Vec<Vec<serde_json::Value>>), sqlx::Error> {
+        let mut guard = self.tx.lock().await;
+        let tx = guard.as_mut().ok_or_else(transaction_finished)?;
+        let rows = sqlx::query(sql).persistent(self.cache_statements).fetch_all(&mut **tx).await?;
+        decode_rows(rows, decode_mysql)
+    }
+
+    async fn commit(&self) -> Result<(), sqlx::Error> {
+        let tx = self.tx.lock().await.take().ok_or_else(transaction_finished)?;
+        tx.commit().await
+    }
+
+    async fn rollback(&self) -> Result<(), sqlx::Error> {
+        let tx = self.tx.lock().await.take().ok_or_else(transaction_finished)?;
+        tx.rollback().await
+    }
+
+    fn supports_window_functions(&self) -> bool {
+        self.window_functions
     }
 }
 
@@ -109,6 +619,9 @@ pub struct PostgresConnection {
     pool: Pool<Postgres>,
 }
 
+// Statements are always cached on Postgres. sqlx 0.7 prepares an uncached statement under a
+// name it never closes, and the unprepared simple protocol can't decode custom types such as
+// enums, so there is no cache_statements(false) here
 impl PostgresConnection {
     pub async fn new(url: &str) -> Result<Self, sqlx::Error> {
         let pool = sqlx::PgPool::connect(url).await?;
@@ -118,100 +631,226 @@ impl PostgresConnection {
 
 #[async_trait::async_trait]
 impl DatabaseConnection for PostgresConnection {
+    fn driver(&self) -> Driver {
+        Driver::Postgres
+    }
+
     async fn execute(&self, sql: &str) -> Result<u64, sqlx::Error> {
         let result = sqlx::query(sql).execute(&self.pool).await?;
         Ok(result.rows_affected())
     }
 
+    // A bare &str has no arguments, so sqlx sends it unprepared
+    async fn unprepared(&self, sql: &str) -> Result<u64, sqlx::Error> {
+        let result = self.pool.execute(sql).await?;
+        Ok(result.rows_affected())
+    }
+
     async fn fetch_one(&self, sql: &str) -> Result<Vec<(String, serde_json::Value)>, sqlx::Error> {
         let row = sqlx::query(sql).fetch_one(&self.pool).await?;
-        let mut result = Vec::new();
-        
-        for (i, column) in row.columns().iter().enumerate() {
-            let value: Option<String> = row.try_get(i).unwrap_or(None);
-            let json_value = match value {
-                Some(v) => serde_json::Value::String(v),
-                None => serde_json::Value::Null,
-            };
-            result.push((column.name().to_string(), json_value));
-        }
-        
-        Ok(result)
+        decode_row(&row, decode_postgres)
     }
 
-    async fn fetch_all(&self, sql: &str) -> Result<Vec<Vec<(String, serde_json::Value)>>, sqlx::Error> {
+    async fn fetch_all(&self, sql: &str) -> Result<(Arc<[String]>, Vec<Vec<serde_json::Value>>), sqlx::Error> {
         let rows = sqlx::query(sql).fetch_all(&self.pool).await?;
-        let mut results = Vec::new();
-        
-        for row in rows {
-            let mut row_data = Vec::new();
-            for (i, column) in row.columns().iter().enumerate() {
-                let value: Option<String> = row.try_get(i).unwrap_or(None);
-                let json_value = match value {
-                    Some(v) => serde_json::Value::String(v),
-                    None => serde_json::Value::Null,
-                };
-                row_data.push((column.name().to_string(), json_value));
-            }
-            results.push(row_data);
-        }
-        
-        Ok(results)
+        decode_rows(rows, decode_postgres)
+    }
+
+    async fn begin(&self) -> Result<Box<dyn DatabaseTransaction>, sqlx::Error> {
+        let tx = self.pool.begin().await?;
+        Ok(Box::new(PostgresTransaction { tx: Mutex::new(Some(tx)) }))
+    }
+
+    // Postgres takes SET TRANSACTION as the first statement inside the transaction
+    async fn begin_with_isolation(&self, level: IsolationLevel) -> Result<Box<dyn DatabaseTransaction>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(&format!("SET TRANSACTION ISOLATION LEVEL {}", level.sql())).execute(&mut *tx).await?;
+        Ok(Box::new(PostgresTransaction { tx: Mutex::new(Some(tx)) }))
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+}
+
+pub struct PostgresTransaction {
+    tx: Mutex<Option<Transaction<'static, Postgres>>>,
+}
+
+#[async_trait::async_trait]
+impl DatabaseTransaction for PostgresTransaction {
+    async fn execute(&self, sql: &str) -> Result<u64, sqlx::Error> {
+        let mut guard = self.tx.lock().await;
+        let tx = guard.as_mut().ok_or_else(transaction_finished)?;
+        let result = sqlx::query(sql).execute(&mut **tx).await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn unprepared(&self, sql: &str) -> Result<u64, sqlx::Error> {
+        let mut guard = self.tx.lock().await;
+        let tx = guard.as_mut().ok_or_else(transaction_finished)?;
+        let result = tx.execute(sql).await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn fetch_one(&self, sql: &str) -> Result<Vec<(String, serde_json::Value)>, sqlx::Error> {
+        let mut guard = self.tx.lock().await;
+        let tx = guard.as_mut().ok_or_else(transaction_finished)?;
+        let row = sqlx::query(sql).fetch_one(&mut **tx).await?;
+        decode_row(&row, decode_postgres)
+    }
+
+    async fn fetch_all(&self, sql: &str) -> Result<(Arc<[String]>, Vec<Vec<serde_json::Value>>), sqlx::Error> {
+        let mut guard = self.tx.lock().await;
+        let tx = guard.as_mut().ok_or_else(transaction_finished)?;
+        let rows = sqlx::query(sql).fetch_all(&mut **tx).await?;
+        decode_rows(rows, decode_postgres)
+    }
+
+    async fn commit(&self) -> Result<(), sqlx::Error> {
+        let tx = self.tx.lock().await.take().ok_or_else(transaction_finished)?;
+        tx.commit().await
+    }
+
+    async fn rollback(&self) -> Result<(), sqlx::Error> {
+        let tx = self.tx.lock().await.take().ok_or_else(transaction_finished)?;
+        tx.rollback().await
     }
 }
 
 // SQLite connection
 pub struct SqliteConnection {
     pool: Pool<Sqlite>,
+    cache_statements: bool,
+    window_functions: bool,
 }
 
 impl SqliteConnection {
     pub async fn new(url: &str) -> Result<Self, sqlx::Error> {
         let pool = sqlx::SqlitePool::connect(url).await?;
-        Ok(Self { pool })
+        let version: String = sqlx::query_scalar("SELECT sqlite_version()").fetch_one(&pool).await?;
+        Ok(Self { pool, cache_statements: true, window_functions: version_at_least(&version, 3, 25) })
+    }
+
+    // As MySqlConnection::cache_statements
+    pub fn cache_statements(mut self, enabled: bool) -> Self {
+        self.cache_statements = enabled;
+        self
     }
 }
 
 #[async_trait::async_trait]
 impl DatabaseConnection for SqliteConnection {
+    fn driver(&self) -> Driver {
+        Driver::Sqlite
+    }
+
     async fn execute(&self, sql: &str) -> Result<u64, sqlx::Error> {
-        let result = sqlx::query(sql).execute(&self.pool).await?;
+        let result = sqlx::query(sql).persistent(self.cache_statements).execute(&self.pool).await?;
+        Ok(result.rows_affected())
+    }
+
+    // A bare &str has no arguments, so sqlx sends it unprepared
+    async fn unprepared(&self, sql: &str) -> Result<u64, sqlx::Error> {
+        let result = self.pool.execute(sql).await?;
         Ok(result.rows_affected())
     }
 
     async fn fetch_one(&self, sql: &str) -> Result<Vec<(String, serde_json::Value)>, sqlx::Error> {
-        let row = sqlx::query(sql).fetch_one(&self.pool).await?;
-        let mut result = Vec::new();
-        
-        for (i, column) in row.columns().iter().enumerate() {
-            let value: Option<String> = row.try_get(i).unwrap_or(None);
-            let json_value = match value {
-                Some(v) => serde_json::Value::String(v),
-                None => serde_json::Value::Null,
-            };
-            result.push((column.name().to_string(), json_value));
-        }
-        
-        Ok(result)
+        let row = sqlx::query(sql).persistent(self.cache_statements).fetch_one(&self.pool).await?;
+        decode_row(&row, decode_sqlite)
     }
 
-    async fn fetch_all(&self, sql: &str) -> Result<Vec<Vec<(String, serde_json::Value)>>, sqlx::Error> {
-        let rows = sqlx::query(sql).fetch_all(&self.pool).await?;
-        let mut results = Vec::new();
-        
-        for row in rows {
-            let mut row_data = Vec::new();
-            for (i, column) in row.columns().iter().enumerate() {
-                let value: Option<String> = row.try_get(i).unwrap_or(None);
-                let json_value = match value {
-                    Some(v) => serde_json::Value::String(v),
-                    None => serde_json::Value::Null,
-                };
-                row_data.push((column.name().to_string(), json_value));
-            }
-            results.push(row_data);
-        }
-        
-        Ok(results)
+    async fn fetch_all(&self, sql: &str) -> Result<(Arc<[String]>, Vec<Vec<serde_json::Value>>), sqlx::Error> {
+        let rows = sqlx::query(sql).persistent(self.cache_statements).fetch_all(&self.pool).await?;
+        decode_rows(rows, decode_sqlite)
+    }
+
+    async fn begin(&self) -> Result<Box<dyn DatabaseTransaction>, sqlx::Error> {
+        let tx = self.pool.begin().await?;
+        Ok(Box::new(SqliteTransaction {
+            tx: Mutex::new(Some(tx)),
+            cache_statements: self.cache_statements,
+            window_functions: self.window_functions,
+        }))
+    }
+
+    // SQLite is always serializable
+    async fn begin_with_isolation(&self, _level: IsolationLevel) -> Result<Box<dyn DatabaseTransaction>, sqlx::Error> {
+        self.begin().await
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+
+    fn supports_window_functions(&self) -> bool {
+        self.window_functions
+    }
+}
+
+pub struct SqliteTransaction {
+    tx: Mutex<Option<Transaction<'static, Sqlite>>>,
+    cache_statements: bool,
+    window_functions: bool,
+}
+
+#[async_trait::async_trait]
+impl DatabaseTransaction for SqliteTransaction {
+    async fn execute(&self, sql: &str) -> Result<u64, sqlx::Error> {
+        let mut guard = self.tx.lock().await;
+        let tx = guard.as_mut().ok_or_else(transaction_finished)?;
+        let result = sqlx::query(sql).persistent(self.cache_statements).execute(&mut **tx).await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn unprepared(&self, sql: &str) -> Result<u64, sqlx::Error> {
+        let mut guard = self.tx.lock().await;
+        let tx = guard.as_mut().ok_or_else(transaction_finished)?;
+        let result = tx.execute(sql).await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn fetch_one(&self, sql: &str) -> Result<Vec<(String, serde_json::Value)>, sqlx::Error> {
+        let mut guard = self.tx.lock().await;
+        let tx = guard.as_mut().ok_or_else(transaction_finished)?;
+        let row = sqlx::query(sql).persistent(self.cache_statements).fetch_one(&mut **tx).await?;
+        decode_row(&row, decode_sqlite)
+    }
+
+    async fn fetch_all(&self, sql: &str) -> Result<(Arc<[String]>, Vec<Vec<serde_json::Value>>), sqlx::Error> {
+        let mut guard = self.tx.lock().await;
+        let tx = guard.as_mut().ok_or_else(transaction_finished)?;
+        let rows = sqlx::query(sql).persistent(self.cache_statements).fetch_all(&mut **tx).await?;
+        decode_rows(rows, decode_sqlite)
+    }
+
+    async fn commit(&self) -> Result<(), sqlx::Error> {
+        let tx = self.tx.lock().await.take().ok_or_else(transaction_finished)?;
+        tx.commit().await
+    }
+
+    async fn rollback(&self) -> Result<(), sqlx::Error> {
+        let tx = self.tx.lock().await.take().ok_or_else(transaction_finished)?;
+        tx.rollback().await
+    }
+
+    fn supports_window_functions(&self) -> bool {
+        self.window_functions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_compare_by_major_then_minor() {
+        assert!(version_at_least("8.0.36", 8, 0));
+        assert!(version_at_least("10.11.6-MariaDB-log", 10, 2));
+        assert!(version_at_least("3.45.1", 3, 25));
+        assert!(!version_at_least("5.7.44-log", 8, 0));
+        assert!(!version_at_least("10.1.48-MariaDB", 10, 2));
+        assert!(!version_at_least("3.24.0", 3, 25));
     }
 }
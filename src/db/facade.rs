@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use serde_json::Value;
+use crate::db::connection::{driver_for, ConnectionManager, DatabaseConnection, DatabaseTransaction};
+use crate::db::transaction::Executor;
+use crate::orm::dynamic::DynamicModel;
+use crate::orm::query::{Query, RawExpr};
+
+// Model-free access to a connection - similar to Laravel's DB facade
+#[derive(Clone)]
+pub struct DB {
+    manager: Arc<ConnectionManager>,
+    connection: Option<String>,
+}
+
+impl DB {
+    pub fn new(manager: Arc<ConnectionManager>) -> Self {
+        Self { manager, connection: None }
+    }
+
+    // The same facade, running against the named connection instead of the default
+    pub fn connection(&self, name: &str) -> Self {
+        Self {
+            manager: self.manager.clone(),
+            connection: Some(name.to_string()),
+        }
+    }
+
+    // Unescaped SQL for select, order_by and comparisons, e.g. DB::raw("COUNT(*) AS c")
+    pub fn raw(sql: &str) -> RawExpr {
+        crate::orm::query::raw(sql)
+    }
+
+    fn connection_name(&self) -> &str {
+        self.connection.as_deref().unwrap_or(self.manager.default_connection())
+    }
+
+    // Query builder for a table without a model struct; rows come back as DynamicModel,
+    // whose attributes are the column => value map
+    pub fn table(&self, table: &str) -> Query<DynamicModel> {
+        DynamicModel::query_table(table).driver(driver_for(self.connection_name()))
+    }
+
+    // Run a raw SELECT, returning each row as a column => value map
+    pub async fn select(&self, sql: &str) -> Result<Vec<HashMap<String, Value>>, sqlx::Error> {
+        let (columns, rows) = self.executor().await?.fetch_all(sql).await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| columns.iter().cloned().zip(row).collect())
+            .collect())
+    }
+
+    // Run a raw statement, returning the affected row count
+    pub async fn statement(&self, sql: &str) -> Result<u64, sqlx::Error> {
+        self.executor().await?.execute(sql).await
+    }
+
+    pub async fn transaction<F, Fut, R>(&self, callback: F) -> Result<R, sqlx::Error>
+    where
+        F: FnOnce(Arc<dyn DatabaseTransaction>) -> Fut,
+        Fut: Future<Output = Result<R, sqlx::Error>>,
+    {
+        self.manager.transaction(Some(self.connection_name()), callback).await
+    }
+
+    // Inside a RustEloquent::transaction on this connection, statements join it
+    async fn executor(&self) -> Result<Executor, sqlx::Error> {
+        Ok(Executor::for_connection(&self.get_connection().await?))
+    }
+
+    async fn get_connection(&self) -> Result<Arc<dyn DatabaseConnection>, sqlx::Error> {
+        let name = self.connection_name();
+        self.manager.get_connection(Some(name)).await.ok_or_else(|| {
+            sqlx::Error::Configuration(format!("Database connection [{}] not configured", name).into())
+        })
+    }
+}
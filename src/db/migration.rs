@@ -0,0 +1,162 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::db::connection::{DatabaseConnection, DatabaseTransaction, Driver};
+use crate::db::schema::Schema;
+
+const MIGRATIONS_TABLE: &str = "migrations";
+
+// A reversible schema change; the name is what gets recorded in the migrations table
+#[async_trait]
+pub trait Migration: Send + Sync {
+    fn name(&self) -> &str;
+    async fn up(&self, schema: &Schema) -> Result<(), sqlx::Error>;
+    async fn down(&self, schema: &Schema) -> Result<(), sqlx::Error>;
+}
+
+// Runs migrations in the order they were registered, recording each in a batch
+pub struct Migrator {
+    connection: Arc<dyn DatabaseConnection>,
+    migrations: Vec<Box<dyn Migration>>,
+}
+
+impl Migrator {
+    pub fn new(connection: Arc<dyn DatabaseConnection>) -> Self {
+        Self {
+            connection,
+            migrations: Vec::new(),
+        }
+    }
+
+    pub fn register<M: Migration + 'static>(mut self, migration: M) -> Self {
+        self.migrations.push(Box::new(migration));
+        self
+    }
+
+    // Names of registered migrations that haven't been run yet
+    pub async fn pending(&self) -> Result<Vec<String>, sqlx::Error> {
+        self.ensure_table().await?;
+        let ran: Vec<String> = self.ran().await?.into_iter().map(|(name, _)| name).collect();
+        Ok(self.migrations.iter()
+            .map(|migration| migration.name().to_string())
+            .filter(|name| !ran.contains(name))
+            .collect())
+    }
+
+    // Run every pending migration as one new batch, returning the names that ran.
+    // A failing migration is rolled back (where the driver has transactional DDL) and stops
+    // the batch; migrations before it in the batch stay applied
+    pub async fn run(&self) -> Result<Vec<String>, sqlx::Error> {
+        let pending = self.pending().await?;
+        if pending.is_empty() {
+            return Ok(pending);
+        }
+
+        let batch = self.ran().await?.iter().map(|(_, batch)| *batch).max().unwrap_or(0) + 1;
+        for name in &pending {
+            let migration = self.find(name)?;
+            let record = format!(
+                "INSERT INTO {} (migration, batch) VALUES ('{}', {})",
+                MIGRATIONS_TABLE, escape(name), batch
+            );
+            self.apply(migration, true, &record).await?;
+        }
+        Ok(pending)
+    }
+
+    // Undo the most recent batch in reverse order, returning the names that were rolled back
+    pub async fn rollback(&self) -> Result<Vec<String>, sqlx::Error> {
+        self.ensure_table().await?;
+        let ran = self.ran().await?;
+        let Some(last_batch) = ran.iter().map(|(_, batch)| *batch).max() else {
+            return Ok(Vec::new());
+        };
+
+        let mut rolled_back = Vec::new();
+        for (name, _) in ran.iter().rev().filter(|(_, batch)| *batch == last_batch) {
+            let migration = self.find(name)?;
+            let record = format!("DELETE FROM {} WHERE migration = '{}'", MIGRATIONS_TABLE, escape(name));
+            self.apply(migration, false, &record).await?;
+            rolled_back.push(name.clone());
+        }
+        Ok(rolled_back)
+    }
+
+    fn find(&self, name: &str) -> Result<&dyn Migration, sqlx::Error> {
+        self.migrations.iter()
+            .find(|migration| migration.name() == name)
+            .map(|migration| migration.as_ref())
+            .ok_or_else(|| sqlx::Error::Protocol(format!("Migration not found: {}", name)))
+    }
+
+    // Run up or down plus its bookkeeping statement, together in a transaction when
+    // the driver supports it. MySQL commits implicitly on DDL, so it runs without one
+    async fn apply(&self, migration: &dyn Migration, up: bool, record: &str) -> Result<(), sqlx::Error> {
+        let driver = self.connection.driver();
+        if driver == Driver::MySql {
+            let schema = Schema::new(self.connection.clone());
+            run_migration(migration, &schema, up).await?;
+            self.connection.execute(record).await?;
+            return Ok(());
+        }
+
+        let transaction: Arc<dyn DatabaseTransaction> = Arc::from(self.connection.begin().await?);
+        let schema = Schema::in_transaction(transaction.clone(), driver);
+        let result = match run_migration(migration, &schema, up).await {
+            Ok(()) => transaction.execute(record).await.map(|_| ()),
+            Err(error) => Err(error),
+        };
+        match result {
+            Ok(()) => transaction.commit().await,
+            Err(error) => {
+                transaction.rollback().await?;
+                Err(error)
+            }
+        }
+    }
+
+    async fn ensure_table(&self) -> Result<(), sqlx::Error> {
+        Schema::new(self.connection.clone())
+            .create_if_not_exists(MIGRATIONS_TABLE, |table| {
+                table.id();
+                table.string("migration");
+                table.integer("batch");
+            })
+            .await
+    }
+
+    // (name, batch) for every recorded migration, oldest first
+    async fn ran(&self) -> Result<Vec<(String, i64)>, sqlx::Error> {
+        // Rows decode as strings, so the batch number is cast on the way out
+        let text = if self.connection.driver() == Driver::MySql { "CHAR" } else { "TEXT" };
+        let sql = format!(
+            "SELECT migration, CAST(batch AS {}) FROM {} ORDER BY batch, id",
+            text, MIGRATIONS_TABLE
+        );
+        let (_, rows) = self.connection.fetch_all(&sql).await?;
+        // A row that doesn't decode is an error rather than skipped, as skipping it would
+        // make its migration look pending and run it again
+        rows.into_iter()
+            .map(|row| {
+                let name = row.first().and_then(|name| name.as_str()).ok_or_else(|| undecodable("migration", &row))?;
+                let batch = row.get(1).and_then(|batch| batch.as_str()?.parse().ok()).ok_or_else(|| undecodable("batch", &row))?;
+                Ok((name.to_string(), batch))
+            })
+            .collect()
+    }
+}
+
+async fn run_migration(migration: &dyn Migration, schema: &Schema, up: bool) -> Result<(), sqlx::Error> {
+    if up {
+        migration.up(schema).await
+    } else {
+        migration.down(schema).await
+    }
+}
+
+fn undecodable(column: &str, row: &[serde_json::Value]) -> sqlx::Error {
+    sqlx::Error::Decode(format!("{} has an unreadable [{}] value: {:?}", MIGRATIONS_TABLE, column, row).into())
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\'', "''")
+}
@@ -1,2 +1,5 @@
 pub mod connection;
-
+pub mod facade;
+pub mod migration;
+pub mod schema;
+pub mod transaction;
@@ -0,0 +1,324 @@
+use std::sync::Arc;
+use serde_json::Value;
+use crate::db::connection::{DatabaseConnection, DatabaseTransaction, Driver};
+
+// Where the DDL runs; migrations pass a transaction on drivers with transactional DDL
+enum Executor {
+    Connection(Arc<dyn DatabaseConnection>),
+    Transaction(Arc<dyn DatabaseTransaction>),
+}
+
+// Table DDL built from a Blueprint, rendered for the connection's driver
+pub struct Schema {
+    executor: Executor,
+    driver: Driver,
+}
+
+impl Schema {
+    pub fn new(connection: Arc<dyn DatabaseConnection>) -> Self {
+        let driver = connection.driver();
+        Self { executor: Executor::Connection(connection), driver }
+    }
+
+    pub(crate) fn in_transaction(transaction: Arc<dyn DatabaseTransaction>, driver: Driver) -> Self {
+        Self { executor: Executor::Transaction(transaction), driver }
+    }
+
+    pub fn driver(&self) -> Driver {
+        self.driver
+    }
+
+    // Run a raw statement, e.g. for DDL the builder doesn't cover
+    pub async fn statement(&self, sql: &str) -> Result<u64, sqlx::Error> {
+        match &self.executor {
+            Executor::Connection(connection) => connection.execute(sql).await,
+            Executor::Transaction(transaction) => transaction.execute(sql).await,
+        }
+    }
+
+    // Raw DDL for what the builder doesn't cover: triggers, extensions, custom indexes.
+    // It runs unprepared, so it may hold several statements. Inside a migration it runs in
+    // the migration's transaction, so on Postgres and SQLite it is rolled back with the rest
+    // of a failed migration; MySQL commits DDL implicitly
+    pub async fn raw(&self, sql: &str) -> Result<(), sqlx::Error> {
+        match &self.executor {
+            Executor::Connection(connection) => connection.unprepared(sql).await.map(|_| ()),
+            Executor::Transaction(transaction) => transaction.unprepared(sql).await.map(|_| ()),
+        }
+    }
+
+    pub async fn create<F>(&self, table: &str, build: F) -> Result<(), sqlx::Error>
+    where
+        F: FnOnce(&mut Blueprint),
+    {
+        let mut blueprint = Blueprint::new(table);
+        build(&mut blueprint);
+        self.statement(&blueprint.to_create_sql(self.driver)).await?;
+        Ok(())
+    }
+
+    pub async fn create_if_not_exists<F>(&self, table: &str, build: F) -> Result<(), sqlx::Error>
+    where
+        F: FnOnce(&mut Blueprint),
+    {
+        let mut blueprint = Blueprint::new(table);
+        build(&mut blueprint);
+        self.statement(&blueprint.create_sql(self.driver, true)).await?;
+        Ok(())
+    }
+
+    // Add or drop columns on an existing table
+    pub async fn table<F>(&self, table: &str, build: F) -> Result<(), sqlx::Error>
+    where
+        F: FnOnce(&mut Blueprint),
+    {
+        let mut blueprint = Blueprint::new(table);
+        build(&mut blueprint);
+        for sql in blueprint.to_alter_sql(self.driver)? {
+            self.statement(&sql).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn drop(&self, table: &str) -> Result<(), sqlx::Error> {
+        self.statement(&format!("DROP TABLE {}", table)).await?;
+        Ok(())
+    }
+
+    pub async fn drop_if_exists(&self, table: &str) -> Result<(), sqlx::Error> {
+        self.statement(&format!("DROP TABLE IF EXISTS {}", table)).await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Id, // Auto-incrementing big integer primary key
+    String(u32),
+    Text,
+    Integer,
+    BigInteger,
+    Boolean,
+    Timestamp,
+}
+
+#[derive(Debug, Clone)]
+pub struct ColumnDefinition {
+    pub name: String,
+    pub column_type: ColumnType,
+    pub nullable: bool,
+    pub default: Option<Value>,
+    pub unique: bool,
+}
+
+impl ColumnDefinition {
+    pub fn nullable(&mut self) -> &mut Self {
+        self.nullable = true;
+        self
+    }
+
+    pub fn default(&mut self, value: Value) -> &mut Self {
+        self.default = Some(value);
+        self
+    }
+
+    pub fn unique(&mut self) -> &mut Self {
+        self.unique = true;
+        self
+    }
+
+    fn to_sql(&self, driver: Driver) -> String {
+        let column_type = match (self.column_type, driver) {
+            (ColumnType::Id, Driver::MySql) => "BIGINT UNSIGNED AUTO_INCREMENT PRIMARY KEY".to_string(),
+            (ColumnType::Id, Driver::Postgres) => "BIGSERIAL PRIMARY KEY".to_string(),
+            (ColumnType::Id, Driver::Sqlite) => "INTEGER PRIMARY KEY AUTOINCREMENT".to_string(),
+            (ColumnType::String(length), _) => format!("VARCHAR({})", length),
+            (ColumnType::Text, _) => "TEXT".to_string(),
+            (ColumnType::Integer, Driver::MySql) => "INT".to_string(),
+            (ColumnType::Integer, _) => "INTEGER".to_string(),
+            (ColumnType::BigInteger, Driver::Sqlite) => "INTEGER".to_string(),
+            (ColumnType::BigInteger, _) => "BIGINT".to_string(),
+            (ColumnType::Boolean, Driver::MySql) => "TINYINT(1)".to_string(),
+            (ColumnType::Boolean, Driver::Postgres) => "BOOLEAN".to_string(),
+            (ColumnType::Boolean, Driver::Sqlite) => "INTEGER".to_string(),
+            (ColumnType::Timestamp, Driver::Sqlite) => "DATETIME".to_string(),
+            (ColumnType::Timestamp, _) => "TIMESTAMP".to_string(),
+        };
+
+        let mut sql = format!("{} {}", self.name, column_type);
+        if self.column_type != ColumnType::Id {
+            sql.push_str(if self.nullable { " NULL" } else { " NOT NULL" });
+        }
+        if let Some(default) = &self.default {
+            sql.push_str(&format!(" DEFAULT {}", default_value(default, driver)));
+        }
+        if self.unique {
+            sql.push_str(" UNIQUE");
+        }
+        sql
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ForeignKeyDefinition {
+    pub column: String,
+    pub references: String,
+    pub on: String,
+    pub on_delete: Option<String>,
+}
+
+impl ForeignKeyDefinition {
+    pub fn references(&mut self, column: &str) -> &mut Self {
+        self.references = column.to_string();
+        self
+    }
+
+    pub fn on(&mut self, table: &str) -> &mut Self {
+        self.on = table.to_string();
+        self
+    }
+
+    // e.g. "CASCADE", "SET NULL"
+    pub fn on_delete(&mut self, action: &str) -> &mut Self {
+        self.on_delete = Some(action.to_string());
+        self
+    }
+
+    fn to_sql(&self) -> String {
+        let mut sql = format!("FOREIGN KEY ({}) REFERENCES {}({})", self.column, self.on, self.references);
+        if let Some(action) = &self.on_delete {
+            sql.push_str(&format!(" ON DELETE {}", action));
+        }
+        sql
+    }
+}
+
+// Columns and keys for a table, like Laravel's Blueprint
+#[derive(Debug, Clone)]
+pub struct Blueprint {
+    table: String,
+    columns: Vec<ColumnDefinition>,
+    foreign_keys: Vec<ForeignKeyDefinition>,
+    drop_columns: Vec<String>,
+}
+
+impl Blueprint {
+    pub fn new(table: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            columns: Vec::new(),
+            foreign_keys: Vec::new(),
+            drop_columns: Vec::new(),
+        }
+    }
+
+    pub fn id(&mut self) -> &mut ColumnDefinition {
+        self.column("id", ColumnType::Id)
+    }
+
+    pub fn string(&mut self, name: &str) -> &mut ColumnDefinition {
+        self.column(name, ColumnType::String(255))
+    }
+
+    pub fn string_with_length(&mut self, name: &str, length: u32) -> &mut ColumnDefinition {
+        self.column(name, ColumnType::String(length))
+    }
+
+    pub fn text(&mut self, name: &str) -> &mut ColumnDefinition {
+        self.column(name, ColumnType::Text)
+    }
+
+    pub fn integer(&mut self, name: &str) -> &mut ColumnDefinition {
+        self.column(name, ColumnType::Integer)
+    }
+
+    pub fn big_integer(&mut self, name: &str) -> &mut ColumnDefinition {
+        self.column(name, ColumnType::BigInteger)
+    }
+
+    pub fn boolean(&mut self, name: &str) -> &mut ColumnDefinition {
+        self.column(name, ColumnType::Boolean)
+    }
+
+    pub fn timestamp(&mut self, name: &str) -> &mut ColumnDefinition {
+        self.column(name, ColumnType::Timestamp)
+    }
+
+    // Nullable created_at and updated_at
+    pub fn timestamps(&mut self) {
+        self.timestamp("created_at").nullable();
+        self.timestamp("updated_at").nullable();
+    }
+
+    pub fn soft_deletes(&mut self) {
+        self.timestamp("deleted_at").nullable();
+    }
+
+    // Foreign key constraint on an existing column; defaults to referencing `id`
+    pub fn foreign(&mut self, column: &str) -> &mut ForeignKeyDefinition {
+        self.foreign_keys.push(ForeignKeyDefinition {
+            column: column.to_string(),
+            references: "id".to_string(),
+            on: String::new(),
+            on_delete: None,
+        });
+        self.foreign_keys.last_mut().expect("just pushed")
+    }
+
+    // Only used by Schema::table
+    pub fn drop_column(&mut self, name: &str) {
+        self.drop_columns.push(name.to_string());
+    }
+
+    fn column(&mut self, name: &str, column_type: ColumnType) -> &mut ColumnDefinition {
+        self.columns.push(ColumnDefinition {
+            name: name.to_string(),
+            column_type,
+            nullable: false,
+            default: None,
+            unique: false,
+        });
+        self.columns.last_mut().expect("just pushed")
+    }
+
+    pub fn to_create_sql(&self, driver: Driver) -> String {
+        self.create_sql(driver, false)
+    }
+
+    fn create_sql(&self, driver: Driver, if_not_exists: bool) -> String {
+        let definitions: Vec<String> = self.columns.iter()
+            .map(|column| column.to_sql(driver))
+            .chain(self.foreign_keys.iter().map(ForeignKeyDefinition::to_sql))
+            .collect();
+        let create = if if_not_exists { "CREATE TABLE IF NOT EXISTS" } else { "CREATE TABLE" };
+        format!("{} {} ({})", create, self.table, definitions.join(", "))
+    }
+
+    // One statement per change, since SQLite only accepts a single change per ALTER TABLE.
+    // SQLite can't add foreign keys to an existing table, so that is an error there
+    pub fn to_alter_sql(&self, driver: Driver) -> Result<Vec<String>, sqlx::Error> {
+        if driver == Driver::Sqlite && !self.foreign_keys.is_empty() {
+            return Err(sqlx::Error::Protocol(format!(
+                "SQLite cannot add foreign keys to existing table [{}]", self.table
+            )));
+        }
+
+        let added = self.columns.iter()
+            .map(|column| format!("ALTER TABLE {} ADD COLUMN {}", self.table, column.to_sql(driver)));
+        let dropped = self.drop_columns.iter()
+            .map(|column| format!("ALTER TABLE {} DROP COLUMN {}", self.table, column));
+        let foreign = self.foreign_keys.iter()
+            .map(|foreign| format!("ALTER TABLE {} ADD {}", self.table, foreign.to_sql()));
+        Ok(added.chain(dropped).chain(foreign).collect())
+    }
+}
+
+fn default_value(value: &Value, driver: Driver) -> String {
+    match value {
+        Value::String(s) => format!("'{}'", s.replace('\'', "''")),
+        Value::Bool(b) if driver == Driver::Postgres => b.to_string(),
+        Value::Bool(b) => if *b { "1" } else { "0" }.to_string(),
+        Value::Number(n) => n.to_string(),
+        _ => "NULL".to_string(),
+    }
+}
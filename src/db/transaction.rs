@@ -0,0 +1,131 @@
+use std::future::Future;
+use std::sync::Arc;
+use crate::db::connection::{registered_connection, run_transaction, DatabaseConnection, DatabaseTransaction, Listeners};
+use crate::orm::error::EloquentError;
+
+// A transaction opened by transaction()/transaction_on(); depth counts the savepoints
+// nested calls have set inside it
+#[derive(Clone)]
+struct OpenTransaction {
+    name: String,
+    connection: Arc<dyn DatabaseConnection>,
+    transaction: Arc<dyn DatabaseTransaction>,
+    depth: usize,
+}
+
+tokio::task_local! {
+    // The transactions the current task's closure is running in, innermost last
+    static OPEN_TRANSACTIONS: Vec<OpenTransaction>;
+}
+
+// Run the closure in a transaction on the "default" connection (the one Model::connection()
+// names unless overridden), committing on Ok and rolling back on Err. The connection must
+// have been registered with a ConnectionManager, whose listeners get the transaction events
+pub async fn transaction<F, Fut, R>(callback: F) -> Result<R, sqlx::Error>
+where
+    F: FnOnce(Arc<dyn DatabaseTransaction>) -> Fut,
+    Fut: Future<Output = Result<R, sqlx::Error>>,
+{
+    transaction_on("default", callback).await
+}
+
+// As transaction, on the named connection. Called while a transaction on the same
+// connection is already open, it runs inside that one under a savepoint: Err rolls back
+// to the savepoint only, and nothing is committed until the outermost closure returns Ok
+pub async fn transaction_on<F, Fut, R>(name: &str, callback: F) -> Result<R, sqlx::Error>
+where
+    F: FnOnce(Arc<dyn DatabaseTransaction>) -> Fut,
+    Fut: Future<Output = Result<R, sqlx::Error>>,
+{
+    let mut open = open_transactions();
+    if let Some(outer) = open.iter().rev().find(|open| open.name == name).cloned() {
+        let savepoint = format!("trans{}", outer.depth + 1);
+        let transaction = outer.transaction.clone();
+        transaction.unprepared(&format!("SAVEPOINT {}", savepoint)).await?;
+        open.push(OpenTransaction { depth: outer.depth + 1, ..outer });
+
+        let body = OPEN_TRANSACTIONS.scope(open, callback(transaction.clone()));
+        return run_transaction(&Listeners::for_name(name), name, &transaction, Some(&savepoint), body).await;
+    }
+
+    let connection = registered_connection(name).ok_or_else(|| {
+        sqlx::Error::Configuration(format!("Database connection [{}] not configured", name).into())
+    })?;
+    let transaction: Arc<dyn DatabaseTransaction> = Arc::from(connection.begin().await?);
+    open.push(OpenTransaction {
+        name: name.to_string(),
+        connection,
+        transaction: transaction.clone(),
+        depth: 0,
+    });
+
+    let body = OPEN_TRANSACTIONS.scope(open, callback(transaction.clone()));
+    run_transaction(&Listeners::for_name(name), name, &transaction, None, body).await
+}
+
+// The innermost transaction the current task is running in, if any. Only the task that
+// called transaction() sees it; tasks it spawns don't
+pub fn current_transaction() -> Option<Arc<dyn DatabaseTransaction>> {
+    open_transactions().pop().map(|open| open.transaction)
+}
+
+fn open_transactions() -> Vec<OpenTransaction> {
+    OPEN_TRANSACTIONS.try_with(Clone::clone).unwrap_or_default()
+}
+
+// Where a statement for a connection runs: the task's open transaction on it, if any,
+// otherwise the connection itself
+pub(crate) enum Executor {
+    Connection(Arc<dyn DatabaseConnection>),
+    Transaction(Arc<dyn DatabaseTransaction>),
+}
+
+impl Executor {
+    pub(crate) fn for_connection(connection: &Arc<dyn DatabaseConnection>) -> Self {
+        // Compared by data pointer, as vtable pointers for the same type can differ
+        let target = Arc::as_ptr(connection) as *const ();
+        open_transactions().into_iter().rev()
+            .find(|open| Arc::as_ptr(&open.connection) as *const () == target)
+            .map_or_else(|| Executor::Connection(connection.clone()), |open| Executor::Transaction(open.transaction))
+    }
+
+    // The task's open transaction on the named connection, otherwise the connection's pool
+    // as registered by ConnectionManager. Fails with EloquentError::Configuration when
+    // nothing is registered under the name
+    pub(crate) fn for_name(name: &str) -> Result<Self, sqlx::Error> {
+        if let Some(open) = open_transactions().into_iter().rev().find(|open| open.name == name) {
+            return Ok(Executor::Transaction(open.transaction));
+        }
+        registered_connection(name).map(Executor::Connection).ok_or_else(|| {
+            EloquentError::Configuration(format!("no connection registered as [{}]", name)).into_sqlx()
+        })
+    }
+
+    pub(crate) async fn execute(&self, sql: &str) -> Result<u64, sqlx::Error> {
+        match self {
+            Executor::Connection(connection) => connection.execute(sql).await,
+            Executor::Transaction(transaction) => transaction.execute(sql).await,
+        }
+    }
+
+    pub(crate) fn supports_window_functions(&self) -> bool {
+        match self {
+            Executor::Connection(connection) => connection.supports_window_functions(),
+            Executor::Transaction(transaction) => transaction.supports_window_functions(),
+        }
+    }
+
+    pub(crate) async fn fetch_one(&self, sql: &str) -> Result<Vec<(String, serde_json::Value)>, sqlx::Error> {
+        match self {
+            Executor::Connection(connection) => connection.fetch_one(sql).await,
+            Executor::Transaction(transaction) => transaction.fetch_one(sql).await,
+        }
+    }
+
+    pub(crate) async fn fetch_all(&self, sql: &str) -> Result<(Arc<[String]>, Vec<Vec<serde_json::Value>>), sqlx::Error> {
+        match self {
+            Executor::Connection(connection) => connection.fetch_all(sql).await,
+            Executor::Transaction(transaction) => transaction.fetch_all(sql).await,
+        }
+    }
+}
@@ -1,3 +1,12 @@
+#![allow(non_snake_case)]
+
 pub mod orm;
 pub mod db;
 
+pub use db::facade::DB;
+pub use orm::query::prevent_destructive_without_where;
+pub use db::transaction::{current_transaction, transaction, transaction_on};
+
+pub use orm::diagnostics::{detect_n_plus_one, detect_n_plus_one_with, n_plus_one_scope, reset_n_plus_one, NPlusOneAction};
+pub use orm::diagnostics::{log_queries_to, log_queries_to_writer, stop_logging_queries};
+pub use orm::diagnostics::{validate_columns, load_table_columns, register_table_columns};
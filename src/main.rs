@@ -1,5 +1,4 @@
-mod orm;
-mod db;
+#![allow(non_snake_case)]
 
 use RustEloquent::orm::{Model, Eloquent};
 use serde::{Deserialize, Serialize};
@@ -0,0 +1,67 @@
+use serde::Serialize;
+use serde_json::Value;
+use crate::orm::query::{Join, OrderBy, WhereCondition};
+
+// Structured description of a query, for tooling that wants data instead of SQL text
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryAst {
+    pub table: String,
+    pub select: Vec<String>,
+    pub distinct: bool,
+    pub joins: Vec<Join>,
+    pub wheres: Option<ConditionNode>,
+    pub group_by: Vec<String>,
+    pub having: Option<ConditionNode>,
+    pub order_by: Vec<OrderBy>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// Condition tree - AND binds tighter than OR, matching how the SQL is evaluated
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionNode {
+    And(Vec<ConditionNode>),
+    Or(Vec<ConditionNode>),
+    Predicate {
+        column: String,
+        operator: String,
+        value: Value,
+    },
+}
+
+impl ConditionNode {
+    // Build a tree from the flat condition list kept by the query builder
+    pub fn from_conditions(conditions: &[WhereCondition]) -> Option<Self> {
+        let mut groups: Vec<Vec<ConditionNode>> = Vec::new();
+        for (i, condition) in conditions.iter().enumerate() {
+            if i == 0 || condition.boolean == "OR" {
+                groups.push(Vec::new());
+            }
+            if let Some(group) = groups.last_mut() {
+                group.push(ConditionNode::Predicate {
+                    column: condition.column.clone(),
+                    operator: condition.operator.clone(),
+                    value: condition.value.clone(),
+                });
+            }
+        }
+
+        let mut branches: Vec<ConditionNode> = groups
+            .into_iter()
+            .map(|mut group| {
+                if group.len() == 1 {
+                    group.remove(0)
+                } else {
+                    ConditionNode::And(group)
+                }
+            })
+            .collect();
+
+        match branches.len() {
+            0 => None,
+            1 => branches.pop(),
+            _ => Some(ConditionNode::Or(branches)),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use serde_json::Value;
+use crate::orm::model::Model;
+use crate::orm::query::{Expression, Query};
+
+// Chainable methods shared by Query and relations, so generic code can accept
+// anything that can be narrowed with a where clause
+pub trait QueryBuilder<R: Model>: Sized {
+    // The underlying query, including any constraints the implementor adds
+    fn into_query(self) -> Query<R>;
+
+    fn select<C: Into<Expression>>(self, columns: Vec<C>) -> Query<R> {
+        self.into_query().select(columns)
+    }
+
+    fn where_clause(self, column: &str, value: &str) -> Query<R> {
+        self.into_query().where_clause(column, value)
+    }
+
+    fn where_op<C: Into<Expression>>(self, column: C, operator: &str, value: Value) -> Query<R> {
+        self.into_query().where_op(column, operator, value)
+    }
+
+    fn or_where(self, column: &str, operator: &str, value: Value) -> Query<R> {
+        self.into_query().or_where(column, operator, value)
+    }
+
+    fn where_in(self, column: &str, values: Vec<Value>) -> Query<R> {
+        self.into_query().where_in(column, values)
+    }
+
+    fn where_not_in(self, column: &str, values: Vec<Value>) -> Query<R> {
+        self.into_query().where_not_in(column, values)
+    }
+
+    fn where_null(self, column: &str) -> Query<R> {
+        self.into_query().where_null(column)
+    }
+
+    fn where_not_null(self, column: &str) -> Query<R> {
+        self.into_query().where_not_null(column)
+    }
+
+    fn where_between(self, column: &str, min: Value, max: Value) -> Query<R> {
+        self.into_query().where_between(column, min, max)
+    }
+
+    fn where_raw(self, sql: &str, bindings: Vec<Value>) -> Query<R> {
+        self.into_query().where_raw(sql, bindings)
+    }
+
+    fn order_by<C: Into<Expression>>(self, column: C, direction: &str) -> Query<R> {
+        self.into_query().order_by(column, direction)
+    }
+
+    fn latest(self, column: Option<&str>) -> Query<R> {
+        self.into_query().latest(column)
+    }
+
+    fn oldest(self, column: Option<&str>) -> Query<R> {
+        self.into_query().oldest(column)
+    }
+
+    fn limit(self, limit: i64) -> Query<R> {
+        self.into_query().limit(limit)
+    }
+
+    fn offset(self, offset: i64) -> Query<R> {
+        self.into_query().offset(offset)
+    }
+
+    fn with(self, relations: Vec<&str>) -> Query<R> {
+        self.into_query().with(relations)
+    }
+
+    // Soft-delete toggles for this query only, e.g. `user.posts().with_trashed()` keeps the
+    // relation's constraints but includes the related model's trashed rows
+    fn with_trashed(self) -> Query<R> {
+        self.into_query().with_trashed()
+    }
+
+    fn only_trashed(self) -> Query<R> {
+        self.into_query().only_trashed()
+    }
+
+    fn without_trashed(self) -> Query<R> {
+        self.into_query().without_trashed()
+    }
+}
+
+impl<T: Model> QueryBuilder<T> for Query<T> {
+    fn into_query(self) -> Query<T> {
+        self
+    }
+}
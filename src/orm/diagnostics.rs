@@ -0,0 +1,258 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::OpenOptions;
+use std::future::Future;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, RwLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use crate::db::connection::{DatabaseConnection, Driver};
+use crate::orm::error::EloquentError;
+
+// What to do when a statement repeats past the N+1 threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NPlusOneAction {
+    Log,
+    Panic,
+}
+
+static N_PLUS_ONE_THRESHOLD: AtomicUsize = AtomicUsize::new(0);
+static N_PLUS_ONE_PANICS: AtomicBool = AtomicBool::new(false);
+
+// Fingerprint counts for queries run outside an n_plus_one_scope
+static GLOBAL_COUNTS: Mutex<Option<HashMap<String, usize>>> = Mutex::new(None);
+
+tokio::task_local! {
+    static SCOPE_COUNTS: Mutex<HashMap<String, usize>>;
+}
+
+// Flag statements that run more than threshold times with only their literal values
+// changing - the shape of a loop issuing one query per parent row. Debug builds only;
+// see detect_n_plus_one_with to also enable it in release builds. A threshold of 0 turns it off
+pub fn detect_n_plus_one(threshold: usize) {
+    detect_n_plus_one_with(threshold, NPlusOneAction::Log, false);
+}
+
+pub fn detect_n_plus_one_with(threshold: usize, action: NPlusOneAction, in_release: bool) {
+    let enabled = cfg!(debug_assertions) || in_release;
+    N_PLUS_ONE_THRESHOLD.store(if enabled { threshold } else { 0 }, Ordering::Relaxed);
+    N_PLUS_ONE_PANICS.store(action == NPlusOneAction::Panic, Ordering::Relaxed);
+}
+
+// Count queries made while running future separately, e.g. once per request
+pub async fn n_plus_one_scope<F: Future>(future: F) -> F::Output {
+    SCOPE_COUNTS.scope(Mutex::new(HashMap::new()), future).await
+}
+
+// Forget the counts of queries run outside any scope
+pub fn reset_n_plus_one() {
+    if let Ok(mut counts) = GLOBAL_COUNTS.lock() {
+        *counts = None;
+    }
+}
+
+// Called for every statement the query builder runs
+pub(crate) fn record_query(sql: &str) {
+    let threshold = N_PLUS_ONE_THRESHOLD.load(Ordering::Relaxed);
+    if threshold == 0 {
+        return;
+    }
+
+    let fingerprint = fingerprint(sql);
+    let count = SCOPE_COUNTS
+        .try_with(|counts| increment(&mut counts.lock().unwrap_or_else(|e| e.into_inner()), &fingerprint))
+        .unwrap_or_else(|_| {
+            let mut counts = GLOBAL_COUNTS.lock().unwrap_or_else(|e| e.into_inner());
+            increment(counts.get_or_insert_with(HashMap::new), &fingerprint)
+        });
+
+    // Reported once, when the threshold is first exceeded
+    if count == threshold + 1 {
+        let message = format!(
+            "possible N+1 query: ran {} times, last as: {}",
+            count, sql
+        );
+        if N_PLUS_ONE_PANICS.load(Ordering::Relaxed) {
+            panic!("{}", message);
+        }
+        eprintln!("{}", message);
+    }
+}
+
+fn increment(counts: &mut HashMap<String, usize>, fingerprint: &str) -> usize {
+    let count = counts.entry(fingerprint.to_string()).or_insert(0);
+    *count += 1;
+    *count
+}
+
+// The SQL with quoted strings and numeric literals replaced by `?`
+fn fingerprint(sql: &str) -> String {
+    let mut result = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    let mut previous = ' ';
+
+    while let Some(c) = chars.next() {
+        if c == '\'' {
+            // '' inside a string is an escaped quote
+            while let Some(c) = chars.next() {
+                if c == '\'' && chars.next_if_eq(&'\'').is_none() {
+                    break;
+                }
+            }
+            result.push('?');
+            previous = '?';
+        } else if c.is_ascii_digit() && !(previous.is_alphanumeric() || previous == '_') {
+            while chars.next_if(|c| c.is_ascii_digit() || *c == '.').is_some() {}
+            result.push('?');
+            previous = '?';
+        } else {
+            result.push(c);
+            previous = c;
+        }
+    }
+    result
+}
+
+// Statements go over a channel to a writer thread, so logging never waits on the sink
+static QUERY_LOG: Mutex<Option<(Sender<String>, JoinHandle<()>)>> = Mutex::new(None);
+static LOGGING_QUERIES: AtomicBool = AtomicBool::new(false);
+
+// Append every statement the query builder runs, with its duration, to the file at path.
+// Entries are written as `-- 1.234ms` followed by the statement, so the file replays as a
+// SQL script. Bindings are inlined into the SQL, so they appear as part of the statement
+pub fn log_queries_to<P: AsRef<Path>>(path: P) -> std::io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    log_queries_to_writer(file);
+    Ok(())
+}
+
+// As log_queries_to, writing to any sink. Replaces the previous sink, which is flushed first
+pub fn log_queries_to_writer<W: Write + Send + 'static>(sink: W) {
+    let (sender, receiver) = mpsc::channel::<String>();
+    let writer = std::thread::spawn(move || {
+        let mut writer = BufWriter::new(sink);
+        // Flush whenever the channel is drained, so entries reach the sink without
+        // a write per statement
+        while let Ok(entry) = receiver.recv() {
+            let _ = writer.write_all(entry.as_bytes());
+            while let Ok(entry) = receiver.try_recv() {
+                let _ = writer.write_all(entry.as_bytes());
+            }
+            let _ = writer.flush();
+        }
+    });
+
+    let previous = QUERY_LOG.lock().unwrap_or_else(|e| e.into_inner()).replace((sender, writer));
+    LOGGING_QUERIES.store(true, Ordering::Relaxed);
+    finish(previous);
+}
+
+// Stop logging, waiting for entries already queued to be written
+pub fn stop_logging_queries() {
+    LOGGING_QUERIES.store(false, Ordering::Relaxed);
+    let log = QUERY_LOG.lock().unwrap_or_else(|e| e.into_inner()).take();
+    finish(log);
+}
+
+// Dropping the sender lets the writer thread flush and exit
+fn finish(log: Option<(Sender<String>, JoinHandle<()>)>) {
+    if let Some((sender, writer)) = log {
+        drop(sender);
+        let _ = writer.join();
+    }
+}
+
+pub(crate) fn logging_queries() -> bool {
+    LOGGING_QUERIES.load(Ordering::Relaxed)
+}
+
+pub(crate) fn log_query(sql: &str, duration: Duration) {
+    let log = QUERY_LOG.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((sender, _)) = log.as_ref() {
+        let entry = format!("-- {:.3}ms\n{};\n", duration.as_secs_f64() * 1000.0, sql);
+        let _ = sender.send(entry);
+    }
+}
+
+// A model setup problem worth a look but not an error, written to the query log as a
+// `-- warning:` comment so the log still replays as SQL. Dropped when nothing is logging
+pub(crate) fn log_warning(message: &str) {
+    let log = QUERY_LOG.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((sender, _)) = log.as_ref() {
+        let _ = sender.send(format!("-- warning: {}\n", message));
+    }
+}
+
+static VALIDATE_COLUMNS: AtomicBool = AtomicBool::new(false);
+
+// Known columns per table, filled by load_table_columns / register_table_columns
+static TABLE_COLUMNS: RwLock<Option<HashMap<String, HashSet<String>>>> = RwLock::new(None);
+
+// Check where/select/order_by/group_by columns against the schema cache before running a
+// query, failing with EloquentError::UnknownColumn. Only has an effect in debug builds, and
+// only for tables whose columns have been loaded
+pub fn validate_columns(enabled: bool) {
+    VALIDATE_COLUMNS.store(enabled && cfg!(debug_assertions), Ordering::Relaxed);
+}
+
+pub(crate) fn validating_columns() -> bool {
+    VALIDATE_COLUMNS.load(Ordering::Relaxed)
+}
+
+pub fn register_table_columns(table: &str, columns: Vec<String>) {
+    let mut cache = TABLE_COLUMNS.write().unwrap_or_else(|e| e.into_inner());
+    cache.get_or_insert_with(HashMap::new).insert(table.to_string(), columns.into_iter().collect());
+}
+
+// Fetch a table's columns from the database into the schema cache.
+// The table may be schema-qualified (schema.table)
+pub async fn load_table_columns(connection: &dyn DatabaseConnection, table: &str) -> Result<(), sqlx::Error> {
+    let (schema, name) = match table.split_once('.') {
+        Some((schema, name)) => (Some(schema), name),
+        None => (None, table),
+    };
+    let sql = match connection.driver() {
+        Driver::MySql => format!(
+            "SELECT COLUMN_NAME FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = {} AND TABLE_NAME = '{}'",
+            schema.map(|s| format!("'{}'", s)).unwrap_or_else(|| "DATABASE()".to_string()), name),
+        Driver::Postgres => format!(
+            "SELECT column_name FROM information_schema.columns WHERE table_schema = {} AND table_name = '{}'",
+            schema.map(|s| format!("'{}'", s)).unwrap_or_else(|| "current_schema()".to_string()), name),
+        Driver::Sqlite => format!("SELECT name FROM pragma_table_info('{}')", name),
+    };
+
+    let (_, rows) = connection.fetch_all(&sql).await?;
+    let columns = rows
+        .into_iter()
+        .filter_map(|row| row.into_iter().next().and_then(|value| value.as_str().map(str::to_string)))
+        .collect();
+    register_table_columns(table, columns);
+    Ok(())
+}
+
+// Referenced columns are "column" or "table.column"; a bare column belongs to default_table
+pub(crate) fn check_columns(default_table: &str, columns: &[String]) -> Result<(), EloquentError> {
+    let cache = TABLE_COLUMNS.read().unwrap_or_else(|e| e.into_inner());
+    let Some(cache) = cache.as_ref() else {
+        return Ok(());
+    };
+
+    for reference in columns {
+        let (table, column) = match reference.rsplit_once('.') {
+            Some((table, column)) => (table, column),
+            None => (default_table, reference.as_str()),
+        };
+        if let Some(known) = cache.get(table)
+            && column != "*"
+            && !known.contains(column)
+        {
+            return Err(EloquentError::UnknownColumn {
+                table: table.to_string(),
+                column: column.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
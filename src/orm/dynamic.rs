@@ -0,0 +1,206 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::collections::HashMap;
+use crate::orm::error::EloquentError;
+use crate::orm::model::{Attributable, Model, ModelInstance};
+use crate::orm::query::Query;
+
+// A model without a compile-time struct: attributes live in a ModelInstance and the
+// table and fillable list are chosen at runtime, e.g. for admin tools.
+//
+// Model::table_name() must be 'static, so it is empty here and queries go through
+// DynamicModel::query_table(), which sets the table on the Query itself
+#[derive(Debug, Clone, Default)]
+pub struct DynamicModel {
+    table: String,
+    fillable: Vec<String>,
+    instance: ModelInstance,
+}
+
+impl DynamicModel {
+    pub fn new(table: &str, fillable: Vec<&str>) -> Self {
+        Self {
+            table: table.to_string(),
+            fillable: fillable.iter().map(|s| s.to_string()).collect(),
+            instance: ModelInstance::new(),
+        }
+    }
+
+    // An existing row, e.g. one hydrated from a query_table() result
+    pub fn from_attributes(table: &str, attributes: HashMap<String, Value>) -> Self {
+        Self {
+            table: table.to_string(),
+            fillable: Vec::new(),
+            instance: ModelInstance::from_attributes(attributes),
+        }
+    }
+
+    pub fn query_table(table: &str) -> Query<DynamicModel> {
+        Query::new().from(table)
+    }
+
+    pub fn get_table(&self) -> &str {
+        &self.table
+    }
+
+    pub fn set_table(&mut self, table: &str) {
+        self.table = table.to_string();
+    }
+
+    pub fn set_fillable(&mut self, fillable: Vec<&str>) {
+        self.fillable = fillable.iter().map(|s| s.to_string()).collect();
+    }
+
+    pub fn exists(&self) -> bool {
+        self.instance.exists
+    }
+
+    // This instance's table, keyed by its primary key when it has one
+    fn key_query(&self) -> Query<DynamicModel> {
+        let query = Self::query_table(&self.table);
+        match self.get_key_value() {
+            Some(key) => query.where_op(Self::primary_key(), "=", key),
+            None => query,
+        }
+    }
+
+    // INSERT for a new row, UPDATE of the dirty attributes for an existing one
+    pub fn to_save_sql(&self) -> Option<String> {
+        if !self.instance.exists {
+            return Some(self.key_query().to_insert_sql(&self.instance.attributes));
+        }
+
+        let dirty = self.instance.get_dirty();
+        if dirty.is_empty() {
+            None
+        } else {
+            Some(self.key_query().to_update_sql(&dirty))
+        }
+    }
+}
+
+#[async_trait]
+impl Model for DynamicModel {
+    fn table_name() -> &'static str {
+        ""
+    }
+
+    fn fillable() -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    fn get_key_value(&self) -> Option<Value> {
+        self.instance.get_attribute(Self::primary_key()).filter(|key| !key.is_null()).cloned()
+    }
+
+    fn was_recently_created(&self) -> bool {
+        self.instance.was_recently_created
+    }
+
+    fn set_was_recently_created(&mut self, created: bool) {
+        self.instance.was_recently_created = created;
+    }
+
+    // Uses the runtime fillable list rather than Model::fillable()
+    fn fill(&mut self, attributes: HashMap<String, Value>) -> Result<(), EloquentError> {
+        for (key, value) in attributes {
+            if self.fillable.contains(&key) {
+                self.instance.set_attribute(&key, value);
+            }
+        }
+        Ok(())
+    }
+
+    // Any key is accepted, since there is no fixed set of fields
+    fn force_fill(&mut self, attributes: HashMap<String, Value>) -> Result<(), EloquentError> {
+        for (key, value) in attributes {
+            self.instance.set_attribute(&key, value);
+        }
+        Ok(())
+    }
+
+    // The static lookups have no table to work with
+    async fn find(_id: i64) -> Result<Option<Self>, sqlx::Error> {
+        Err(no_static_table())
+    }
+
+    async fn all() -> Result<Vec<Self>, sqlx::Error> {
+        Err(no_static_table())
+    }
+
+    async fn create(_attributes: HashMap<String, Value>) -> Result<Self, sqlx::Error> {
+        Err(no_static_table())
+    }
+
+    async fn save(&mut self) -> Result<(), sqlx::Error> {
+        if let Some(_sql) = self.to_save_sql() {
+            // This would execute the INSERT/UPDATE
+            self.instance.was_recently_created = !self.instance.exists;
+            self.instance.exists = true;
+            self.instance.original = self.instance.attributes.clone();
+            self.touch_owners().await.map_err(EloquentError::into_sqlx)?;
+        }
+        Ok(())
+    }
+
+    async fn delete(&self) -> Result<(), sqlx::Error> {
+        // This would execute self.key_query().to_delete_sql()
+        Ok(())
+    }
+
+    async fn update(&mut self, attributes: HashMap<String, Value>) -> Result<(), sqlx::Error> {
+        for (key, value) in attributes {
+            self.instance.set_attribute(&key, value);
+        }
+        self.save().await
+    }
+}
+
+impl Attributable for DynamicModel {
+    fn get_attribute(&self, key: &str) -> Option<&Value> {
+        self.instance.get_attribute(key)
+    }
+
+    fn set_attribute(&mut self, key: &str, value: Value) {
+        self.instance.set_attribute(key, value);
+    }
+
+    fn get_attributes(&self) -> &HashMap<String, Value> {
+        self.instance.get_attributes()
+    }
+
+    fn get_original(&self) -> &HashMap<String, Value> {
+        self.instance.get_original()
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.instance.is_dirty()
+    }
+
+    fn get_dirty(&self) -> HashMap<String, Value> {
+        self.instance.get_dirty()
+    }
+}
+
+// Serialized as its plain attribute map, so rows hydrate straight into it
+impl Serialize for DynamicModel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.instance.attributes.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DynamicModel {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let attributes = HashMap::<String, Value>::deserialize(deserializer)?;
+        Ok(Self {
+            table: String::new(),
+            fillable: Vec::new(),
+            instance: ModelInstance::from_attributes(attributes),
+        })
+    }
+}
+
+fn no_static_table() -> sqlx::Error {
+    sqlx::Error::Protocol("DynamicModel has no static table; use DynamicModel::query_table()".to_string())
+}
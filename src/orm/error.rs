@@ -0,0 +1,194 @@
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+// Errors raised by the ORM layer on top of the driver's own errors
+#[derive(Debug)]
+pub enum EloquentError {
+    Database(sqlx::Error),
+    Serialization(serde_json::Error),
+    UnknownAttribute(String), // Attribute key that matches no model field
+    MissingKey(String),       // Table of a model that has no primary key value yet
+    MissingWhereClause { operation: String, table: String },
+    Timeout(Duration), // Statement exceeded Query::timeout
+    UnknownColumn { table: String, column: String }, // Caught by validate_columns
+    Hydration { table: String, error: serde_json::Error }, // A fetched row that doesn't deserialize into the model
+    // A unique or primary key constraint was hit. Either part may be empty when the
+    // driver doesn't report it: MySQL names only the key, SQLite only the columns
+    UniqueViolation { constraint: Option<String>, columns: Vec<String> },
+    Configuration(String), // e.g. a query on a connection name nothing was registered under
+    Unsupported(String),   // SQL the connected server's version can't run, e.g. window functions on MySQL 5.7
+}
+
+impl EloquentError {
+    // Carry this error through APIs that return sqlx::Error. Converting back with
+    // EloquentError::from recovers the original variant. Only Timeout is reported as
+    // TimedOut, so callers that just check the io kind don't mistake the rest for timeouts
+    pub(crate) fn into_sqlx(self) -> sqlx::Error {
+        let kind = match self {
+            EloquentError::Timeout(_) => io::ErrorKind::TimedOut,
+            _ => io::ErrorKind::Other,
+        };
+        sqlx::Error::Io(io::Error::new(kind, self))
+    }
+}
+
+// Whether an insert failed on a unique or primary key constraint. sqlx classifies the
+// driver's error code; Postgres and SQLite codes are also checked directly for errors
+// it reports without a kind
+pub fn is_unique_violation(error: &sqlx::Error) -> bool {
+    let sqlx::Error::Database(error) = error else {
+        return false;
+    };
+    if error.is_unique_violation() {
+        return true;
+    }
+    // 23505 is Postgres' unique_violation; 2067 and 1555 are SQLite's extended
+    // SQLITE_CONSTRAINT_UNIQUE and SQLITE_CONSTRAINT_PRIMARYKEY
+    matches!(error.code().as_deref(), Some("23505" | "2067" | "1555"))
+}
+
+// The constraint and columns named in a unique violation, read from the driver's error
+fn unique_violation_details(error: &sqlx::Error) -> Option<(Option<String>, Vec<String>)> {
+    let sqlx::Error::Database(database_error) = error else {
+        return None;
+    };
+    if let Some(mysql) = database_error.try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>() {
+        return (mysql.number() == 1062).then(|| (mysql_duplicate_key(mysql.message()), Vec::new()));
+    }
+    if !is_unique_violation(error) {
+        return None;
+    }
+    if let Some(postgres) = database_error.try_downcast_ref::<sqlx::postgres::PgDatabaseError>() {
+        let columns = postgres.detail().map(postgres_key_columns).unwrap_or_default();
+        return Some((postgres.constraint().map(str::to_string), columns));
+    }
+    Some((None, sqlite_unique_columns(database_error.message())))
+}
+
+// "Duplicate entry 'a@b.c' for key 'users.users_email_unique'"; MySQL 8 prefixes the table
+fn mysql_duplicate_key(message: &str) -> Option<String> {
+    let key = message.rsplit_once(" for key ")?.1.trim_matches('\'');
+    Some(key.rsplit_once('.').map_or(key, |(_, name)| name).to_string())
+}
+
+// "Key (email)=(a@b.c) already exists." or "Key (team_id, name)=(1, x) already exists."
+fn postgres_key_columns(detail: &str) -> Vec<String> {
+    let Some(columns) = detail.strip_prefix("Key (").and_then(|rest| rest.split_once(")=(")) else {
+        return Vec::new();
+    };
+    columns.0.split(',').map(|column| column.trim().trim_matches('"').to_string()).collect()
+}
+
+// "UNIQUE constraint failed: users.email, users.team_id"
+fn sqlite_unique_columns(message: &str) -> Vec<String> {
+    let Some((_, columns)) = message.split_once("constraint failed: ") else {
+        return Vec::new();
+    };
+    columns.split(',')
+        .map(|column| column.trim().rsplit_once('.').map_or(column.trim(), |(_, name)| name).to_string())
+        .collect()
+}
+
+impl fmt::Display for EloquentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EloquentError::Database(error) => write!(f, "database error: {}", error),
+            EloquentError::Serialization(error) => write!(f, "serialization error: {}", error),
+            EloquentError::UnknownAttribute(key) => write!(f, "unknown attribute [{}]", key),
+            EloquentError::MissingKey(table) => write!(f, "model on [{}] has no primary key value", table),
+            EloquentError::MissingWhereClause { operation, table } => {
+                write!(f, "refusing to run {} on [{}] without a WHERE clause", operation, table)
+            }
+            EloquentError::Timeout(duration) => write!(f, "query timed out after {:?}", duration),
+            EloquentError::UnknownColumn { table, column } => {
+                write!(f, "unknown column [{}] on [{}]", column, table)
+            }
+            EloquentError::Hydration { table, error } => {
+                write!(f, "row from [{}] could not be hydrated: {}", table, error)
+            }
+            EloquentError::UniqueViolation { constraint, columns } => {
+                write!(f, "unique constraint violated")?;
+                if let Some(constraint) = constraint {
+                    write!(f, " [{}]", constraint)?;
+                }
+                if !columns.is_empty() {
+                    write!(f, " on ({})", columns.join(", "))?;
+                }
+                Ok(())
+            }
+            EloquentError::Configuration(message) => write!(f, "configuration error: {}", message),
+            EloquentError::Unsupported(message) => write!(f, "unsupported by the server: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for EloquentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EloquentError::Database(error) => Some(error),
+            EloquentError::Serialization(error) => Some(error),
+            EloquentError::Hydration { error, .. } => Some(error),
+            EloquentError::UnknownAttribute(_)
+            | EloquentError::MissingKey(_)
+            | EloquentError::MissingWhereClause { .. }
+            | EloquentError::Timeout(_)
+            | EloquentError::UnknownColumn { .. }
+            | EloquentError::UniqueViolation { .. }
+            | EloquentError::Configuration(_)
+            | EloquentError::Unsupported(_) => None,
+        }
+    }
+}
+
+// Unique violations become UniqueViolation, so callers can match on them without
+// inspecting driver messages
+impl From<sqlx::Error> for EloquentError {
+    fn from(error: sqlx::Error) -> Self {
+        match error {
+            sqlx::Error::Io(io_error) if io_error.get_ref().is_some_and(|inner| inner.is::<EloquentError>()) => {
+                let inner = io_error.into_inner().expect("checked above");
+                *inner.downcast::<EloquentError>().expect("checked above")
+            }
+            error => match unique_violation_details(&error) {
+                Some((constraint, columns)) => EloquentError::UniqueViolation { constraint, columns },
+                None => EloquentError::Database(error),
+            },
+        }
+    }
+}
+
+impl From<serde_json::Error> for EloquentError {
+    fn from(error: serde_json::Error) -> Self {
+        EloquentError::Serialization(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_timeouts_are_carried_as_timed_out() {
+        let timeout = EloquentError::Timeout(Duration::from_secs(1)).into_sqlx();
+        assert!(matches!(&timeout, sqlx::Error::Io(error) if error.kind() == io::ErrorKind::TimedOut));
+
+        let missing = EloquentError::MissingWhereClause { operation: "DELETE".into(), table: "users".into() }.into_sqlx();
+        assert!(matches!(&missing, sqlx::Error::Io(error) if error.kind() == io::ErrorKind::Other));
+        assert!(matches!(EloquentError::from(missing), EloquentError::MissingWhereClause { .. }));
+    }
+
+    #[test]
+    fn unique_violation_messages_are_parsed_per_driver() {
+        assert_eq!(mysql_duplicate_key("Duplicate entry 'a@b.c' for key 'users.users_email_unique'"),
+            Some("users_email_unique".to_string()));
+        assert_eq!(mysql_duplicate_key("Duplicate entry '1' for key 'PRIMARY'"), Some("PRIMARY".to_string()));
+        assert_eq!(mysql_duplicate_key("Deadlock found"), None);
+
+        assert_eq!(postgres_key_columns("Key (team_id, \"name\")=(1, x) already exists."), ["team_id", "name"]);
+        assert!(postgres_key_columns("Failing row contains (1).").is_empty());
+
+        assert_eq!(sqlite_unique_columns("UNIQUE constraint failed: users.email, users.team_id"), ["email", "team_id"]);
+        assert!(sqlite_unique_columns("NOT NULL constraint failed").is_empty());
+    }
+}
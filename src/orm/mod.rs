@@ -1,22 +1,36 @@
+pub mod ast;
+pub mod builder;
+pub mod diagnostics;
+pub mod dynamic;
+pub mod error;
 pub mod model;
 pub mod query;
 pub mod relations;
 
+use async_trait::async_trait;
 use std::collections::HashMap;
+use std::sync::Arc;
+use crate::db::connection::{driver_for, DatabaseConnection, Driver};
 
 // Main trait that provides Laravel-like functionality
+#[async_trait]
 pub trait Eloquent: model::Model + Clone + Sized + Send + Sync + 'static {
     // Query builder methods
+    // Base query for the model - its table, global scopes and soft-delete filter
     fn query() -> query::Query<Self> {
-        query::Query::new()
+        query::Query::new().from(&Self::qualified_table_name())
+    }
+
+    fn query_without_scopes() -> query::Query<Self> {
+        Self::query().without_global_scopes()
     }
 
     fn where_(field: &str, value: &str) -> query::Query<Self> {
-        query::Query::new().where_clause(field, value)
+        Self::query().where_clause(field, value)
     }
 
     fn all() -> query::Query<Self> {
-        query::Query::new()
+        Self::query()
     }
 
     // Relationship helper methods
@@ -88,24 +102,224 @@ pub trait Eloquent: model::Model + Clone + Sized + Send + Sync + 'static {
         relations::HasMorphMany::new(self.clone(), name, type_column, id_column, local_key)
     }
 
+    // The inverse of morph_one/morph_many: the owner named by `{name}_type` and `{name}_id`
+    fn morph_to(&self, name: &str, type_column: Option<String>, id_column: Option<String>) -> relations::MorphTo<Self> {
+        relations::MorphTo::new(self.clone(), name, type_column, id_column)
+    }
+
+    // Query bound to a connection handle, for code that injects connections instead of
+    // relying on the registry behind Model::connection()
+    fn on_connection(connection: Arc<dyn DatabaseConnection>) -> query::Query<Self> {
+        Self::query().on_connection(connection)
+    }
+
     // Static methods for creating queries - fixed the string slice issue
     fn find_by_id(id: i64) -> query::Query<Self> {
         let id_str = id.to_string();
-        query::Query::new().where_clause(Self::primary_key(), &id_str)
+        Self::query().where_clause(Self::primary_key(), &id_str)
     }
 
     fn find_or_fail(id: i64) -> query::Query<Self> {
         Self::find_by_id(id)
     }
 
-    fn first_or_create(_attributes: HashMap<String, serde_json::Value>) -> query::Query<Self> {
-        // This would implement first_or_create logic
-        query::Query::new()
+    // Resolve a model from a route parameter using route_key_name() (e.g. a slug)
+    async fn find_by_route_key(value: &str) -> Result<Option<Self>, sqlx::Error> {
+        Self::query().where_clause(Self::route_key_name(), value).first().await
+    }
+
+    // A new copy of this row as it is now in the database, leaving self untouched; None
+    // when the model has no key or the row is gone. Global scopes don't apply, so a
+    // soft-deleted row is still returned
+    async fn fresh(&self) -> Result<Option<Self>, sqlx::Error> {
+        Ok(self.fresh_with(Vec::new()).await?.map(query::Loaded::into_inner))
+    }
+
+    // As fresh, eager loading relations on the copy; read them with Loaded::related
+    async fn fresh_with(&self, relations: Vec<&str>) -> Result<Option<query::Loaded<Self>>, sqlx::Error> {
+        let Some(key) = self.get_key_value().filter(|key| !key.is_null()) else {
+            return Ok(None);
+        };
+        let loaded = Self::query_without_scopes()
+            .with(relations)
+            .where_op(Self::primary_key(), "=", key)
+            .limit(1)
+            .get_loaded()
+            .await?;
+        Ok(loaded.into_iter().next())
+    }
+
+    async fn exists_by_id(id: i64) -> Result<bool, sqlx::Error> {
+        Self::find_by_id(id).exists().await
+    }
+
+    // Rows visible through query(), so global scopes and soft deletes apply
+    async fn count_all() -> Result<i64, sqlx::Error> {
+        Self::query().count().await
+    }
+
+    // Terminal shortcuts for query().first() / count() / pluck(column); like count_all,
+    // they go through query(), so global scopes and soft deletes apply
+    async fn first() -> Result<Option<Self>, sqlx::Error> {
+        Self::query().first().await
+    }
+
+    // Same as count_all
+    async fn count() -> Result<i64, sqlx::Error> {
+        Self::count_all().await
+    }
+
+    async fn pluck(column: &str) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+        Self::query().pluck(column).await
+    }
+
+    // Remove every row and reset the auto-increment counter, e.g. in test teardown
+    async fn truncate() -> Result<(), EloquentError> {
+        Self::query().truncate().await
+    }
+
+    // Insert the rows in one statement, keeping only fillable() keys like fill(), and
+    // return them with their generated keys. Every row must end up with the same keys;
+    // see Query::create_many for how each driver reads the rows back
+    async fn create_many(rows: Vec<HashMap<String, serde_json::Value>>) -> Result<Vec<Self>, sqlx::Error> {
+        let fillable = Self::fillable();
+        let rows = rows.into_iter()
+            .map(|mut row| {
+                row.retain(|key, _| fillable.contains(&key.as_str()));
+                row
+            })
+            .collect();
+        let mut created = Self::query().create_many(rows).await?;
+        for model in &mut created {
+            model.set_was_recently_created(true);
+        }
+        Ok(created)
+    }
+
+    // Find the first row matching the attributes, or create it. A concurrent insert of the
+    // same row is only detected through a unique index over the attributes, on every driver:
+    // - Postgres inserts with INSERT ... ON CONFLICT DO NOTHING RETURNING * (create_or_ignore)
+    //   and selects the winner's row when nothing comes back. Nothing fails, so this is also
+    //   safe inside an explicit transaction, which a failed INSERT would abort.
+    // - MySQL and SQLite insert with create(); a unique violation from it selects the
+    //   winner's row instead of failing. Both keep an open transaction usable after that.
+    async fn first_or_create(attributes: HashMap<String, serde_json::Value>) -> Result<Self, sqlx::Error> {
+        Ok(Self::first_or_create_reporting(attributes).await?.0)
+    }
+
+    // As first_or_create, also returning whether the row was created by this call.
+    // The created model has set_was_recently_created(true) applied
+    async fn first_or_create_reporting(
+        attributes: HashMap<String, serde_json::Value>,
+    ) -> Result<(Self, bool), sqlx::Error> {
+        let query = Self::attributes_query(&attributes);
+        if let Some(existing) = query.clone().first().await? {
+            return Ok((existing, false));
+        }
+        create_or_select(attributes, query).await
+    }
+
+    // As first_or_create, but the creation defaults only come from values when nothing matches.
+    // The row is created from attributes merged with values (values win), keeping only
+    // fillable() keys, like fill(), and a concurrent insert is handled as in first_or_create.
+    // The created model has set_was_recently_created(true) applied
+    async fn first_or_create_with<F>(
+        attributes: HashMap<String, serde_json::Value>,
+        values: F,
+    ) -> Result<Self, sqlx::Error>
+    where
+        F: FnOnce() -> HashMap<String, serde_json::Value> + Send,
+    {
+        let query = Self::attributes_query(&attributes);
+        if let Some(existing) = query.clone().first().await? {
+            return Ok(existing);
+        }
+
+        let fillable = Self::fillable();
+        let mut merged = attributes;
+        merged.extend(values());
+        merged.retain(|key, _| fillable.contains(&key.as_str()));
+        Ok(create_or_select(merged, query).await?.0)
+    }
+
+    // Insert the row, or return the existing one if the insert hits a unique constraint.
+    // Unlike first_or_create there is no select first and no ON CONFLICT: the insert is
+    // always attempted and a unique violation is resolved by selecting the row whose
+    // unique_columns match attributes, which handles a concurrent insert deterministically.
+    // Every unique column must be among the attributes
+    async fn create_unique(
+        attributes: HashMap<String, serde_json::Value>,
+        unique_columns: &[&str],
+    ) -> Result<Self, sqlx::Error> {
+        let mut unique = HashMap::new();
+        for column in unique_columns {
+            let value = attributes.get(*column).ok_or_else(|| sqlx::Error::Protocol(format!(
+                "create_unique: unique column [{}] is not among the attributes", column
+            )))?;
+            unique.insert(column.to_string(), value.clone());
+        }
+
+        match Self::create(attributes).await {
+            Ok(mut created) => {
+                created.set_was_recently_created(true);
+                Ok(created)
+            }
+            Err(error) if error::is_unique_violation(&error) => {
+                match Self::attributes_query(&unique).first().await? {
+                    Some(existing) => Ok(existing),
+                    None => Err(error),
+                }
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    // Update the first row matching attributes with values, or create it from both.
+    // Returns whether the row was created; a lost insert race falls back to updating
+    async fn update_or_create_reporting(
+        attributes: HashMap<String, serde_json::Value>,
+        values: HashMap<String, serde_json::Value>,
+    ) -> Result<(Self, bool), sqlx::Error> {
+        let query = Self::attributes_query(&attributes);
+        if let Some(mut existing) = query.clone().first().await? {
+            existing.update(values).await?;
+            return Ok((existing, false));
+        }
+
+        let mut merged = attributes;
+        merged.extend(values.clone());
+        match Self::create(merged).await {
+            Ok(mut created) => {
+                created.set_was_recently_created(true);
+                Ok((created, true))
+            }
+            Err(error) if error::is_unique_violation(&error) => {
+                match query.first().await? {
+                    Some(mut existing) => {
+                        existing.update(values).await?;
+                        Ok((existing, false))
+                    }
+                    None => Err(error),
+                }
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    // Equality on every attribute, in column order so the SQL is stable
+    fn attributes_query(attributes: &HashMap<String, serde_json::Value>) -> query::Query<Self> {
+        let mut columns: Vec<&String> = attributes.keys().collect();
+        columns.sort();
+        let mut query = Self::query();
+        for column in columns {
+            query = query.where_op(column, "=", attributes[column].clone());
+        }
+        query
     }
 
     fn first_or_new(_attributes: HashMap<String, serde_json::Value>) -> query::Query<Self> {
         // This would implement first_or_new logic
-        query::Query::new()
+        Self::query()
     }
 
     fn update_or_create(
@@ -113,23 +327,60 @@ pub trait Eloquent: model::Model + Clone + Sized + Send + Sync + 'static {
         _values: HashMap<String, serde_json::Value>,
     ) -> query::Query<Self> {
         // This would implement update_or_create logic
-        query::Query::new()
+        Self::query()
     }
 
     // Scopes
     fn latest(column: Option<&str>) -> query::Query<Self> {
-        query::Query::new().latest(column)
+        Self::query().latest(column)
     }
 
     fn oldest(column: Option<&str>) -> query::Query<Self> {
-        query::Query::new().oldest(column)
+        Self::query().oldest(column)
+    }
+}
+
+// The insert behind first_or_create: create the row from attributes, or, when a concurrent
+// insert of the same row won, return the row existing selects, with whether this call created it
+async fn create_or_select<M: Eloquent>(
+    attributes: HashMap<String, serde_json::Value>,
+    existing: query::Query<M>,
+) -> Result<(M, bool), sqlx::Error> {
+    if driver_for(M::connection()) == Driver::Postgres {
+        return match M::query().create_or_ignore(attributes).await? {
+            Some(mut created) => {
+                created.set_was_recently_created(true);
+                Ok((created, true))
+            }
+            None => existing.first().await?.map(|existing| (existing, false)).ok_or(sqlx::Error::RowNotFound),
+        };
+    }
+
+    match M::create(attributes).await {
+        Ok(mut created) => {
+            created.set_was_recently_created(true);
+            Ok((created, true))
+        }
+        Err(error) if error::is_unique_violation(&error) => {
+            match existing.first().await? {
+                Some(existing) => Ok((existing, false)),
+                None => Err(error),
+            }
+        }
+        Err(error) => Err(error),
     }
 }
 
 // Re-export commonly used types
-pub use model::{Model, HasTimestamps, SoftDeletes, Attributable};
+pub use ast::{QueryAst, ConditionNode};
+pub use builder::QueryBuilder;
+pub use dynamic::DynamicModel;
+pub use error::{is_unique_violation, EloquentError};
+pub use model::{model_keys, model_keys_i64, GlobalScope, SOFT_DELETE_SCOPE, Model, HasTimestamps, SoftDeletes, Attributable, Tracked};
 pub use relations::{
     Relation, CreatableRelation, AttachableRelation,
-    HasOne, HasMany, BelongsTo, BelongsToMany, HasMorphOne, HasMorphMany
+    HasOne, HasMany, BelongsTo, BelongsToMany, HasMorphOne, HasMorphMany, MorphTo,
+    morph_map, morph_table,
+    RelationDefinition, PivotDefinition
 };
 
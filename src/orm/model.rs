@@ -1,6 +1,19 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::any::Any;
 use std::collections::HashMap;
+use std::sync::Arc;
+use crate::db::connection::{driver_for, registered_connection, Driver};
+use crate::orm::dynamic::DynamicModel;
+use crate::orm::error::EloquentError;
+use crate::orm::query::Query;
+use crate::orm::relations::{Relation, RelationDefinition};
+
+// A named global scope: its name and the constraints it adds
+pub type GlobalScope<T> = (&'static str, fn(Query<T>) -> Query<T>);
+
+// Name of the soft-delete filter, for without_global_scope
+pub const SOFT_DELETE_SCOPE: &str = "soft_delete";
 
 // Core trait for all models - similar to Laravel's Model
 #[async_trait]
@@ -12,25 +25,169 @@ pub trait Model: Serialize + for<'de> Deserialize<'de> + Send + Sync + Clone + '
 
     // Optional overrides
     fn connection() -> &'static str { "default" }
+    fn schema() -> Option<&'static str> { None } // Postgres schema or MySQL database
     fn timestamps() -> bool { true }
     fn created_at_column() -> &'static str { "created_at" }
     fn updated_at_column() -> &'static str { "updated_at" }
+    // Whether Query::to_create_sql stamps created_at/updated_at with the database's clock
+    // rather than the client's, so servers with drifting clocks still agree on ordering.
+    // SQLite has no server of its own, so it uses the client's time
+    fn uses_db_timestamps() -> bool { driver_for(Self::connection()) != Driver::Sqlite }
+    fn route_key_name() -> &'static str { Self::primary_key() }
+    // The value stored in `{name}_type` columns for this model's polymorphic relations.
+    // Register a non-default one with relations::morph_map so MorphTo can find the table
+    fn morph_class() -> &'static str { Self::table_name() }
+    // Computed fields (e.g. a selected distance) that are never inserted or updated
+    fn non_persistent() -> Vec<&'static str> { Vec::new() }
+    fn soft_deletes() -> bool { false }
+    fn deleted_at_column() -> &'static str { "deleted_at" }
+    // has_one/has_many relations (by relation_definition name) whose rows Query::soft_delete
+    // soft-deletes along with this model, and Query::restore restores if they were deleted
+    // at the same moment
+    fn cascade_soft_deletes() -> Vec<&'static str> { Vec::new() }
+
+    // Constraints added to every query for this model, e.g. a tenant filter.
+    // Applied when the SQL is rendered, and skipped by without_global_scopes()
+    fn apply_global_scopes(query: Query<Self>) -> Query<Self> {
+        query
+    }
+
+    // As apply_global_scopes, but each scope is named so a query can drop just that one
+    // with without_global_scope(name), e.g. vec![("tenant", |q| q.where_clause("tenant_id", "7"))].
+    // Soft deletes are the built-in SOFT_DELETE_SCOPE
+    fn global_scopes() -> Vec<GlobalScope<Self>> {
+        Vec::new()
+    }
 
     // Helper methods
     fn table() -> &'static str {
         Self::table_name()
     }
+
+    // Table name as it appears in SQL, prefixed with schema() when set
+    fn qualified_table_name() -> String {
+        match Self::schema() {
+            Some(schema) => format!("{}.{}", schema, Self::table_name()),
+            None => Self::table_name().to_string(),
+        }
+    }
     
     fn get_key_name() -> &'static str {
         Self::primary_key()
     }
 
+    // Describe relations by name so queries can reference them (e.g. where_relation)
+    // Models override this to map each relation name to its keys
+    fn relation_definition(_name: &str) -> Option<RelationDefinition> {
+        None
+    }
+
+    // Relations whose rows get their updated_at bumped when this model is saved,
+    // like Laravel's $touches (e.g. a comment touching its post)
+    fn touches() -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    // Touch every relation listed in touches(). save_returning, the relations' save and
+    // DynamicModel::save call this after writing. Each relation is matched through its
+    // definition: related rows whose related_key equals this model's parent_key value
+    // (through the pivot table for many-to-many). A name relation_definition doesn't
+    // know is an EloquentError::Configuration
+    async fn touch_owners(&self) -> Result<(), EloquentError> {
+        let relations = Self::touches();
+        if relations.is_empty() {
+            return Ok(());
+        }
+
+        let attributes = serde_json::to_value(self)?;
+        for relation in relations {
+            let definition = Self::relation_definition(relation).ok_or_else(|| EloquentError::Configuration(
+                format!("Call to undefined relationship [{}] on model [{}] in touches()", relation, Self::table_name()),
+            ))?;
+            let key = match attributes.get(&definition.parent_key) {
+                Some(key) if !key.is_null() => key.clone(),
+                _ => continue,
+            };
+
+            // The owners' rows are on this model's connection
+            let connection = registered_connection(Self::connection()).ok_or_else(|| EloquentError::Configuration(
+                format!("no connection registered as [{}]", Self::connection()),
+            ))?;
+            let mut query = Query::<DynamicModel>::new().from(&definition.related_table).on_connection(connection);
+            query = match &definition.pivot {
+                Some(pivot) => query.where_raw(
+                    &format!("{}.{} IN (SELECT {}.{} FROM {} WHERE {}.{} = ?)",
+                        definition.related_table, pivot.related_key, pivot.table, pivot.related_pivot_key,
+                        pivot.table, pivot.table, definition.related_key),
+                    vec![key],
+                ),
+                None => query.where_op(format!("{}.{}", definition.related_table, definition.related_key), "=", key),
+            };
+            for (column, value) in &definition.constraints {
+                query = query.where_op(format!("{}.{}", definition.related_table, column), "=", value.clone());
+            }
+            query.touch().await?;
+        }
+        Ok(())
+    }
+
     // Instance helper to retrieve the primary key value from attributes
     // Default: not all models will provide attribute access; implementations that can should override
     fn get_key_value(&self) -> Option<serde_json::Value> {
         None
     }
 
+    // Whether this instance was inserted by the current operation (e.g. first_or_create).
+    // Models without a ModelInstance to hold the flag keep the defaults
+    fn was_recently_created(&self) -> bool {
+        false
+    }
+
+    fn set_was_recently_created(&mut self, _created: bool) {}
+
+    // Mass assignment - only fillable attributes are applied, others are discarded
+    fn fill(&mut self, attributes: HashMap<String, serde_json::Value>) -> Result<(), EloquentError> {
+        let fillable = Self::fillable();
+        let attributes = attributes
+            .into_iter()
+            .filter(|(key, _)| fillable.contains(&key.as_str()))
+            .collect();
+        self.force_fill(attributes)
+    }
+
+    // Build an unsaved instance from the fillable attributes, without touching the database.
+    // Option fields left out become None; any other missing field is a Serialization error,
+    // so models with a Default impl can use `Self::default()` plus fill() instead
+    fn make(attributes: HashMap<String, serde_json::Value>) -> Result<Self, EloquentError> {
+        let fillable = Self::fillable();
+        let fields = attributes
+            .into_iter()
+            .filter(|(key, _)| fillable.contains(&key.as_str()))
+            .collect();
+        Ok(serde_json::from_value(serde_json::Value::Object(fields))?)
+    }
+
+    // Mass assignment bypassing fillable. Values are applied through a serde round-trip,
+    // so every key must be a field in the model's serialized form
+    fn force_fill(&mut self, attributes: HashMap<String, serde_json::Value>) -> Result<(), EloquentError> {
+        let mut fields = match serde_json::to_value(&*self)? {
+            serde_json::Value::Object(fields) => fields,
+            _ => return Err(EloquentError::Serialization(serde::ser::Error::custom(
+                "model does not serialize to an object",
+            ))),
+        };
+
+        for (key, value) in attributes {
+            if !fields.contains_key(&key) {
+                return Err(EloquentError::UnknownAttribute(key));
+            }
+            fields.insert(key, value);
+        }
+
+        *self = serde_json::from_value(serde_json::Value::Object(fields))?;
+        Ok(())
+    }
+
     // Database operations
     async fn find(id: i64) -> Result<Option<Self>, sqlx::Error>;
     async fn all() -> Result<Vec<Self>, sqlx::Error>;
@@ -38,6 +195,14 @@ pub trait Model: Serialize + for<'de> Deserialize<'de> + Send + Sync + Clone + '
     async fn save(&mut self) -> Result<(), sqlx::Error>;
     async fn delete(&self) -> Result<(), sqlx::Error>;
     async fn update(&mut self, attributes: HashMap<String, serde_json::Value>) -> Result<(), sqlx::Error>;
+
+    // Save, touch the owners in touches() and hand the model back for chaining, e.g. reading
+    // the generated id. Relies on save() writing generated keys and timestamps back onto the model
+    async fn save_returning(mut self) -> Result<Self, sqlx::Error> {
+        self.save().await?;
+        self.touch_owners().await.map_err(EloquentError::into_sqlx)?;
+        Ok(self)
+    }
 }
 
 // Trait for models with timestamps
@@ -48,9 +213,16 @@ pub trait HasTimestamps: Model {
 }
 
 // Trait for soft deletes (like Laravel's SoftDeletes)
+// Implementors also return true from Model::soft_deletes() so queries filter trashed rows
+#[async_trait]
 pub trait SoftDeletes: Model {
-    fn deleted_at_column() -> &'static str { "deleted_at" }
-    fn trashed(&self) -> bool;
+    // Whether the model's deleted_at column (Model::deleted_at_column) is set
+    fn trashed(&self) -> bool {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|value| value.get(Self::deleted_at_column()).cloned())
+            .is_some_and(|deleted_at| !deleted_at.is_null())
+    }
     async fn restore(&mut self) -> Result<(), sqlx::Error>;
     async fn force_delete(&self) -> Result<(), sqlx::Error>;
 }
@@ -94,6 +266,12 @@ impl ModelInstance {
     }
 }
 
+impl Default for ModelInstance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Attributable for ModelInstance {
     fn get_attribute(&self, key: &str) -> Option<&serde_json::Value> {
         self.attributes.get(key)
@@ -124,4 +302,233 @@ impl Attributable for ModelInstance {
         }
         dirty
     }
-}
\ No newline at end of file
+}
+// Attribute access and dirty tracking for struct models. There is no derive for Attributable,
+// so the struct is wrapped: its serialized fields are cached as the attribute map, with a
+// snapshot of them as the original. Nested structs, maps and lists are single JSON values,
+// so changing anything inside one marks the whole field dirty
+#[derive(Debug, Clone)]
+pub struct Tracked<M: Model> {
+    model: M,
+    attributes: HashMap<String, serde_json::Value>,
+    original: HashMap<String, serde_json::Value>,
+    relations: RelationCache,
+    counts: HashMap<String, i64>,
+}
+
+// Results of relations explicitly loaded on a Tracked model, keyed by relation name
+#[derive(Clone, Default)]
+struct RelationCache(HashMap<String, Arc<dyn Any + Send + Sync>>);
+
+impl std::fmt::Debug for RelationCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
+
+impl<M: Model> Tracked<M> {
+    pub fn new(model: M) -> Result<Self, EloquentError> {
+        let attributes = to_attributes(&model)?;
+        Ok(Self {
+            model,
+            original: attributes.clone(),
+            attributes,
+            relations: RelationCache::default(),
+            counts: HashMap::new(),
+        })
+    }
+
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    pub fn into_inner(self) -> M {
+        self.model
+    }
+
+    // Applies the value to the struct through a serde round-trip, so it must have the
+    // field's type. On error both the struct and the attribute map are left unchanged
+    pub fn try_set_attribute(&mut self, key: &str, value: serde_json::Value) -> Result<(), EloquentError> {
+        let mut model = self.model.clone();
+        model.force_fill(HashMap::from([(key.to_string(), value)]))?;
+        self.attributes = to_attributes(&model)?;
+        self.model = model;
+        // A changed key may point the relations at different rows
+        self.relations.0.clear();
+        self.counts.clear();
+        Ok(())
+    }
+
+    // Run the relation and keep its rows on this instance under name, so later
+    // loaded/relation_exists calls reuse them until fresh_relation or forget_relation.
+    // Only relations loaded this way are cached, e.g. `user.load("posts", user.posts())`
+    pub async fn load<R, Rel>(&mut self, name: &str, relation: Rel) -> Result<&[R], sqlx::Error>
+    where
+        R: Model,
+        Rel: Relation<M, R>,
+    {
+        let rows: Arc<dyn Any + Send + Sync> = Arc::new(relation.get().await?);
+        self.relations.0.insert(name.to_string(), rows);
+        Ok(self.loaded(name).unwrap_or_default())
+    }
+
+    // Rows cached by load; None when name wasn't loaded or was loaded as another type
+    pub fn loaded<R: Model>(&self, name: &str) -> Option<&[R]> {
+        self.relations.0.get(name)?.downcast_ref::<Vec<R>>().map(Vec::as_slice)
+    }
+
+    pub fn relation_loaded(&self, name: &str) -> bool {
+        self.relations.0.contains_key(name)
+    }
+
+    // Answered from the cached rows when name was loaded; otherwise queried (and not cached)
+    pub async fn relation_exists<R, Rel>(&self, name: &str, relation: Rel) -> Result<bool, sqlx::Error>
+    where
+        R: Model,
+        Rel: Relation<M, R>,
+    {
+        match self.loaded::<R>(name) {
+            Some(rows) => Ok(!rows.is_empty()),
+            None => relation.get_query().exists().await,
+        }
+    }
+
+    // Reload a relation, replacing whatever was cached under name
+    pub async fn fresh_relation<R, Rel>(&mut self, name: &str, relation: Rel) -> Result<&[R], sqlx::Error>
+    where
+        R: Model,
+        Rel: Relation<M, R>,
+    {
+        self.forget_relation(name);
+        self.load(name, relation).await
+    }
+
+    pub fn forget_relation(&mut self, name: &str) {
+        self.relations.0.remove(name);
+    }
+
+    // Count the related rows of each relation and keep them for loaded_count. For many
+    // models at once use Query::load_count, which runs one query per relation for all of them
+    pub async fn load_count(&mut self, relations: Vec<&str>) -> Result<(), sqlx::Error> {
+        Query::<M>::new()
+            .from(&M::qualified_table_name())
+            .load_count(std::slice::from_mut(self), relations)
+            .await
+    }
+
+    // Count kept by load_count; None when name wasn't counted
+    pub fn loaded_count(&self, name: &str) -> Option<i64> {
+        self.counts.get(name).copied()
+    }
+
+    pub(crate) fn set_loaded_count(&mut self, name: &str, count: i64) {
+        self.counts.insert(name.to_string(), count);
+    }
+
+    // Mark the current attributes as persisted, e.g. after a save
+    pub fn sync_original(&mut self) {
+        self.original = self.attributes.clone();
+    }
+}
+
+impl<M: Model> std::ops::Deref for Tracked<M> {
+    type Target = M;
+
+    fn deref(&self) -> &M {
+        &self.model
+    }
+}
+
+impl<M: Model> Attributable for Tracked<M> {
+    fn get_attribute(&self, key: &str) -> Option<&serde_json::Value> {
+        self.attributes.get(key)
+    }
+
+    // Values that don't fit the field are ignored; use try_set_attribute to see the error
+    fn set_attribute(&mut self, key: &str, value: serde_json::Value) {
+        let _ = self.try_set_attribute(key, value);
+    }
+
+    fn get_attributes(&self) -> &HashMap<String, serde_json::Value> {
+        &self.attributes
+    }
+
+    fn get_original(&self) -> &HashMap<String, serde_json::Value> {
+        &self.original
+    }
+
+    fn is_dirty(&self) -> bool {
+        !self.get_dirty().is_empty()
+    }
+
+    // Non-persistent fields are never dirty, since they can't be saved
+    fn get_dirty(&self) -> HashMap<String, serde_json::Value> {
+        let computed = M::non_persistent();
+        self.attributes
+            .iter()
+            .filter(|(key, value)| self.original.get(*key) != Some(*value))
+            .filter(|(key, _)| !computed.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
+fn to_attributes<M: Model>(model: &M) -> Result<HashMap<String, serde_json::Value>, EloquentError> {
+    match serde_json::to_value(model)? {
+        serde_json::Value::Object(fields) => Ok(fields.into_iter().collect()),
+        _ => Err(EloquentError::Serialization(serde::ser::Error::custom(
+            "model does not serialize to an object",
+        ))),
+    }
+}
+
+// The keys of the models, e.g. for a follow-up where_in; models without a key are skipped
+pub fn model_keys<M: Model>(models: &[M]) -> Vec<serde_json::Value> {
+    models.iter()
+        .filter_map(|model| model.get_key_value().filter(|key| !key.is_null()))
+        .collect()
+}
+
+// As model_keys, for integer keys. Numeric strings are parsed; other keys are skipped
+pub fn model_keys_i64<M: Model>(models: &[M]) -> Vec<i64> {
+    model_keys(models).iter()
+        .filter_map(|key| key.as_i64().or_else(|| key.as_str()?.parse().ok()))
+        .collect()
+}
+
+// Field names of a struct model, read from its derived Deserialize impl (which passes them to
+// deserialize_struct) without needing an instance. These are the serialized names, so serde
+// renames are respected. None for models that don't deserialize as a struct, e.g. DynamicModel
+pub fn field_names<M: Model>() -> Option<&'static [&'static str]> {
+    let mut recorder = FieldRecorder { fields: None };
+    let _ = M::deserialize(&mut recorder);
+    recorder.fields
+}
+
+struct FieldRecorder {
+    fields: Option<&'static [&'static str]>,
+}
+
+impl<'de> serde::Deserializer<'de> for &mut FieldRecorder {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: serde::de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(serde::de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: serde::de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.fields = Some(fields);
+        Err(serde::de::Error::custom("fields recorded"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
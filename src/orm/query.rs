@@ -1,11 +1,45 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 use serde_json::Value;
-use crate::orm::model::Model;
+use serde::{Deserialize, Serialize};
+use crate::db::connection::{driver_for, DatabaseConnection, DatabaseTransaction, Driver};
+use crate::db::transaction::Executor;
+use crate::orm::ast::{ConditionNode, QueryAst};
+use crate::orm::diagnostics;
+use crate::orm::dynamic::DynamicModel;
+use crate::orm::error::EloquentError;
+use crate::orm::model::{model_keys, Attributable, Model, Tracked, SOFT_DELETE_SCOPE};
+use crate::orm::relations::{default_foreign_key, RelationDefinition};
+
+// When enabled, UPDATE/DELETE without a WHERE clause are refused app-wide
+static PREVENT_DESTRUCTIVE_WITHOUT_WHERE: AtomicBool = AtomicBool::new(false);
+
+pub fn prevent_destructive_without_where(enabled: bool) {
+    PREVENT_DESTRUCTIVE_WITHOUT_WHERE.store(enabled, Ordering::Relaxed);
+}
+
+// Rows fetched per round trip by reduce/for_each
+const REDUCE_PAGE_SIZE: i64 = 1000;
+
+// Which rows the soft-delete scope lets through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrashedScope {
+    WithoutTrashed,
+    WithTrashed,
+    OnlyTrashed,
+}
 
 #[derive(Debug, Clone)]
 pub struct Query<T> {
     table: Option<String>,
-    select_columns: Vec<String>,
+    select_columns: Vec<Expression>,
+    distinct: bool,
     where_conditions: Vec<WhereCondition>,
     joins: Vec<Join>,
     order_by: Vec<OrderBy>,
@@ -13,11 +47,116 @@ pub struct Query<T> {
     offset_value: Option<i64>,
     group_by: Vec<String>,
     having_conditions: Vec<WhereCondition>,
-    with_relations: Vec<String>,
+    unions: Vec<Union>,
+    with_relations: Vec<EagerLoad>,
+    eager_load_error: Option<String>, // Why with() rejected its relations, reported by get_loaded
+    allow_without_where: bool,
+    apply_global_scopes: bool,
+    removed_scopes: Vec<String>,
+    trashed: TrashedScope,
+    driver: Driver,
+    index_hint: Option<IndexHint>,
+    timeout: Option<Duration>,
+    connection: Option<BoundConnection>,
     _marker: PhantomData<T>,
 }
 
-#[derive(Debug, Clone)]
+// Connection handle a query was bound to with on_connection
+#[derive(Clone)]
+struct BoundConnection(Arc<dyn DatabaseConnection>);
+
+impl std::fmt::Debug for BoundConnection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("BoundConnection").field(&self.0.driver()).finish()
+    }
+}
+
+// Aggregate functions for with_aggregate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    pub fn sql_function(&self) -> &'static str {
+        match self {
+            Aggregate::Count => "COUNT",
+            Aggregate::Sum => "SUM",
+            Aggregate::Avg => "AVG",
+            Aggregate::Min => "MIN",
+            Aggregate::Max => "MAX",
+        }
+    }
+}
+
+// Where order_by_nulls puts NULLs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+impl NullsOrder {
+    pub fn sql(&self) -> &'static str {
+        match self {
+            NullsOrder::First => "NULLS FIRST",
+            NullsOrder::Last => "NULLS LAST",
+        }
+    }
+}
+
+// Nested eager loads deeper than this are rejected, e.g. "a.b.c.d.e.f"
+pub const MAX_EAGER_LOAD_DEPTH: usize = 5;
+
+// Alias of the parent key column get_loaded adds to related rows
+const EAGER_PARENT_KEY: &str = "eager_parent_key";
+
+// A relation to eager load, and the relations to load on its results in turn.
+// Each name is resolved through relation_definition() of the model at that level:
+// the top level against the queried model, nested ones against the related model
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EagerLoad {
+    pub relation: String,
+    pub nested: Vec<EagerLoad>,
+}
+
+impl EagerLoad {
+    // Group dotted paths by their first segment, so "posts.comments" and "posts.tags"
+    // load posts once with both nested relations
+    pub fn parse(relations: &[&str]) -> Result<Vec<EagerLoad>, EloquentError> {
+        let mut loads: Vec<EagerLoad> = Vec::new();
+        for relation in relations {
+            let segments: Vec<&str> = relation.split('.').filter(|segment| !segment.is_empty()).collect();
+            if segments.len() > MAX_EAGER_LOAD_DEPTH {
+                return Err(EloquentError::Configuration(format!(
+                    "Eager load [{}] is nested deeper than {} relations", relation, MAX_EAGER_LOAD_DEPTH,
+                )));
+            }
+            Self::insert(&mut loads, &segments);
+        }
+        Ok(loads)
+    }
+
+    fn insert(loads: &mut Vec<EagerLoad>, segments: &[&str]) {
+        let Some((first, rest)) = segments.split_first() else {
+            return;
+        };
+        let index = match loads.iter().position(|load| load.relation == *first) {
+            Some(index) => index,
+            None => {
+                loads.push(EagerLoad { relation: first.to_string(), nested: Vec::new() });
+                loads.len() - 1
+            }
+        };
+        Self::insert(&mut loads[index].nested, rest);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WhereCondition {
     pub column: String,
     pub operator: String,
@@ -25,7 +164,7 @@ pub struct WhereCondition {
     pub boolean: String, // AND, OR
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Join {
     pub table: String,
     pub first: String,
@@ -34,10 +173,207 @@ pub struct Join {
     pub join_type: String, // INNER, LEFT, RIGHT, etc.
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexHint {
+    pub kind: String, // USE, FORCE
+    pub index: String,
+}
+
+// Unescaped SQL from raw(), used as written wherever an Expression is taken
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawExpr(String);
+
+impl RawExpr {
+    pub fn sql(&self) -> &str {
+        &self.0
+    }
+}
+
+// Unescaped SQL for select, order_by and comparisons - similar to Laravel's DB::raw.
+// It is never quoted or bound, so don't build it from user input
+pub fn raw(sql: &str) -> RawExpr {
+    RawExpr(sql.to_string())
+}
+
+// A column name, quoted when rendered, or a raw expression, used as written. Bound raw
+// expressions keep their `?` placeholders until rendered, so the bindings are formatted
+// for the driver the query ends up with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Expression {
+    Column(String),
+    Raw(String),
+    Bound(String, Vec<Value>),
+    Window(WindowFunction),
+    Case(CaseExpression),
+}
+
+impl Expression {
+    // The SQL as given, placeholders included; window columns with their identifiers unquoted
+    pub fn sql(&self) -> Cow<'_, str> {
+        match self {
+            Expression::Column(sql) | Expression::Raw(sql) | Expression::Bound(sql, _) => Cow::Borrowed(sql),
+            Expression::Window(window) => Cow::Owned(window.render(&|column: &str| column.to_string())),
+            Expression::Case(case) => Cow::Owned(case.sql(&|_| "?".to_string(), case.alias.clone())),
+        }
+    }
+
+
+    // The name given with `AS`, e.g. with_count's posts_count
+    pub fn alias(&self) -> Option<&str> {
+        match self {
+            Expression::Column(sql) | Expression::Raw(sql) | Expression::Bound(sql, _) => {
+                sql.rsplit_once(" AS ").map(|(_, alias)| alias)
+            }
+            Expression::Window(window) => Some(&window.alias),
+            Expression::Case(case) => Some(&case.alias),
+        }
+    }
+
+    fn to_sql(&self, driver: Driver) -> String {
+        match self {
+            Expression::Column(column) => quote_identifier(column, driver),
+            Expression::Raw(sql) => sql.clone(),
+            Expression::Bound(sql, bindings) => inline_bindings(sql, bindings, driver),
+            Expression::Window(window) => window.render(&|column: &str| quote_identifier(column, driver)),
+            Expression::Case(case) => case.sql(&|value| format_value(value, driver), quote_identifier(&case.alias, driver)),
+        }
+    }
+}
+
+// `function OVER (PARTITION BY ... ORDER BY ...) AS alias`, kept apart so the columns are
+// quoted for the driver the query ends up with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowFunction {
+    pub function: String, // Used as written, e.g. ROW_NUMBER()
+    pub partition_by: Vec<String>,
+    pub order_by: Vec<(String, String)>, // Column and ASC/DESC
+    pub alias: String,
+}
+
+// `CASE WHEN condition THEN value ... ELSE value END AS alias`. The values are bindings, in
+// the order they appear, formatted for the driver the query ends up with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaseExpression {
+    pub whens: Vec<(String, Value)>, // Raw SQL condition and the value it yields
+    pub else_: Value,
+    pub alias: String,
+}
+
+impl CaseExpression {
+    fn bindings(&self) -> Vec<Value> {
+        self.whens.iter().map(|(_, then)| then).chain([&self.else_]).cloned().collect()
+    }
+
+    fn sql(&self, value: &dyn Fn(&Value) -> String, alias: String) -> String {
+        let whens: Vec<String> = self.whens.iter()
+            .map(|(condition, then)| format!("WHEN {} THEN {}", condition, value(then)))
+            .collect();
+        format!("CASE {} ELSE {} END AS {}", whens.join(" "), value(&self.else_), alias)
+    }
+}
+
+impl WindowFunction {
+    fn render(&self, quote: &dyn Fn(&str) -> String) -> String {
+        let mut window = Vec::new();
+        if !self.partition_by.is_empty() {
+            window.push(format!("PARTITION BY {}", self.partition_by.iter()
+                .map(|column| quote(column))
+                .collect::<Vec<_>>()
+                .join(", ")));
+        }
+        if !self.order_by.is_empty() {
+            window.push(format!("ORDER BY {}", self.order_by.iter()
+                .map(|(column, direction)| format!("{} {}", quote(column), direction))
+                .collect::<Vec<_>>()
+                .join(", ")));
+        }
+        format!("{} OVER ({}) AS {}", self.function, window.join(" "), quote(&self.alias))
+    }
+}
+
+impl From<&str> for Expression {
+    fn from(column: &str) -> Self {
+        Expression::Column(column.to_string())
+    }
+}
+
+impl From<&String> for Expression {
+    fn from(column: &String) -> Self {
+        Expression::Column(column.clone())
+    }
+}
+
+impl From<String> for Expression {
+    fn from(column: String) -> Self {
+        Expression::Column(column)
+    }
+}
+
+impl From<RawExpr> for Expression {
+    fn from(expression: RawExpr) -> Self {
+        Expression::Raw(expression.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct OrderBy {
     pub column: String,
-    pub direction: String, // ASC, DESC
+    pub direction: String, // ASC, DESC, optionally + NULLS FIRST/LAST; empty for raw expressions; RANDOM for in_random_order
+    pub bindings: Vec<Value>, // a raw expression's `?` values, filled in when the query is rendered
+}
+
+// A query added with union/union_all. It's rendered with the outer query's driver in to_sql,
+// so a later driver or on_connection applies to it too
+#[derive(Clone)]
+struct Union {
+    render: Arc<dyn Fn(Driver) -> String + Send + Sync>,
+    all: bool,
+}
+
+impl std::fmt::Debug for Union {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Union").field("all", &self.all).finish_non_exhaustive()
+    }
+}
+
+// What the builder methods recorded, for asserting on a query without going through to_sql
+#[cfg(feature = "test-utils")]
+pub struct Clauses<'a> {
+    where_conditions: &'a [WhereCondition],
+    joins: &'a [Join],
+    order_by: &'a [OrderBy],
+    limit_value: Option<i64>,
+}
+
+#[cfg(feature = "test-utils")]
+impl<'a> Clauses<'a> {
+    pub fn where_conditions(&self) -> &'a [WhereCondition] {
+        self.where_conditions
+    }
+
+    pub fn joins(&self) -> &'a [Join] {
+        self.joins
+    }
+
+    pub fn order_by(&self) -> &'a [OrderBy] {
+        self.order_by
+    }
+
+    pub fn limit_value(&self) -> Option<i64> {
+        self.limit_value
+    }
+}
+
+#[cfg(feature = "test-utils")]
+impl<T> Query<T> {
+    pub fn inspect_clauses(&self) -> Clauses<'_> {
+        Clauses {
+            where_conditions: &self.where_conditions,
+            joins: &self.joins,
+            order_by: &self.order_by,
+            limit_value: self.limit_value,
+        }
+    }
 }
 
 impl<T> Query<T>
@@ -47,7 +383,8 @@ where
     pub fn new() -> Self {
         Self {
             table: None,
-            select_columns: vec!["*".to_string()],
+            select_columns: vec![Expression::from("*")],
+            distinct: false,
             where_conditions: Vec::new(),
             joins: Vec::new(),
             order_by: Vec::new(),
@@ -55,19 +392,235 @@ where
             offset_value: None,
             group_by: Vec::new(),
             having_conditions: Vec::new(),
+            unions: Vec::new(),
             with_relations: Vec::new(),
+            eager_load_error: None,
+            allow_without_where: false,
+            apply_global_scopes: true,
+            removed_scopes: Vec::new(),
+            trashed: TrashedScope::WithoutTrashed,
+            driver: driver_for(T::connection()),
+            index_hint: None,
+            timeout: None,
+            connection: None,
             _marker: PhantomData,
         }
     }
 
+    pub fn from(mut self, table: &str) -> Self {
+        self.table = Some(table.to_string());
+        self
+    }
+
+    fn table_name(&self) -> String {
+        self.table.clone().unwrap_or_else(T::qualified_table_name)
+    }
+
+    // SQL dialect used where rendering differs between databases
+    pub fn driver(mut self, driver: Driver) -> Self {
+        self.driver = driver;
+        self
+    }
+
+    // Run terminal methods on this connection instead of the one registered for
+    // T::connection(), and render for its driver
+    pub fn on_connection(mut self, connection: Arc<dyn DatabaseConnection>) -> Self {
+        self.driver = connection.driver();
+        self.connection = Some(BoundConnection(connection));
+        self
+    }
+
+    // Independent copy of the query so far, the intended way to branch a shared base
+    // query (e.g. count vs list vs export); changes to the fork don't affect the base
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+
+    // Conditional building: f runs only when condition holds
+    pub fn when<F>(self, condition: bool, f: F) -> Self
+    where
+        F: FnOnce(Self) -> Self,
+    {
+        if condition { f(self) } else { self }
+    }
+
+    // f runs with the unwrapped value when there is one, e.g. an optional request parameter
+    pub fn when_some<V, F>(self, value: Option<V>, f: F) -> Self
+    where
+        F: FnOnce(Self, V) -> Self,
+    {
+        match value {
+            Some(value) => f(self, value),
+            None => self,
+        }
+    }
+
+    // f runs only when value is None, e.g. a default filter when no parameter was given
+    pub fn when_none<V, F>(self, value: &Option<V>, f: F) -> Self
+    where
+        F: FnOnce(Self) -> Self,
+    {
+        self.when(value.is_none(), f)
+    }
+
+    // Scopes - global scopes and the soft-delete filter are applied when the SQL is rendered
+    pub fn without_global_scopes(mut self) -> Self {
+        self.apply_global_scopes = false;
+        self
+    }
+
+    // Drop one named scope from Model::global_scopes(), leaving the others applied.
+    // SOFT_DELETE_SCOPE drops the soft-delete filter
+    pub fn without_global_scope(mut self, name: &str) -> Self {
+        self.removed_scopes.push(name.to_string());
+        self
+    }
+
+    pub fn with_trashed(mut self) -> Self {
+        self.trashed = TrashedScope::WithTrashed;
+        self
+    }
+
+    // only_trashed and without_trashed bring back a removed SOFT_DELETE_SCOPE
+    pub fn only_trashed(mut self) -> Self {
+        self.trashed = TrashedScope::OnlyTrashed;
+        self.removed_scopes.retain(|name| name != SOFT_DELETE_SCOPE);
+        self
+    }
+
+    pub fn without_trashed(mut self) -> Self {
+        self.trashed = TrashedScope::WithoutTrashed;
+        self.removed_scopes.retain(|name| name != SOFT_DELETE_SCOPE);
+        self
+    }
+
+    fn scope_removed(&self, name: &str) -> bool {
+        self.removed_scopes.iter().any(|removed| removed == name)
+    }
+
+    fn scope_conditions(&self) -> Vec<WhereCondition> {
+        if !self.apply_global_scopes {
+            return Vec::new();
+        }
+
+        let named = T::global_scopes()
+            .into_iter()
+            .filter(|(name, _)| !self.scope_removed(name))
+            .fold(Query::new(), |query, (_, apply)| apply(query));
+        let mut conditions = T::apply_global_scopes(named).where_conditions;
+        if T::soft_deletes() && !self.scope_removed(SOFT_DELETE_SCOPE) {
+            let column = format!("{}.{}", self.table_name(), T::deleted_at_column());
+            match self.trashed {
+                TrashedScope::WithoutTrashed => conditions.push(null_condition(&column, "IS NULL")),
+                TrashedScope::OnlyTrashed => conditions.push(null_condition(&column, "IS NOT NULL")),
+                TrashedScope::WithTrashed => {}
+            }
+        }
+        conditions
+    }
+
+    // The query's own conditions AND-ed with the scope conditions. Each side is
+    // parenthesized when it contains an OR so neither can widen the other
+    fn compile_where(&self) -> Option<String> {
+        let scopes = self.scope_conditions();
+        let groups: Vec<&[WhereCondition]> = [&self.where_conditions[..], &scopes[..]]
+            .into_iter()
+            .filter(|conditions| !conditions.is_empty())
+            .collect();
+
+        match groups.len() {
+            0 => None,
+            1 => Some(compile_conditions(groups[0], self.driver)),
+            _ => Some(groups.iter().map(|conditions| {
+                let sql = compile_conditions(conditions, self.driver);
+                if conditions.iter().skip(1).any(|c| c.boolean == "OR") {
+                    format!("({})", sql)
+                } else {
+                    sql
+                }
+            }).collect::<Vec<_>>().join(" AND ")),
+        }
+    }
+
     // Select methods
-    pub fn select(mut self, columns: Vec<&str>) -> Self {
-        self.select_columns = columns.iter().map(|s| s.to_string()).collect();
+    // Column names are quoted; raw() expressions are used as written, e.g.
+    // select(vec![Expression::from("status"), raw("COUNT(*) AS c").into()])
+    pub fn select<C: Into<Expression>>(mut self, columns: Vec<C>) -> Self {
+        self.select_columns = columns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    // Adds a raw expression to the select list, e.g. select(vec!["status"]).select_raw("COUNT(*) AS cnt");
+    // on its own it replaces the default `*`
+    pub fn select_raw(self, sql: &str) -> Self {
+        self.select_raw_bound(sql, Vec::new())
+    }
+
+    // As select_raw, with each `?` filled from bindings, e.g. ("COUNT(*) FILTER (WHERE status = ?) AS open", [json!("open")]).
+    // Each fragment keeps its own bindings, so they can't shift between select columns
+    pub fn select_raw_bound(mut self, sql: &str, bindings: Vec<Value>) -> Self {
+        debug_check_bindings(sql, &bindings);
+        if self.select_columns == [Expression::from("*")] {
+            self.select_columns.clear();
+        }
+        self.select_columns.push(match bindings.is_empty() {
+            true => Expression::Raw(sql.to_string()),
+            false => Expression::Bound(sql.to_string(), bindings),
+        });
+        self
+    }
+
+    // Replace the whole select list with one raw expression
+    pub fn set_select_raw(mut self, sql: &str, bindings: Vec<Value>) -> Self {
+        self.select_columns.clear();
+        self.select_raw_bound(sql, bindings)
+    }
+
+    // Adds `function OVER (PARTITION BY ... ORDER BY ...) AS alias` as a select column, e.g.
+    // select_window("rn", "ROW_NUMBER()", vec!["user_id"], vec![("created_at", "DESC")]).
+    // Needs MySQL 8+, MariaDB 10.2+ or SQLite 3.25+ (Postgres has always had window functions);
+    // on older servers the query fails with EloquentError::Unsupported before it's sent
+    pub fn select_window(mut self, alias: &str, function: &str, partition_by: Vec<&str>, order_by: Vec<(&str, &str)>) -> Self {
+        self.select_columns.push(Expression::Window(WindowFunction {
+            function: function.to_string(),
+            partition_by: partition_by.into_iter().map(str::to_string).collect(),
+            order_by: order_by.into_iter().map(|(column, direction)| (column.to_string(), direction.to_uppercase())).collect(),
+            alias: alias.to_string(),
+        }));
+        self
+    }
+
+    // Adds `CASE WHEN condition THEN value ... ELSE value END AS alias` as a select column.
+    // Conditions are raw SQL; the THEN/ELSE values are bound in order
+    pub fn select_case(mut self, alias: &str, whens: Vec<(&str, Value)>, else_: Value) -> Self {
+        self.select_columns.push(Expression::Case(CaseExpression {
+            whens: whens.into_iter().map(|(condition, then)| (condition.to_string(), then)).collect(),
+            else_,
+            alias: alias.to_string(),
+        }));
+        self
+    }
+
+    pub fn distinct(mut self) -> Self {
+        self.distinct = true;
         self
     }
 
-    pub fn select_raw(mut self, sql: &str) -> Self {
-        self.select_columns = vec![sql.to_string()];
+    // Combine with another query's rows, dropping duplicates. This query's order_by, limit
+    // and offset apply to the combined rows; the other query's stay with it. Both must
+    // select the same number of columns
+    pub fn union<U: Model>(self, query: Query<U>) -> Self {
+        self.push_union(query, false)
+    }
+
+    // As union, keeping duplicate rows
+    pub fn union_all<U: Model>(self, query: Query<U>) -> Self {
+        self.push_union(query, true)
+    }
+
+    fn push_union<U: Model>(mut self, query: Query<U>, all: bool) -> Self {
+        let render = move |driver| query.clone().driver(driver).to_sql();
+        self.unions.push(Union { render: Arc::new(render), all });
         self
     }
 
@@ -82,16 +635,40 @@ where
         self
     }
 
-    pub fn where_op(mut self, column: &str, operator: &str, value: Value) -> Self {
+    // A raw() column is compared as written, e.g. where_op(raw("LOWER(email)"), "=", json!("a@b.c"))
+    pub fn where_op<C: Into<Expression>>(mut self, column: C, operator: &str, value: Value) -> Self {
+        let (column, operator, value) = match column.into() {
+            Expression::Column(column) => (column, operator.to_string(), value),
+            // Raw left-hand sides are stored as "RAW <operator>", the value as [expression, value],
+            // so the expression is rendered for the driver the query ends up with
+            raw => (
+                raw.sql().into_owned(),
+                format!("RAW {}", operator),
+                Value::Array(vec![serde_json::to_value(&raw).unwrap_or_default(), value]),
+            ),
+        };
         self.where_conditions.push(WhereCondition {
-            column: column.to_string(),
-            operator: operator.to_string(),
+            column,
+            operator,
             value,
             boolean: "AND".to_string(),
         });
         self
     }
 
+    // Compare a column with a raw expression instead of a literal, e.g.
+    // where_expr("expires_at", "<", raw("NOW() - INTERVAL '1 day'"))
+    pub fn where_expr(mut self, column: &str, operator: &str, expression: RawExpr) -> Self {
+        self.where_conditions.push(WhereCondition {
+            column: column.to_string(),
+            // Stored as "EXPR <operator>" with the expression as the value
+            operator: format!("EXPR {}", operator),
+            value: Value::String(expression.0),
+            boolean: "AND".to_string(),
+        });
+        self
+    }
+
     pub fn where_in(mut self, column: &str, values: Vec<Value>) -> Self {
         self.where_conditions.push(WhereCondition {
             column: column.to_string(),
@@ -112,6 +689,54 @@ where
         self
     }
 
+    // IN list of the models' keys - models that have no key yet are skipped. With no keys
+    // left it matches no rows, as `IN ()` isn't valid SQL
+    pub fn where_in_models<M: Model>(self, column: &str, models: &[M]) -> Self {
+        self.where_in_keys(column, model_keys(models))
+    }
+
+    // Same as where_in_models, but a model without a key is an error
+    pub fn try_where_in_models<M: Model>(self, column: &str, models: &[M]) -> Result<Self, EloquentError> {
+        let keys = models
+            .iter()
+            .map(|model| model_key(model).ok_or_else(|| EloquentError::MissingKey(M::table_name().to_string())))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.where_in_keys(column, keys))
+    }
+
+    fn where_in_keys(self, column: &str, keys: Vec<Value>) -> Self {
+        if keys.is_empty() {
+            return self.where_raw("0 = 1", Vec::new());
+        }
+        self.where_in(column, keys)
+    }
+
+    // Rows belonging to parent, with the foreign key inferred as for belongs_to relations.
+    // A parent without a key matches no rows
+    pub fn where_belongs_to<P: Model>(self, parent: &P) -> Self {
+        let foreign_key = default_foreign_key::<P, T>();
+        self.where_belongs_to_key(parent, &foreign_key)
+    }
+
+    pub fn where_belongs_to_key<P: Model>(self, parent: &P, foreign_key: &str) -> Self {
+        match model_key(parent) {
+            Some(key) => self.where_op(foreign_key, "=", key),
+            None => self.where_raw("0 = 1", Vec::new()),
+        }
+    }
+
+    // Composite IN: (a, b) IN ((1, 2), (3, 4)). SQLite lacks row-value IN, so it
+    // gets the equivalent OR of ANDs
+    pub fn where_in_columns(mut self, columns: Vec<&str>, rows: Vec<Vec<Value>>) -> Self {
+        self.where_conditions.push(WhereCondition {
+            column: columns.join(", "),
+            operator: "ROW IN".to_string(),
+            value: Value::Array(rows.into_iter().map(Value::Array).collect()),
+            boolean: "AND".to_string(),
+        });
+        self
+    }
+
     pub fn where_null(mut self, column: &str) -> Self {
         self.where_conditions.push(WhereCondition {
             column: column.to_string(),
@@ -132,6 +757,43 @@ where
         self
     }
 
+    // One `column = value` condition per pair, AND-joined in the given order
+    pub fn where_many(self, conditions: Vec<(&str, Value)>) -> Self {
+        conditions.into_iter().fold(self, |query, (column, value)| query.where_op(column, "=", value))
+    }
+
+    // One IS NULL condition per column, AND-joined
+    pub fn where_null_many(self, columns: Vec<&str>) -> Self {
+        columns.into_iter().fold(self, |query, column| query.where_null(column))
+    }
+
+    pub fn where_not_null_many(self, columns: Vec<&str>) -> Self {
+        columns.into_iter().fold(self, |query, column| query.where_not_null(column))
+    }
+
+    // As where_op, casting the value to pg_type on Postgres, e.g. ("status", "=", json!("open"),
+    // "ticket_status") for an enum column or "jsonb" for JSON. Other drivers ignore the type
+    pub fn where_op_typed(mut self, column: &str, operator: &str, value: Value, pg_type: &str) -> Self {
+        let value = Value::Array(vec![value, Value::from(pg_type)]);
+        self.where_conditions.push(condition(column, &format!("TYPED {}", operator), value, "AND"));
+        self
+    }
+
+    // Compare the length of a JSON array in the column, e.g. ("settings", "tags", ">", json!(3)).
+    // path is dot-separated keys and array indexes, or "" for the column itself
+    pub fn where_json_length(mut self, column: &str, path: &str, operator: &str, value: Value) -> Self {
+        let value = Value::Array(vec![Value::from(path), value]);
+        self.where_conditions.push(condition(column, &format!("JSON_LENGTH {}", operator), value, "AND"));
+        self
+    }
+
+    // Rows whose JSON column has the key, even when its value is null, e.g.
+    // ("settings", "notifications.email"); a trailing index checks an array is that long
+    pub fn where_json_contains_key(mut self, column: &str, key: &str) -> Self {
+        self.where_conditions.push(condition(column, "JSON_KEY", Value::from(key), "AND"));
+        self
+    }
+
     pub fn or_where(mut self, column: &str, operator: &str, value: Value) -> Self {
         self.where_conditions.push(WhereCondition {
             column: column.to_string(),
@@ -142,55 +804,370 @@ where
         self
     }
 
-    // Join methods
-    pub fn join(mut self, table: &str, first: &str, operator: &str, second: &str) -> Self {
-        self.joins.push(Join {
-            table: table.to_string(),
-            first: first.to_string(),
-            operator: operator.to_string(),
-            second: second.to_string(),
-            join_type: "INNER".to_string(),
-        });
+    pub fn where_between(mut self, column: &str, min: Value, max: Value) -> Self {
+        self.where_conditions.push(between_condition(column, "BETWEEN", min, max));
         self
     }
 
-    pub fn left_join(mut self, table: &str, first: &str, operator: &str, second: &str) -> Self {
-        self.joins.push(Join {
-            table: table.to_string(),
-            first: first.to_string(),
-            operator: operator.to_string(),
-            second: second.to_string(),
-            join_type: "LEFT".to_string(),
-        });
+    pub fn where_not_between(mut self, column: &str, min: Value, max: Value) -> Self {
+        self.where_conditions.push(between_condition(column, "NOT BETWEEN", min, max));
         self
     }
 
-    pub fn right_join(mut self, table: &str, first: &str, operator: &str, second: &str) -> Self {
-        self.joins.push(Join {
-            table: table.to_string(),
-            first: first.to_string(),
-            operator: operator.to_string(),
-            second: second.to_string(),
-            join_type: "RIGHT".to_string(),
+    pub fn or_where_between(mut self, column: &str, min: Value, max: Value) -> Self {
+        self.where_conditions.push(WhereCondition {
+            boolean: "OR".to_string(),
+            ..between_condition(column, "BETWEEN", min, max)
         });
         self
     }
 
-    // Order methods
-    pub fn order_by(mut self, column: &str, direction: &str) -> Self {
-        self.order_by.push(OrderBy {
-            column: column.to_string(),
-            direction: direction.to_uppercase(),
+    pub fn or_where_not_between(mut self, column: &str, min: Value, max: Value) -> Self {
+        self.where_conditions.push(WhereCondition {
+            boolean: "OR".to_string(),
+            ..between_condition(column, "NOT BETWEEN", min, max)
         });
         self
     }
 
-    pub fn order_by_asc(self, column: &str) -> Self {
-        self.order_by(column, "ASC")
+    pub fn or_where_in(mut self, column: &str, values: Vec<Value>) -> Self {
+        self.where_conditions.push(condition(column, "IN", Value::Array(values), "OR"));
+        self
     }
 
-    pub fn order_by_desc(self, column: &str) -> Self {
-        self.order_by(column, "DESC")
+    pub fn or_where_not_in(mut self, column: &str, values: Vec<Value>) -> Self {
+        self.where_conditions.push(condition(column, "NOT IN", Value::Array(values), "OR"));
+        self
+    }
+
+    pub fn or_where_null(mut self, column: &str) -> Self {
+        self.where_conditions.push(condition(column, "IS NULL", Value::Null, "OR"));
+        self
+    }
+
+    pub fn or_where_not_null(mut self, column: &str) -> Self {
+        self.where_conditions.push(condition(column, "IS NOT NULL", Value::Null, "OR"));
+        self
+    }
+
+    // LIKE patterns are passed through as-is, so include the % wildcards
+    pub fn where_like(mut self, column: &str, pattern: &str) -> Self {
+        self.where_conditions.push(condition(column, "LIKE", Value::String(pattern.to_string()), "AND"));
+        self
+    }
+
+    pub fn where_not_like(mut self, column: &str, pattern: &str) -> Self {
+        self.where_conditions.push(condition(column, "NOT LIKE", Value::String(pattern.to_string()), "AND"));
+        self
+    }
+
+    pub fn or_where_like(mut self, column: &str, pattern: &str) -> Self {
+        self.where_conditions.push(condition(column, "LIKE", Value::String(pattern.to_string()), "OR"));
+        self
+    }
+
+    pub fn or_where_not_like(mut self, column: &str, pattern: &str) -> Self {
+        self.where_conditions.push(condition(column, "NOT LIKE", Value::String(pattern.to_string()), "OR"));
+        self
+    }
+
+    // Case-insensitive comparison that doesn't depend on the column's collation, e.g.
+    // ("email", "=", json!("Bob@Example.com")) or ("name", "LIKE", json!("%smith%"))
+    pub fn where_ci(mut self, column: &str, operator: &str, value: Value) -> Self {
+        self.where_conditions.push(condition(column, &format!("CI {}", operator), value, "AND"));
+        self
+    }
+
+    pub fn or_where_ci(mut self, column: &str, operator: &str, value: Value) -> Self {
+        self.where_conditions.push(condition(column, &format!("CI {}", operator), value, "OR"));
+        self
+    }
+
+    // Compare against the database clock rather than a bound literal
+    pub fn where_future(mut self, column: &str) -> Self {
+        self.where_conditions.push(date_condition(column, "FUTURE"));
+        self
+    }
+
+    pub fn where_past(mut self, column: &str) -> Self {
+        self.where_conditions.push(date_condition(column, "PAST"));
+        self
+    }
+
+    pub fn where_today(mut self, column: &str) -> Self {
+        self.where_conditions.push(date_condition(column, "TODAY"));
+        self
+    }
+
+    // Compare part of a date/time column; the two-argument forms compare with `=`
+    pub fn where_date(self, column: &str, value: Value) -> Self {
+        self.where_date_op(column, "=", value)
+    }
+
+    pub fn where_date_op(mut self, column: &str, operator: &str, value: Value) -> Self {
+        self.where_conditions.push(condition(column, &format!("DATE {}", operator), value, "AND"));
+        self
+    }
+
+    // Compare only the time of day, e.g. where_time("starts_at", ">", "18:00:00")
+    pub fn where_time(mut self, column: &str, operator: &str, time: &str) -> Self {
+        self.where_conditions.push(condition(column, &format!("TIME {}", operator), Value::from(time), "AND"));
+        self
+    }
+
+    pub fn where_year(self, column: &str, value: Value) -> Self {
+        self.where_year_op(column, "=", value)
+    }
+
+    pub fn where_year_op(mut self, column: &str, operator: &str, value: Value) -> Self {
+        self.where_conditions.push(condition(column, &format!("YEAR {}", operator), value, "AND"));
+        self
+    }
+
+    pub fn where_month(self, column: &str, value: Value) -> Self {
+        self.where_month_op(column, "=", value)
+    }
+
+    pub fn where_month_op(mut self, column: &str, operator: &str, value: Value) -> Self {
+        self.where_conditions.push(condition(column, &format!("MONTH {}", operator), value, "AND"));
+        self
+    }
+
+    pub fn where_day(self, column: &str, value: Value) -> Self {
+        self.where_day_op(column, "=", value)
+    }
+
+    pub fn where_day_op(mut self, column: &str, operator: &str, value: Value) -> Self {
+        self.where_conditions.push(condition(column, &format!("DAY {}", operator), value, "AND"));
+        self
+    }
+
+    // Raw SQL condition; each `?` is filled from bindings in order
+    pub fn where_raw(mut self, sql: &str, bindings: Vec<Value>) -> Self {
+        debug_check_bindings(sql, &bindings);
+        self.where_conditions.push(raw_condition(sql, bindings));
+        self
+    }
+
+    // Relation existence methods
+    pub fn where_relation(self, relation: &str, column: &str, operator: &str, value: Value) -> Self {
+        self.add_where_relation(relation, column, operator, value, "AND")
+    }
+
+    pub fn or_where_relation(self, relation: &str, column: &str, operator: &str, value: Value) -> Self {
+        self.add_where_relation(relation, column, operator, value, "OR")
+    }
+
+    // Rows with at least one related row matching the constraints built by f
+    pub fn where_has<F>(self, relation: &str, f: F) -> Self
+    where
+        F: FnOnce(Query<DynamicModel>) -> Query<DynamicModel>,
+    {
+        let constraints = f(Query::new()).where_conditions;
+        self.add_relation_existence(relation, "EXISTS", &constraints, "AND")
+    }
+
+    // Rows with no related row matching the constraints built by f
+    pub fn where_doesnt_have<F>(self, relation: &str, f: F) -> Self
+    where
+        F: FnOnce(Query<DynamicModel>) -> Query<DynamicModel>,
+    {
+        let constraints = f(Query::new()).where_conditions;
+        self.add_relation_existence(relation, "NOT EXISTS", &constraints, "AND")
+    }
+
+    // OR variants; like or_where, each is one OR-ed condition, so `a AND b OR EXISTS (...)`
+    // reads as `(a AND b) OR EXISTS (...)`
+    pub fn or_where_has<F>(self, relation: &str, f: F) -> Self
+    where
+        F: FnOnce(Query<DynamicModel>) -> Query<DynamicModel>,
+    {
+        let constraints = f(Query::new()).where_conditions;
+        self.add_relation_existence(relation, "EXISTS", &constraints, "OR")
+    }
+
+    pub fn or_where_doesnt_have<F>(self, relation: &str, f: F) -> Self
+    where
+        F: FnOnce(Query<DynamicModel>) -> Query<DynamicModel>,
+    {
+        let constraints = f(Query::new()).where_conditions;
+        self.add_relation_existence(relation, "NOT EXISTS", &constraints, "OR")
+    }
+
+    // Rows whose number of related rows compares to count, e.g. has("posts", ">=", 3)
+    pub fn has(mut self, relation: &str, operator: &str, count: i64) -> Self {
+        let (subquery, mut bindings) = self.relation_subquery(relation, "COUNT(*)");
+        bindings.push(Value::from(count));
+        self.where_conditions.push(raw_condition(&format!("({}) {} ?", subquery, operator), bindings));
+        self
+    }
+
+    fn add_where_relation(self, relation: &str, column: &str, operator: &str, value: Value, boolean: &str) -> Self {
+        let constraint = WhereCondition {
+            column: column.to_string(),
+            operator: operator.to_string(),
+            value,
+            boolean: "AND".to_string(),
+        };
+        self.add_relation_existence(relation, "EXISTS", &[constraint], boolean)
+    }
+
+    // The constraints are kept as conditions and compiled with the rest of the query, so they
+    // are quoted for the driver it ends up with
+    fn add_relation_existence(mut self, relation: &str, operator: &str, constraints: &[WhereCondition], boolean: &str) -> Self {
+        let (subquery, bindings) = self.relation_subquery(relation, "*");
+        self.where_conditions.push(WhereCondition {
+            column: subquery,
+            operator: operator.to_string(),
+            value: Value::Array(vec![
+                Value::Array(bindings),
+                serde_json::to_value(constraints).unwrap_or_default(),
+            ]),
+            boolean: boolean.to_string(),
+        });
+        self
+    }
+
+    // Correlated subquery over a named relation's rows, matched back to this model's table.
+    // The relation's own constraint values are left as `?` placeholders and returned as bindings
+    fn relation_subquery(&self, relation: &str, select: &str) -> (String, Vec<Value>) {
+        let definition = relation_definition::<T>(relation);
+        let mut sql = format!("SELECT {} FROM {}", select, relation_from(&definition));
+        sql.push_str(&format!(" WHERE {}.{} = {}.{}",
+            definition.key_table(), definition.related_key, self.table_name(), definition.parent_key));
+        for (column, _) in &definition.constraints {
+            sql.push_str(&format!(" AND {}.{} = ?", definition.related_table, column));
+        }
+        (sql, definition.constraints.iter().map(|(_, value)| value.clone()).collect())
+    }
+
+    // Index hints - MySQL USE/FORCE INDEX, SQLite INDEXED BY, and on Postgres a
+    // pg_hint_plan comment (ignored unless that extension is loaded)
+    pub fn use_index(self, index: &str) -> Self {
+        self.index_hint("USE", index)
+    }
+
+    pub fn force_index(self, index: &str) -> Self {
+        self.index_hint("FORCE", index)
+    }
+
+    fn index_hint(mut self, kind: &str, index: &str) -> Self {
+        self.index_hint = Some(IndexHint {
+            kind: kind.to_string(),
+            index: index.to_string(),
+        });
+        self
+    }
+
+    // Join methods
+    pub fn join(mut self, table: &str, first: &str, operator: &str, second: &str) -> Self {
+        self.joins.push(Join {
+            table: table.to_string(),
+            first: first.to_string(),
+            operator: operator.to_string(),
+            second: second.to_string(),
+            join_type: "INNER".to_string(),
+        });
+        self
+    }
+
+    pub fn left_join(mut self, table: &str, first: &str, operator: &str, second: &str) -> Self {
+        self.joins.push(Join {
+            table: table.to_string(),
+            first: first.to_string(),
+            operator: operator.to_string(),
+            second: second.to_string(),
+            join_type: "LEFT".to_string(),
+        });
+        self
+    }
+
+    pub fn right_join(mut self, table: &str, first: &str, operator: &str, second: &str) -> Self {
+        self.joins.push(Join {
+            table: table.to_string(),
+            first: first.to_string(),
+            operator: operator.to_string(),
+            second: second.to_string(),
+            join_type: "RIGHT".to_string(),
+        });
+        self
+    }
+
+    // Order methods
+    // A raw() expression is ordered by as written, e.g. order_by(raw("LENGTH(name)"), "desc")
+    pub fn order_by<C: Into<Expression>>(mut self, column: C, direction: &str) -> Self {
+        let order = match column.into() {
+            Expression::Column(column) => OrderBy { column, direction: direction.to_uppercase(), bindings: Vec::new() },
+            // Raw orderings carry their direction in the column, like order_by_raw
+            raw => OrderBy {
+                column: format!("{} {}", raw.sql(), direction.to_uppercase()).trim_end().to_string(),
+                direction: String::new(),
+                bindings: match raw {
+                    Expression::Bound(_, bindings) => bindings,
+                    _ => Vec::new(),
+                },
+            },
+        };
+        self.order_by.push(order);
+        self
+    }
+
+    // As order_by, with NULLs sorted first or last whatever the direction. Postgres and SQLite
+    // (3.30+) take NULLS FIRST/LAST; MySQL has no such clause, so it sorts on `column IS NULL` first
+    pub fn order_by_nulls(mut self, column: &str, direction: &str, nulls: NullsOrder) -> Self {
+        self.order_by.push(OrderBy {
+            column: column.to_string(),
+            direction: format!("{} {}", direction.to_uppercase(), nulls.sql()),
+            bindings: Vec::new(),
+        });
+        self
+    }
+
+    // Raw ORDER BY expression, including its own direction
+    pub fn order_by_raw(self, sql: &str) -> Self {
+        self.order_by_raw_bound(sql, Vec::new())
+    }
+
+    // As order_by_raw, with each `?` filled from bindings, e.g. ("status = ? DESC", [json!("open")])
+    pub fn order_by_raw_bound(mut self, sql: &str, bindings: Vec<Value>) -> Self {
+        debug_check_bindings(sql, &bindings);
+        self.order_by.push(OrderBy {
+            column: sql.to_string(),
+            direction: String::new(),
+            bindings,
+        });
+        self
+    }
+
+    // Shuffle the rows, e.g. with limit(n) to sample n of them. The database has to sort
+    // the whole result set to do this, so avoid it on large tables
+    pub fn in_random_order(mut self) -> Self {
+        self.order_by.push(OrderBy {
+            column: String::new(),
+            direction: "RANDOM".to_string(),
+            bindings: Vec::new(),
+        });
+        self
+    }
+
+    // Drop every ordering added so far, raw and random ones included, e.g. to reuse a
+    // base query with a different sort
+    pub fn reorder(mut self) -> Self {
+        self.order_by.clear();
+        self
+    }
+
+    // As reorder, then order by column
+    pub fn reorder_by<C: Into<Expression>>(self, column: C, direction: &str) -> Self {
+        self.reorder().order_by(column, direction)
+    }
+
+    pub fn order_by_asc(self, column: &str) -> Self {
+        self.order_by(column, "ASC")
+    }
+
+    pub fn order_by_desc(self, column: &str) -> Self {
+        self.order_by(column, "DESC")
     }
 
     pub fn latest(self, column: Option<&str>) -> Self {
@@ -218,141 +1195,1339 @@ where
         self.offset(skip)
     }
 
-    pub fn take(self, take: i64) -> Self {
-        self.limit(take)
+    pub fn take(self, take: i64) -> Self {
+        self.limit(take)
+    }
+
+    // One page of per_page rows without counting the total, e.g. for_page(3, 20) skips 40.
+    // Pages below 1 are treated as the first page and per_page is at least 1
+    pub fn for_page(self, page: i64, per_page: i64) -> Self {
+        let per_page = per_page.max(1);
+        self.offset((page.max(1) - 1) * per_page).limit(per_page)
+    }
+
+    // Group by and having
+    pub fn group_by(mut self, columns: Vec<&str>) -> Self {
+        self.group_by = columns.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    pub fn having(mut self, column: &str, operator: &str, value: Value) -> Self {
+        self.having_conditions.push(WhereCondition {
+            column: column.to_string(),
+            operator: operator.to_string(),
+            value,
+            boolean: "AND".to_string(),
+        });
+        self
+    }
+
+    // HAVING COUNT(*) operator value, e.g. having_count(">", 5) for groups of more than five rows
+    pub fn having_count(self, operator: &str, value: i64) -> Self {
+        self.having("COUNT(*)", operator, Value::from(value))
+    }
+
+    // HAVING function(column) operator value, e.g. (Aggregate::Sum, "total", ">", json!(100))
+    pub fn having_aggregate(mut self, function: Aggregate, column: &str, operator: &str, value: Value) -> Self {
+        self.having_conditions.push(condition(column, &format!("{} {}", function.sql_function(), operator), value, "AND"));
+        self
+    }
+
+    pub fn having_in(mut self, column: &str, values: Vec<Value>) -> Self {
+        self.having_conditions.push(WhereCondition {
+            column: column.to_string(),
+            operator: "IN".to_string(),
+            value: Value::Array(values),
+            boolean: "AND".to_string(),
+        });
+        self
+    }
+
+    pub fn having_between(mut self, column: &str, min: Value, max: Value) -> Self {
+        self.having_conditions.push(between_condition(column, "BETWEEN", min, max));
+        self
+    }
+
+    pub fn having_raw(mut self, sql: &str, bindings: Vec<Value>) -> Self {
+        debug_check_bindings(sql, &bindings);
+        self.having_conditions.push(raw_condition(sql, bindings));
+        self
+    }
+
+    // Eager loading - dotted names load nested relations, e.g. "posts.comments". Names nested
+    // deeper than MAX_EAGER_LOAD_DEPTH make get_loaded fail with EloquentError::Configuration
+    pub fn with(mut self, relations: Vec<&str>) -> Self {
+        match EagerLoad::parse(&relations) {
+            Ok(loads) => {
+                self.with_relations = loads;
+                self.eager_load_error = None;
+            }
+            Err(error) => {
+                self.with_relations = Vec::new();
+                self.eager_load_error = Some(match error {
+                    EloquentError::Configuration(message) => message,
+                    error => error.to_string(),
+                });
+            }
+        }
+        self
+    }
+
+    pub fn eager_loads(&self) -> &[EagerLoad] {
+        &self.with_relations
+    }
+
+    // Adds a correlated aggregate over a relation as an extra select column, aliased
+    // like Laravel's: posts_count, orders_sum_total, orders_avg_total, ...
+    pub fn with_aggregate(mut self, relation: &str, column: &str, function: Aggregate) -> Self {
+        let (expression, alias) = match function {
+            Aggregate::Count => ("COUNT(*)".to_string(), format!("{}_count", relation)),
+            _ => {
+                let related_table = T::relation_definition(relation)
+                    .map(|definition| definition.related_table)
+                    .unwrap_or_default();
+                (format!("{}({}.{})", function.sql_function(), related_table, column),
+                 format!("{}_{}_{}", relation, function.sql_function().to_lowercase(), column))
+            }
+        };
+
+        let (subquery, bindings) = self.relation_subquery(relation, &expression);
+        self.select_columns.push(Expression::Bound(format!("({}) AS {}", subquery, alias), bindings));
+        self
+    }
+
+    pub fn with_count(self, relation: &str) -> Self {
+        self.with_aggregate(relation, "*", Aggregate::Count)
+    }
+
+    pub fn with_sum(self, relation: &str, column: &str) -> Self {
+        self.with_aggregate(relation, column, Aggregate::Sum)
+    }
+
+    pub fn with_avg(self, relation: &str, column: &str) -> Self {
+        self.with_aggregate(relation, column, Aggregate::Avg)
+    }
+
+    pub fn with_min(self, relation: &str, column: &str) -> Self {
+        self.with_aggregate(relation, column, Aggregate::Min)
+    }
+
+    pub fn with_max(self, relation: &str, column: &str) -> Self {
+        self.with_aggregate(relation, column, Aggregate::Max)
+    }
+
+    // Counts for relations of models that were fetched without with_count: one grouped
+    // COUNT(*) query per relation for all of the models, kept on each model for loaded_count.
+    // Like with_count, the related model's global scopes and soft-delete filter don't apply
+    pub async fn load_count(&self, models: &mut [Tracked<T>], relations: Vec<&str>) -> Result<(), sqlx::Error> {
+        for relation in relations {
+            let definition = relation_definition::<T>(relation);
+            let keys: Vec<Value> = models.iter()
+                .filter_map(|model| model.get_attribute(&definition.parent_key))
+                .filter(|key| !key.is_null())
+                .cloned()
+                .collect();
+
+            let mut counts = HashMap::new();
+            if !keys.is_empty() {
+                let sql = self.to_relation_count_sql(relation, &keys);
+                let rows: Vec<HashMap<String, Value>> = self.run(&sql, self.fetch_rows(&sql)).await?;
+                for mut row in rows {
+                    if let (Some(key), Some(count)) = (row.remove("parent_key"), row.remove("aggregate")) {
+                        counts.insert(key_text(&key), integer("aggregate", count)?);
+                    }
+                }
+            }
+
+            for model in models.iter_mut() {
+                let count = model.get_attribute(&definition.parent_key)
+                    .and_then(|key| counts.get(&key_text(key)))
+                    .copied()
+                    .unwrap_or(0);
+                model.set_loaded_count(relation, count);
+            }
+        }
+        Ok(())
+    }
+
+    // Both columns are cast to text, as integers don't decode on every driver
+    pub fn to_relation_count_sql(&self, relation: &str, keys: &[Value]) -> String {
+        let definition = relation_definition::<T>(relation);
+        let key = format!("{}.{}", definition.key_table(), definition.related_key);
+        let text = if self.driver == Driver::MySql { "CHAR" } else { "TEXT" };
+        format!("SELECT CAST({} AS {}) AS parent_key, CAST(COUNT(*) AS {}) AS aggregate FROM {} WHERE {} IN ({}){} GROUP BY {}",
+            key, text, text, relation_from(&definition), key,
+            keys.iter().map(|key| format_value(key, self.driver)).collect::<Vec<_>>().join(", "),
+            self.relation_constraints(&definition), key)
+    }
+
+    // The related rows of every parent in keys, each tagged with its parent's key (as text)
+    pub fn to_eager_load_sql(&self, relation: &str, keys: &[Value]) -> String {
+        self.eager_load_sql(&relation_definition::<T>(relation), keys)
+    }
+
+    fn eager_load_sql(&self, definition: &RelationDefinition, keys: &[Value]) -> String {
+        let key = format!("{}.{}", definition.key_table(), definition.related_key);
+        let text = if self.driver == Driver::MySql { "CHAR" } else { "TEXT" };
+        let trashed = match &definition.related_deleted_at {
+            Some(column) => format!(" AND {}.{} IS NULL", definition.related_table, column),
+            None => String::new(),
+        };
+        format!("SELECT {}.*, CAST({} AS {}) AS {} FROM {} WHERE {} IN ({}){}{}",
+            definition.related_table, key, text, EAGER_PARENT_KEY, relation_from(definition), key,
+            keys.iter().map(|key| format_value(key, self.driver)).collect::<Vec<_>>().join(", "),
+            self.relation_constraints(definition), trashed)
+    }
+
+    // Give up on statements that run longer than duration. The wait is abandoned client-side on
+    // every driver; MySQL SELECTs also carry a MAX_EXECUTION_TIME hint so the server stops too
+    pub fn timeout(mut self, duration: Duration) -> Self {
+        self.timeout = Some(duration);
+        self
+    }
+
+    // Every statement the builder runs goes through here
+    async fn run<R>(&self, sql: &str, statement: impl Future<Output = Result<R, sqlx::Error>>) -> Result<R, sqlx::Error> {
+        diagnostics::record_query(sql);
+        if diagnostics::validating_columns() {
+            diagnostics::check_columns(&self.table_name(), &self.referenced_columns())
+                .map_err(EloquentError::into_sqlx)?;
+        }
+        let started = Instant::now();
+        let result = match self.timeout {
+            Some(duration) => tokio::time::timeout(duration, statement)
+                .await
+                .map_err(|_| EloquentError::Timeout(duration).into_sqlx())
+                .and_then(|result| result),
+            None => statement.await,
+        };
+        if diagnostics::logging_queries() {
+            diagnostics::log_query(sql, started.elapsed());
+        }
+        result
+    }
+
+    // Names given to select columns with `AS`, e.g. with_count's posts_count
+    fn select_aliases(&self) -> Vec<&str> {
+        self.select_columns.iter().filter_map(Expression::alias).collect()
+    }
+
+    // Plain column references, for validate_columns. Expressions, raw SQL, subqueries
+    // and orderings by a select alias are skipped
+    fn referenced_columns(&self) -> Vec<String> {
+        let aliases = self.select_aliases();
+        let conditions = self.where_conditions.iter().filter(|condition| {
+            !matches!(condition.operator.as_str(), "RAW" | "EXISTS" | "NOT EXISTS")
+                && !condition.operator.starts_with("RAW ")
+        });
+        let selects = self.select_columns.iter().filter_map(|column| match column {
+            Expression::Column(column) => Some(column.rsplit_once(" AS ").map_or(column.as_str(), |(column, _)| column)),
+            Expression::Raw(_) | Expression::Bound(..) | Expression::Window(_) | Expression::Case(_) => None,
+        });
+
+        conditions.flat_map(|condition| condition.column.split(", "))
+            .chain(selects)
+            .chain(self.order_by.iter().map(|order| order.column.as_str()).filter(|column| !aliases.contains(column)))
+            .chain(self.group_by.iter().map(String::as_str))
+            .filter(|column| !column.is_empty() && !column.starts_with(|c: char| c.is_ascii_digit()))
+            .filter(|column| column.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.' || c == '*'))
+            // With joins a bare column may belong to any of the tables
+            .filter(|column| self.joins.is_empty() || column.contains('.'))
+            .map(str::to_string)
+            .collect()
+    }
+
+    // Execution methods
+    pub async fn get(self) -> Result<Vec<T>, sqlx::Error> {
+        self.get_as().await
+    }
+
+    // Rows deserialized into another type than the model, e.g. Pivoted<T>
+    pub async fn get_as<U>(self) -> Result<Vec<U>, sqlx::Error>
+    where
+        U: for<'de> serde::Deserialize<'de> + Send,
+    {
+        let sql = self.to_sql();
+        self.run(&sql, self.fetch_rows(&sql)).await
+    }
+
+    // As get, also running the relations named in with(): one query per relation (and per
+    // nested relation) for all of the rows, e.g. `user.posts().with(vec!["comments.user"]).get_loaded()`.
+    // The related rows are read back with Loaded::related, and their nested relations with
+    // Loaded::related_loaded. The related model's global scopes don't apply (its soft-delete
+    // filter does)
+    pub async fn get_loaded(self) -> Result<Vec<Loaded<T>>, sqlx::Error> {
+        if let Some(message) = &self.eager_load_error {
+            return Err(EloquentError::Configuration(message.clone()).into_sqlx());
+        }
+        let loads = self.with_relations.clone();
+        let loader = self.clone();
+        let models = self.get().await?;
+        let attributes: Vec<Value> = models.iter()
+            .map(|model| serde_json::to_value(model).unwrap_or_default())
+            .collect();
+        let relations = loader.load_relations(&loads, &attributes, T::relation_definition, T::table_name()).await?;
+        Ok(models.into_iter()
+            .zip(relations)
+            .map(|(model, relations)| Loaded { model, relations })
+            .collect())
+    }
+
+    // The relations in loads for each of parents (as attributes), in the parents' order. Names
+    // resolve through the parents' model's relation_definition, passed in as resolve, since
+    // below the top level that model's type isn't known
+    fn load_relations<'a>(
+        &'a self,
+        loads: &'a [EagerLoad],
+        parents: &'a [Value],
+        resolve: fn(&str) -> Option<RelationDefinition>,
+        model: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<LoadedRelations>, sqlx::Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let mut relations: Vec<LoadedRelations> = vec![HashMap::new(); parents.len()];
+            for load in loads {
+                let definition = resolve(&load.relation).ok_or_else(|| EloquentError::Configuration(
+                    format!("Call to undefined relationship [{}] on model [{}]", load.relation, model),
+                ).into_sqlx())?;
+                let keys: Vec<Value> = parents.iter()
+                    .filter_map(|parent| parent.get(&definition.parent_key))
+                    .filter(|key| !key.is_null())
+                    .cloned()
+                    .collect();
+
+                let mut rows_by_key: HashMap<String, Vec<Loaded<serde_json::Map<String, Value>>>> = HashMap::new();
+                if !keys.is_empty() {
+                    let sql = self.eager_load_sql(&definition, &keys);
+                    let mut rows: Vec<serde_json::Map<String, Value>> = self.run(&sql, self.fetch_rows(&sql)).await?;
+                    let parent_keys: Vec<Option<Value>> = rows.iter_mut().map(|row| row.remove(EAGER_PARENT_KEY)).collect();
+                    let related: Vec<Value> = rows.iter().cloned().map(Value::Object).collect();
+                    let nested = self.load_relations(&load.nested, &related, definition.related_relations, &definition.related_table).await?;
+                    for ((row, relations), key) in rows.into_iter().zip(nested).zip(parent_keys) {
+                        if let Some(key) = key {
+                            rows_by_key.entry(key_text(&key)).or_default().push(Loaded { model: row, relations });
+                        }
+                    }
+                }
+
+                for (relations, parent) in relations.iter_mut().zip(parents) {
+                    let rows = parent.get(&definition.parent_key)
+                        .and_then(|key| rows_by_key.get(&key_text(key)))
+                        .cloned()
+                        .unwrap_or_default();
+                    relations.insert(load.relation.clone(), rows);
+                }
+            }
+            Ok(relations)
+        })
+    }
+
+    // As get, with the time the statement took and how many rows came back
+    pub async fn get_with_metrics(self) -> Result<QueryResult<Vec<T>>, sqlx::Error> {
+        let started = Instant::now();
+        let data = self.get().await?;
+        Ok(QueryResult {
+            rows_returned: data.len() as u64,
+            duration: started.elapsed(),
+            data,
+        })
+    }
+
+    // Fold over every row without collecting them. Rows are fetched a page at a time
+    // (ordered by primary key unless the query has its own order), stopping at the first error
+    pub async fn reduce<A, F>(self, init: A, mut f: F) -> Result<A, sqlx::Error>
+    where
+        A: Send,
+        F: FnMut(A, T) -> A + Send,
+    {
+        if self.limit_value.is_some() || self.offset_value.is_some() {
+            return Ok(self.get().await?.into_iter().fold(init, f));
+        }
+
+        let query = if self.order_by.is_empty() {
+            self.order_by(T::primary_key(), "ASC")
+        } else {
+            self
+        };
+
+        let mut accumulator = init;
+        let mut offset = 0;
+        loop {
+            let rows = query.clone().offset(offset).limit(REDUCE_PAGE_SIZE).get().await?;
+            let fetched = rows.len() as i64;
+            accumulator = rows.into_iter().fold(accumulator, &mut f);
+            if fetched < REDUCE_PAGE_SIZE {
+                return Ok(accumulator);
+            }
+            offset += REDUCE_PAGE_SIZE;
+        }
+    }
+
+    pub async fn for_each<F>(self, mut f: F) -> Result<(), sqlx::Error>
+    where
+        F: FnMut(T) + Send,
+    {
+        self.reduce((), |_, row| f(row)).await
+    }
+
+    // Hand matching rows to f in chunks of size, paging by primary key (WHERE id > last id)
+    // rather than offset, so rows updated or deleted by f don't shift later chunks
+    pub async fn chunk_by_id<F>(self, size: i64, f: F) -> Result<(), sqlx::Error>
+    where
+        F: FnMut(Vec<T>) + Send,
+    {
+        self.chunk_by_id_with_progress(size, f, |_, _| {}).await
+    }
+
+    // Map every matching row through f, fetching chunk_by_id-style chunks of size. Only one
+    // chunk of models is held at a time, but the mapped output is collected in full; use
+    // reduce or for_each when the output shouldn't grow with the table
+    pub async fn chunk_map<U, F>(self, size: i64, mut f: F) -> Result<Vec<U>, sqlx::Error>
+    where
+        U: Send,
+        F: FnMut(T) -> U + Send,
+    {
+        let mut mapped = Vec::new();
+        self.chunk_by_id(size, |rows| mapped.extend(rows.into_iter().map(&mut f))).await?;
+        Ok(mapped)
+    }
+
+    // As chunk_by_id, calling progress(processed_so_far, chunk_size) after each chunk is
+    // handled. An empty result never calls either closure
+    pub async fn chunk_by_id_with_progress<F, P>(self, size: i64, mut f: F, mut progress: P) -> Result<(), sqlx::Error>
+    where
+        F: FnMut(Vec<T>) + Send,
+        P: FnMut(u64, usize) + Send,
+    {
+        let key = format!("{}.{}", self.table_name(), T::primary_key());
+        let mut query = self;
+        query.order_by.clear();
+        query.limit_value = None;
+        query.offset_value = None;
+        // Keep the key condition outside any OR in the query's own conditions
+        if query.where_conditions.iter().skip(1).any(|condition| condition.boolean == "OR") {
+            let grouped = format!("({})", compile_conditions(&query.where_conditions, query.driver));
+            query.where_conditions = vec![raw_condition(&grouped, Vec::new())];
+        }
+        let query = query.order_by(&key, "ASC").limit(size);
+
+        let mut last_key: Option<Value> = None;
+        let mut processed = 0;
+        loop {
+            let page = match &last_key {
+                Some(last) => query.clone().where_op(&key, ">", last.clone()),
+                None => query.clone(),
+            };
+            let rows = page.get().await?;
+            let fetched = rows.len();
+            if fetched == 0 {
+                return Ok(());
+            }
+
+            last_key = rows.last().and_then(Model::get_key_value).filter(|value| !value.is_null());
+            processed += fetched as u64;
+            f(rows);
+            progress(processed, fetched);
+            if (fetched as i64) < size || last_key.is_none() {
+                return Ok(());
+            }
+        }
+    }
+
+    // Ok(None) only when no row matched; a row that doesn't deserialize into T is an
+    // error that converts to EloquentError::Hydration
+    pub async fn first(self) -> Result<Option<T>, sqlx::Error> {
+        let query = self.limit(1);
+        let results = query.get().await?;
+        Ok(results.into_iter().next())
+    }
+
+    pub async fn first_or(self, default: T) -> Result<T, sqlx::Error> {
+        Ok(self.first().await?.unwrap_or(default))
+    }
+
+    pub async fn first_or_else(self, f: impl FnOnce() -> T + Send) -> Result<T, sqlx::Error> {
+        Ok(self.first().await?.unwrap_or_else(f))
+    }
+
+    // Single column from the first matching row
+    pub async fn value(self, column: &str) -> Result<Option<Value>, sqlx::Error> {
+        let row = self.select(vec![column]).first().await?;
+        Ok(row
+            .and_then(|model| serde_json::to_value(model).ok())
+            .and_then(|mut attributes| attributes.get_mut(column).map(Value::take)))
+    }
+
+    // One column from every matching row, without hydrating models. The column may be
+    // qualified, e.g. "roles.id"
+    pub async fn pluck(self, column: &str) -> Result<Vec<Value>, sqlx::Error> {
+        let rows: Vec<HashMap<String, Value>> = self.select(vec![column]).get_as().await?;
+        Ok(rows.into_iter().filter_map(|row| row.into_values().next()).collect())
+    }
+
+    pub async fn value_or(self, column: &str, default: Value) -> Result<Value, sqlx::Error> {
+        Ok(self.value(column).await?.unwrap_or(default))
+    }
+
+    pub async fn find_by_id(self, id: i64) -> Result<Option<T>, sqlx::Error> {
+        let id_str = id.to_string();
+        let query = self.where_clause(T::primary_key(), &id_str);
+        query.first().await
+    }
+
+    // One raw SQL expression over the matching rows, deserialized into V, e.g.
+    // scalar::<String>("MAX(name)"). None when the result is NULL. Some results come back as
+    // text, such as MySQL's DECIMAL for SUM(), so a numeric or boolean string is also tried as
+    // a number or boolean
+    pub async fn scalar<V>(self, expression: &str) -> Result<Option<V>, sqlx::Error>
+    where
+        V: for<'de> serde::Deserialize<'de>,
+    {
+        let sql = self.aggregate_sql(expression);
+        let rows: Vec<HashMap<String, Value>> = self.run(&sql, self.fetch_rows(&sql)).await?;
+        let cell = rows.into_iter().next().and_then(|row| row.into_values().next()).unwrap_or_default();
+        if cell.is_null() {
+            return Ok(None);
+        }
+        serde_json::from_value(cell.clone())
+            .or_else(|_| serde_json::from_value(coerce_scalar(cell)))
+            .map(Some)
+            .map_err(|error| EloquentError::Hydration { table: self.table_name(), error }.into_sqlx())
+    }
+
+    pub async fn count(self) -> Result<i64, sqlx::Error> {
+        let sql = self.to_count_sql();
+        self.run(&sql, self.fetch_aggregate(&sql)).await
+    }
+
+    pub async fn count_distinct(self, column: &str) -> Result<i64, sqlx::Error> {
+        let sql = self.to_count_distinct_sql(column);
+        self.run(&sql, self.fetch_aggregate(&sql)).await
+    }
+
+    // Terminal methods execute on the connection bound with on_connection, or inside the
+    // task's open RustEloquent::transaction on that connection. Unbound queries run the
+    // same way on the connection registered under T::connection()
+    fn executor(&self) -> Result<Executor, sqlx::Error> {
+        match &self.connection {
+            Some(BoundConnection(connection)) => Ok(Executor::for_connection(connection)),
+            None => Executor::for_name(T::connection()),
+        }
+    }
+
+    async fn fetch_rows<U>(&self, sql: &str) -> Result<Vec<U>, sqlx::Error>
+    where
+        U: for<'de> serde::Deserialize<'de>,
+    {
+        self.fetch_rows_on(&self.executor()?, sql).await
+    }
+
+    async fn fetch_rows_on<U>(&self, executor: &Executor, sql: &str) -> Result<Vec<U>, sqlx::Error>
+    where
+        U: for<'de> serde::Deserialize<'de>,
+    {
+        self.check_server_support(executor)?;
+        let (columns, rows) = executor.fetch_all(sql).await?;
+        let table = self.table_name();
+        rows.into_iter()
+            .map(|row| hydrate(&columns, row).map_err(|error| {
+                EloquentError::Hydration { table: table.clone(), error }.into_sqlx()
+            }))
+            .collect()
+    }
+
+    // First column of the first row as a number, e.g. COUNT(*) AS aggregate
+    async fn fetch_aggregate(&self, sql: &str) -> Result<i64, sqlx::Error> {
+        let executor = self.executor()?;
+        self.check_server_support(&executor)?;
+        first_number(executor.fetch_one(sql).await?)
+    }
+
+    // Older servers reject window functions with a bare syntax error, so they're refused here
+    // with one that says why
+    fn check_server_support(&self, executor: &Executor) -> Result<(), sqlx::Error> {
+        let windows = self.select_columns.iter().any(|column| matches!(column, Expression::Window(_)));
+        if windows && !executor.supports_window_functions() {
+            return Err(EloquentError::Unsupported(format!(
+                "window functions on [{}] need MySQL 8.0+, MariaDB 10.2+ or SQLite 3.25+", self.table_name(),
+            )).into_sqlx());
+        }
+        Ok(())
+    }
+
+    async fn execute_statement(&self, sql: &str) -> Result<u64, sqlx::Error> {
+        self.executor()?.execute(sql).await
+    }
+
+    // Fetches at most one row rather than counting every match
+    pub async fn exists(self) -> Result<bool, sqlx::Error> {
+        let sql = self.to_exists_sql();
+        let rows: Vec<HashMap<String, Value>> = self.run(&sql, self.fetch_rows(&sql)).await?;
+        Ok(!rows.is_empty())
+    }
+
+    pub async fn doesnt_exist(self) -> Result<bool, sqlx::Error> {
+        Ok(!self.exists().await?)
+    }
+
+    // Opt this query out of the global destructive-query guard
+    pub fn allow_without_where(mut self) -> Self {
+        self.allow_without_where = true;
+        self
+    }
+
+    fn guard_destructive(&self, operation: &str) -> Result<(), EloquentError> {
+        if self.where_conditions.is_empty()
+            && !self.allow_without_where
+            && PREVENT_DESTRUCTIVE_WITHOUT_WHERE.load(Ordering::Relaxed)
+        {
+            return Err(EloquentError::MissingWhereClause {
+                operation: operation.to_string(),
+                table: self.table_name(),
+            });
+        }
+        Ok(())
+    }
+
+    // Bulk update of every matching row
+    pub async fn update(self, attributes: HashMap<String, Value>) -> Result<u64, EloquentError> {
+        self.guard_destructive("UPDATE")?;
+        let sql = self.to_update_sql(&attributes);
+        Ok(self.run(&sql, self.execute_statement(&sql)).await?)
+    }
+
+    // Bump updated_at on every matching row; models without timestamps are left alone
+    pub async fn touch(self) -> Result<u64, EloquentError> {
+        if !T::timestamps() {
+            return Ok(0);
+        }
+        self.guard_destructive("UPDATE")?;
+        let sql = self.to_touch_sql();
+        Ok(self.run(&sql, self.execute_statement(&sql)).await?)
+    }
+
+    // Add amount to column on every matching row (bumping updated_at when the model has
+    // timestamps), returning the affected count
+    pub async fn increment(self, column: &str, amount: i64) -> Result<u64, EloquentError> {
+        self.guard_destructive("UPDATE")?;
+        let sql = self.to_increment_sql(column, amount);
+        Ok(self.run(&sql, self.execute_statement(&sql)).await?)
+    }
+
+    pub async fn decrement(self, column: &str, amount: i64) -> Result<u64, EloquentError> {
+        self.increment(column, -amount).await
+    }
+
+    // Bulk delete of every matching row
+    pub async fn delete(self) -> Result<u64, EloquentError> {
+        self.guard_destructive("DELETE")?;
+        let sql = self.to_delete_sql();
+        Ok(self.run(&sql, self.execute_statement(&sql)).await?)
+    }
+
+    // As update, returning the updated rows. Postgres and SQLite use UPDATE ... RETURNING *.
+    // MySQL has no RETURNING, so the matching keys are selected first, then updated, then
+    // the rows re-selected by key: three statements that another writer can interleave with,
+    // so run it in a transaction if that matters
+    pub async fn update_returning(self, attributes: HashMap<String, Value>) -> Result<Vec<T>, EloquentError> {
+        self.guard_destructive("UPDATE")?;
+        if self.driver != Driver::MySql {
+            let sql = self.to_update_returning_sql(&attributes);
+            return Ok(self.run(&sql, self.fetch_rows(&sql)).await?);
+        }
+
+        let keys = self.matching_keys().await?;
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let sql = self.to_update_sql(&attributes);
+        self.run(&sql, self.execute_statement(&sql)).await?;
+        Ok(self.by_keys(keys).get().await?)
+    }
+
+    // As delete, returning the deleted rows. On MySQL the rows are selected before the
+    // delete, with the same caveat as update_returning
+    pub async fn delete_returning(self) -> Result<Vec<T>, EloquentError> {
+        self.guard_destructive("DELETE")?;
+        if self.driver != Driver::MySql {
+            let sql = self.to_delete_returning_sql();
+            return Ok(self.run(&sql, self.fetch_rows(&sql)).await?);
+        }
+
+        let rows = self.clone().get().await?;
+        if rows.is_empty() {
+            return Ok(rows);
+        }
+        let sql = self.to_delete_sql();
+        self.run(&sql, self.execute_statement(&sql)).await?;
+        Ok(rows)
+    }
+
+    // Primary keys of the matching rows, for the MySQL RETURNING emulation
+    async fn matching_keys(&self) -> Result<Vec<Value>, sqlx::Error> {
+        let rows: Vec<HashMap<String, Value>> = self.clone().select(vec![T::primary_key()]).get_as().await?;
+        Ok(rows.into_iter().filter_map(|mut row| row.remove(T::primary_key())).collect())
+    }
+
+    // The same table and connection, limited to the given keys and nothing else
+    fn by_keys(&self, keys: Vec<Value>) -> Self {
+        let mut query = self.clone();
+        query.where_conditions.clear();
+        query.apply_global_scopes = false;
+        query.order_by.clear();
+        query.limit_value = None;
+        query.offset_value = None;
+        query.where_in(T::primary_key(), keys)
+    }
+
+    // Clear the deleted_at column on the matching trashed rows. Always limited to trashed rows,
+    // as if only_trashed() were applied; models without soft deletes are left alone.
+    // Relations in cascade_soft_deletes are restored first (see to_restore_statements)
+    pub async fn restore(self) -> Result<u64, EloquentError> {
+        if !T::soft_deletes() {
+            return Ok(0);
+        }
+        let query = self.only_trashed();
+        query.guard_destructive("UPDATE")?;
+        query.run_statements(query.to_restore_statements()).await
+    }
+
+    // Set the model's deleted_at column to the current time on the matching rows that aren't
+    // trashed yet; models without soft deletes are left alone.
+    // Relations in cascade_soft_deletes are soft-deleted first (see to_soft_delete_statements)
+    pub async fn soft_delete(self) -> Result<u64, EloquentError> {
+        if !T::soft_deletes() {
+            return Ok(0);
+        }
+        let query = self.without_trashed();
+        query.guard_destructive("UPDATE")?;
+        query.run_statements(query.to_soft_delete_statements()).await
+    }
+
+    // Run the statements in order, stopping at the first error; the last one's affected row
+    // count is returned. They aren't atomic - wrap the call in a transaction if that matters
+    async fn run_statements(&self, statements: Vec<String>) -> Result<u64, EloquentError> {
+        let mut affected = 0;
+        for sql in &statements {
+            affected = self.run(sql, self.execute_statement(sql)).await?;
+        }
+        Ok(affected)
+    }
+
+    // Permanently delete the matching rows, trashed or not
+    pub async fn force_delete(self) -> Result<u64, EloquentError> {
+        self.with_trashed().delete().await
+    }
+
+    // Empty the whole table and reset its auto-increment counter. Where conditions and
+    // global scopes don't apply; this always removes every row
+    pub async fn truncate(self) -> Result<(), EloquentError> {
+        self.run_truncate(false).await
+    }
+
+    // As truncate, also truncating tables with foreign keys to this one on Postgres.
+    // MySQL and SQLite have no TRUNCATE ... CASCADE, so there it is the same as truncate
+    pub async fn truncate_cascade(self) -> Result<(), EloquentError> {
+        self.run_truncate(true).await
+    }
+
+    async fn run_truncate(&self, cascade: bool) -> Result<(), EloquentError> {
+        for sql in self.to_truncate_sql(cascade) {
+            match self.run(&sql, self.execute_statement(&sql)).await {
+                // sqlite_sequence only exists once some table uses AUTOINCREMENT
+                Err(sqlx::Error::Database(error)) if error.message().contains("no such table: sqlite_sequence") => {}
+                result => {
+                    result?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Insert the rows in one statement, returning the inserted count
+    pub async fn insert(self, rows: Vec<HashMap<String, Value>>) -> Result<u64, sqlx::Error> {
+        if rows.is_empty() {
+            return Ok(0);
+        }
+        let sql = format!("INSERT INTO {}", self.insert_values_sql(&rows));
+        self.run(&sql, self.execute_statement(&sql)).await
+    }
+
+    // Insert the rows in one statement and return them as stored, with generated keys and
+    // the created_at/updated_at that to_create_sql adds. Every row must have the same
+    // columns, since a missing one would be inserted as NULL rather than its default.
+    // Postgres reads the rows back with RETURNING *. MySQL has no RETURNING and SQLite's
+    // comes back in no particular order, so there the rows are re-selected by the keys the
+    // insert generated, counted from the last insert id. Both statements run in one
+    // transaction so they share a connection. The count assumes consecutive keys: true on
+    // SQLite, and on MySQL unless innodb_autoinc_lock_mode = 2 with concurrent inserts
+    pub async fn create_many(self, rows: Vec<HashMap<String, Value>>) -> Result<Vec<T>, sqlx::Error> {
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+        if let Some((index, row)) = rows.iter().enumerate().find(|(_, row)| !same_columns(row, &rows[0])) {
+            return Err(sqlx::Error::Protocol(format!(
+                "create_many: row {} has columns [{}] but row 0 has [{}]",
+                index, sorted_columns(row), sorted_columns(&rows[0])
+            )));
+        }
+
+        let sql = self.to_create_many_sql(&rows);
+        if self.driver == Driver::Postgres {
+            let sql = format!("{} RETURNING *", sql);
+            return self.run(&sql, self.fetch_rows(&sql)).await;
+        }
+        match self.executor()? {
+            Executor::Connection(connection) => {
+                let transaction: Arc<dyn DatabaseTransaction> = Arc::from(connection.begin().await?);
+                let result = self.create_many_on(&Executor::Transaction(transaction.clone()), &sql, &rows).await;
+                match result {
+                    Ok(_) => transaction.commit().await?,
+                    Err(_) => transaction.rollback().await?,
+                }
+                result
+            }
+            executor => self.create_many_on(&executor, &sql, &rows).await,
+        }
+    }
+
+    async fn create_many_on(&self, executor: &Executor, sql: &str, rows: &[HashMap<String, Value>]) -> Result<Vec<T>, sqlx::Error> {
+        let inserted = self.run(sql, executor.execute(sql)).await? as i64;
+        let keys: Vec<Value> = if rows[0].contains_key(T::primary_key()) {
+            rows.iter().map(|row| row[T::primary_key()].clone()).collect()
+        } else {
+            let sql = match self.driver {
+                Driver::MySql => "SELECT LAST_INSERT_ID()",
+                _ => "SELECT last_insert_rowid()",
+            };
+            let last = first_number(self.run(sql, executor.fetch_one(sql)).await?)?;
+            // MySQL reports the first key the statement generated, SQLite the last
+            let first = if self.driver == Driver::MySql { last } else { last - inserted + 1 };
+            (first..first + inserted).map(Value::from).collect()
+        };
+        let sql = self.by_keys(keys).order_by(T::primary_key(), "asc").to_sql();
+        self.run(&sql, self.fetch_rows_on(executor, &sql)).await
+    }
+
+    // Insert rows, silently skipping any that would violate a unique constraint.
+    // Returns the number of rows actually inserted
+    pub async fn insert_or_ignore(self, rows: Vec<HashMap<String, Value>>) -> Result<u64, sqlx::Error> {
+        if rows.is_empty() {
+            return Ok(0);
+        }
+        let sql = self.to_insert_or_ignore_sql(&rows);
+        self.run(&sql, self.execute_statement(&sql)).await
+    }
+
+    // As create, in one statement that skips the row when it conflicts with a unique
+    // constraint: the inserted row, or None when a conflicting row already exists. Unlike a
+    // failed INSERT, the conflict doesn't abort an open Postgres transaction. Needs RETURNING,
+    // so MySQL isn't supported
+    pub async fn create_or_ignore(self, attributes: HashMap<String, Value>) -> Result<Option<T>, sqlx::Error> {
+        if self.driver == Driver::MySql {
+            return Err(sqlx::Error::Protocol("create_or_ignore: MySQL has no INSERT ... RETURNING".to_string()));
+        }
+        let sql = self.to_create_or_ignore_sql(&attributes);
+        let mut rows: Vec<T> = self.run(&sql, self.fetch_rows(&sql)).await?;
+        Ok(rows.pop())
+    }
+
+    pub async fn paginate(self, page: i64, per_page: i64) -> Result<Pagination<T>, sqlx::Error> {
+        let total = self.clone().count().await?;
+        self.paginate_with_total(page, per_page, total).await
+    }
+
+    // As paginate, with the total from COUNT(count_column), e.g. an indexed column or
+    // "DISTINCT user_id"; NULLs in the column aren't counted
+    pub async fn paginate_counting(self, page: i64, per_page: i64, count_column: &str) -> Result<Pagination<T>, sqlx::Error> {
+        let sql = self.aggregate_sql(&format!("COUNT({})", quote_identifier(count_column, self.driver)));
+        let total = self.run(&sql, self.fetch_aggregate(&sql)).await?;
+        self.paginate_with_total(page, per_page, total).await
+    }
+
+    // As paginate, with the total taken from the planner's row estimate for the table
+    // (pg_class.reltuples on Postgres, information_schema TABLE_ROWS on MySQL) instead of
+    // counting. It is only as fresh as the last ANALYZE and can be well off on InnoDB, so
+    // it suits "about N results" displays. The estimate covers the whole table, so queries
+    // that filter, join or group it, and SQLite, which has no estimate, use an exact count
+    pub async fn paginate_estimated(self, page: i64, per_page: i64) -> Result<Pagination<T>, sqlx::Error> {
+        let whole_table = self.compile_where().is_none()
+            && self.joins.is_empty()
+            && self.group_by.is_empty()
+            && self.having_conditions.is_empty()
+            && !self.distinct;
+        let total = match self.to_estimated_count_sql() {
+            Some(sql) if whole_table => match self.run(&sql, self.fetch_aggregate(&sql)).await? {
+                // Postgres reports -1 for tables that have never been analyzed
+                estimate if estimate >= 0 => estimate,
+                _ => self.clone().count().await?,
+            },
+            _ => self.clone().count().await?,
+        };
+        self.paginate_with_total(page, per_page, total).await
+    }
+
+    async fn paginate_with_total(self, page: i64, per_page: i64, total: i64) -> Result<Pagination<T>, sqlx::Error> {
+        let offset = (page - 1) * per_page;
+        let results = self.skip(offset).take(per_page).get().await?;
+
+        let remaining = if total > offset { total - offset } else { 0 };
+        let to_value = offset + std::cmp::min(per_page, remaining);
+        
+        Ok(Pagination {
+            data: results,
+            current_page: page,
+            per_page,
+            total,
+            last_page: (total as f64 / per_page as f64).ceil() as i64,
+            from: offset + 1,
+            to: to_value,
+        })
+    }
+
+    fn where_tree(&self) -> Option<ConditionNode> {
+        let scopes = ConditionNode::from_conditions(&self.scope_conditions());
+        match (ConditionNode::from_conditions(&self.where_conditions), scopes) {
+            (Some(own), Some(scopes)) => Some(ConditionNode::And(vec![own, scopes])),
+            (own, scopes) => own.or(scopes),
+        }
+    }
+
+    // Structured representation of the query, built from the same state as to_sql
+    pub fn to_ast(&self) -> QueryAst {
+        QueryAst {
+            table: self.table_name().to_string(),
+            select: self.select_columns.iter().map(|column| column.sql().to_string()).collect(),
+            distinct: self.distinct,
+            joins: self.joins.clone(),
+            wheres: self.where_tree(),
+            group_by: self.group_by.clone(),
+            having: ConditionNode::from_conditions(&self.having_conditions),
+            order_by: self.order_by.clone(),
+            limit: self.limit_value,
+            offset: self.offset_value,
+        }
+    }
+
+    // The query limited to its first row, ordering dropped, for exists/doesnt_exist
+    pub fn to_exists_sql(&self) -> String {
+        let mut query = self.clone();
+        query.order_by.clear();
+        query.limit_value = Some(query.limit_value.map_or(1, |limit| limit.min(1)));
+        query.to_sql()
+    }
+
+    // The table's estimated row count, for paginate_estimated; None on SQLite
+    pub fn to_estimated_count_sql(&self) -> Option<String> {
+        match self.driver {
+            Driver::Postgres => Some(format!(
+                "SELECT reltuples::bigint AS aggregate FROM pg_class WHERE oid = {}::regclass",
+                quote_string(&self.table_name(), self.driver)
+            )),
+            Driver::MySql => {
+                let (database, table) = match self.table_name().split_once('.') {
+                    Some((database, table)) => (quote_string(database, self.driver), quote_string(table, self.driver)),
+                    None => ("DATABASE()".to_string(), quote_string(&self.table_name(), self.driver)),
+                };
+                // TABLE_ROWS is NULL for views, which then fall back to an exact count
+                Some(format!(
+                    "SELECT CAST(COALESCE(TABLE_ROWS, -1) AS SIGNED) AS aggregate FROM information_schema.TABLES \
+                     WHERE TABLE_SCHEMA = {} AND TABLE_NAME = {}",
+                    database, table
+                ))
+            }
+            Driver::Sqlite => None,
+        }
+    }
+
+    // COUNT(*) over the query. Grouped and DISTINCT queries are counted as a
+    // subquery so the result is the number of rows they return, not per-group counts
+    pub fn to_count_sql(&self) -> String {
+        self.aggregate_sql("COUNT(*)")
+    }
+
+    pub fn to_count_distinct_sql(&self, column: &str) -> String {
+        self.aggregate_sql(&format!("COUNT(DISTINCT {})", quote_identifier(column, self.driver)))
+    }
+
+    fn aggregate_sql(&self, expression: &str) -> String {
+        let mut query = self.clone();
+        query.order_by.clear();
+        query.limit_value = None;
+        query.offset_value = None;
+
+        // HAVING can refer to select aliases, and a union has to be counted as a whole, so
+        // both also need the query as a subquery
+        if query.group_by.is_empty() && !query.distinct && query.having_conditions.is_empty() && query.unions.is_empty() {
+            query.select_columns = vec![Expression::Raw(format!("{} AS aggregate", expression))];
+            query.to_sql()
+        } else {
+            format!("SELECT {} AS aggregate FROM ({}) AS sub", expression, query.to_sql())
+        }
+    }
+
+    pub fn to_insert_sql(&self, attributes: &HashMap<String, Value>) -> String {
+        format!("INSERT INTO {}", self.insert_values_sql(std::slice::from_ref(attributes)))
+    }
+
+    // The INSERT for Model::create: the attributes plus created_at/updated_at when the model
+    // has timestamps and they weren't given. Model::uses_db_timestamps() picks between the
+    // database's NOW()/CURRENT_TIMESTAMP and the client's current UTC time
+    pub fn to_create_sql(&self, attributes: &HashMap<String, Value>) -> String {
+        self.to_create_many_sql(std::slice::from_ref(attributes))
+    }
+
+    // The statement create_or_ignore runs: to_create_sql with ON CONFLICT DO NOTHING RETURNING *
+    pub fn to_create_or_ignore_sql(&self, attributes: &HashMap<String, Value>) -> String {
+        format!("{} ON CONFLICT DO NOTHING RETURNING *", self.to_create_sql(attributes))
+    }
+
+    // As to_create_sql, one VALUES tuple per row
+    pub fn to_create_many_sql(&self, rows: &[HashMap<String, Value>]) -> String {
+        let rows: Vec<HashMap<String, String>> = rows.iter().map(|row| self.create_row(row)).collect();
+        format!("INSERT INTO {}", self.insert_rendered_sql(&rows))
+    }
+
+    fn create_row(&self, attributes: &HashMap<String, Value>) -> HashMap<String, String> {
+        let mut row: HashMap<String, String> = persistent::<T>(attributes)
+            .map(|(column, value)| (column.clone(), format_value(value, self.driver)))
+            .collect();
+        if T::timestamps() {
+            let now = if T::uses_db_timestamps() {
+                current_timestamp(self.driver).to_string()
+            } else {
+                format_value(&Value::String(chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()), self.driver)
+            };
+            for column in [T::created_at_column(), T::updated_at_column()] {
+                row.entry(column.to_string()).or_insert_with(|| now.clone());
+            }
+        }
+        row
+    }
+
+    // Multi-row insert that skips rows violating a unique constraint
+    pub fn to_insert_or_ignore_sql(&self, rows: &[HashMap<String, Value>]) -> String {
+        let values = self.insert_values_sql(rows);
+        match self.driver {
+            Driver::MySql => format!("INSERT IGNORE INTO {}", values),
+            Driver::Postgres | Driver::Sqlite => format!("INSERT INTO {} ON CONFLICT DO NOTHING", values),
+        }
+    }
+
+    // `table (columns) VALUES (...), (...)` over the union of the rows' columns,
+    // with NULL for columns a row doesn't have
+    fn insert_values_sql(&self, rows: &[HashMap<String, Value>]) -> String {
+        let rendered: Vec<HashMap<String, String>> = rows.iter()
+            .map(|row| persistent::<T>(row).map(|(column, value)| (column.clone(), format_value(value, self.driver))).collect())
+            .collect();
+        self.insert_rendered_sql(&rendered)
+    }
+
+    // Like insert_values_sql, but the values are already SQL expressions
+    fn insert_rendered_sql(&self, rows: &[HashMap<String, String>]) -> String {
+        let mut columns: Vec<&String> = rows.iter().flat_map(|row| row.keys()).collect();
+        columns.sort();
+        columns.dedup();
+        let values: Vec<String> = rows.iter()
+            .map(|row| format!("({})", columns.iter()
+                .map(|column| row.get(*column).map_or("NULL", |value| value.as_str()))
+                .collect::<Vec<_>>()
+                .join(", ")))
+            .collect();
+
+        format!("{} ({}) VALUES {}",
+            self.table_name(),
+            columns.iter().map(|column| column.as_str()).collect::<Vec<_>>().join(", "),
+            values.join(", "))
+    }
+
+    pub fn to_update_sql(&self, attributes: &HashMap<String, Value>) -> String {
+        let mut columns: Vec<(&String, &Value)> = persistent::<T>(attributes).collect();
+        columns.sort_by_key(|(column, _)| *column);
+        let assignments: Vec<String> = columns.iter()
+            .map(|(column, value)| format!("{} = {}", column, format_value(value, self.driver)))
+            .collect();
+        self.update_sql(&assignments)
+    }
+
+    pub fn to_increment_sql(&self, column: &str, amount: i64) -> String {
+        let column = quote_identifier(column, self.driver);
+        let mut assignments = vec![format!("{} = {} + {}", column, column, amount)];
+        if T::timestamps() {
+            assignments.push(format!("{} = CURRENT_TIMESTAMP", T::updated_at_column()));
+        }
+        self.update_sql(&assignments)
+    }
+
+    pub fn to_touch_sql(&self) -> String {
+        self.update_sql(&[format!("{} = CURRENT_TIMESTAMP", T::updated_at_column())])
+    }
+
+    pub fn to_soft_delete_sql(&self) -> String {
+        self.clone().without_trashed()
+            .update_sql(&[format!("{} = {}", T::deleted_at_column(), current_timestamp(self.driver))])
+    }
+
+    pub fn to_restore_sql(&self) -> String {
+        self.clone().only_trashed().update_sql(&[format!("{} = NULL", T::deleted_at_column())])
+    }
+
+    // With cascade_soft_deletes, the related rows are soft-deleted before the parents (while the
+    // parents can still be found as untrashed), and every statement stamps the same client-side
+    // UTC time so restore can tell which children went with which parent
+    pub fn to_soft_delete_statements(&self) -> Vec<String> {
+        let relations = self.cascade_relations();
+        if relations.is_empty() {
+            return vec![self.to_soft_delete_sql()];
+        }
+
+        let query = self.clone().without_trashed();
+        let now = format_value(&Value::String(chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()), self.driver);
+        let mut parent_keys = query.clone();
+        parent_keys.order_by.clear();
+        parent_keys.limit_value = None;
+        parent_keys.offset_value = None;
+
+        let mut statements: Vec<String> = relations.iter().map(|(definition, deleted_at)| {
+            let keys = parent_keys.clone()
+                .select(vec![&format!("{}.{}", self.table_name(), definition.parent_key)])
+                .to_sql();
+            format!("UPDATE {} SET {} = {} WHERE {}.{} IS NULL AND {}.{} IN ({}){}",
+                definition.related_table, deleted_at, now,
+                definition.related_table, deleted_at,
+                definition.related_table, definition.related_key, keys,
+                self.relation_constraints(definition))
+        }).collect();
+        statements.push(query.update_sql(&[format!("{} = {}", T::deleted_at_column(), now)]));
+        statements
+    }
+
+    // With cascade_soft_deletes, related rows trashed at exactly the parent's deleted_at are
+    // restored before the parents; ones trashed separately (at another time) stay trashed
+    pub fn to_restore_statements(&self) -> Vec<String> {
+        let query = self.clone().only_trashed();
+        let mut statements: Vec<String> = self.cascade_relations().iter().map(|(definition, deleted_at)| {
+            let table = self.table_name();
+            let parents = format!("SELECT 1 FROM {} WHERE {} AND {}.{} = {}.{} AND {}.{} = {}.{}",
+                table, query.compile_where().unwrap_or_default(),
+                table, definition.parent_key, definition.related_table, definition.related_key,
+                table, T::deleted_at_column(), definition.related_table, deleted_at);
+            format!("UPDATE {} SET {} = NULL WHERE EXISTS ({}){}",
+                definition.related_table, deleted_at, parents, self.relation_constraints(definition))
+        }).collect();
+        statements.push(self.to_restore_sql());
+        statements
+    }
+
+    // (definition, related deleted_at column) for each relation in cascade_soft_deletes.
+    // Only has_one/has_many/morph relations to soft-deleting models can cascade
+    fn cascade_relations(&self) -> Vec<(RelationDefinition, String)> {
+        T::cascade_soft_deletes().into_iter().map(|relation| {
+            let definition = relation_definition::<T>(relation);
+            if definition.pivot.is_some() {
+                panic!("Can't cascade soft deletes through pivot relationship [{}] on model [{}]", relation, T::table_name());
+            }
+            let Some(deleted_at) = definition.related_deleted_at.clone() else {
+                panic!("Relationship [{}] on model [{}] can't cascade soft deletes: the related model doesn't soft delete",
+                    relation, T::table_name());
+            };
+            (definition, deleted_at)
+        }).collect()
     }
 
-    // Group by and having
-    pub fn group_by(mut self, columns: Vec<&str>) -> Self {
-        self.group_by = columns.iter().map(|s| s.to_string()).collect();
-        self
+    fn relation_constraints(&self, definition: &RelationDefinition) -> String {
+        definition.constraints.iter()
+            .map(|(column, value)| format!(" AND {}.{} = {}", definition.related_table, column, format_value(value, self.driver)))
+            .collect()
     }
 
-    pub fn having(mut self, column: &str, operator: &str, value: Value) -> Self {
-        self.having_conditions.push(WhereCondition {
-            column: column.to_string(),
-            operator: operator.to_string(),
-            value,
-            boolean: "AND".to_string(),
-        });
-        self
+    pub fn to_update_returning_sql(&self, attributes: &HashMap<String, Value>) -> String {
+        format!("{} RETURNING *", self.to_update_sql(attributes))
     }
 
-    // Eager loading
-    pub fn with(mut self, relations: Vec<&str>) -> Self {
-        self.with_relations = relations.iter().map(|s| s.to_string()).collect();
-        self
+    pub fn to_delete_returning_sql(&self) -> String {
+        format!("{} RETURNING *", self.to_delete_sql())
     }
 
-    // Execution methods
-    pub async fn get(self) -> Result<Vec<T>, sqlx::Error> {
-        // This would execute the query and return results
-        // For now, we'll return an empty vector
-        Ok(Vec::new())
+    fn update_sql(&self, assignments: &[String]) -> String {
+        let mut sql = format!("UPDATE {} SET {}", self.table_name(), assignments.join(", "));
+        if let Some(conditions) = self.compile_where() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions);
+        }
+        sql
     }
 
-    pub async fn first(self) -> Result<Option<T>, sqlx::Error> {
-        let query = self.limit(1);
-        let results = query.get().await?;
-        Ok(results.into_iter().next())
+    // SQLite has no TRUNCATE, so it deletes the rows and clears the table's AUTOINCREMENT
+    // counter in sqlite_sequence
+    pub fn to_truncate_sql(&self, cascade: bool) -> Vec<String> {
+        let table = self.table_name();
+        match self.driver {
+            Driver::MySql => vec![format!("TRUNCATE TABLE {}", table)],
+            Driver::Postgres => vec![format!(
+                "TRUNCATE TABLE {} RESTART IDENTITY{}", table, if cascade { " CASCADE" } else { "" }
+            )],
+            Driver::Sqlite => vec![
+                format!("DELETE FROM {}", table),
+                format!("DELETE FROM sqlite_sequence WHERE name = {}", quote_string(&table, self.driver)),
+            ],
+        }
     }
 
-    pub async fn find_by_id(self, id: i64) -> Result<Option<T>, sqlx::Error> {
-        let id_str = id.to_string();
-        let query = self.where_clause(T::primary_key(), &id_str);
-        query.first().await
+    pub fn to_delete_sql(&self) -> String {
+        let mut sql = format!("DELETE FROM {}", self.table_name());
+        if let Some(conditions) = self.compile_where() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions);
+        }
+        sql
     }
 
-    pub async fn count(self) -> Result<i64, sqlx::Error> {
-        // This would execute a COUNT query
-        Ok(0)
+    // Values are inlined, so the only bindings are those given to where_raw, having_raw,
+    // select_raw_bound and order_by_raw_bound: each of those fragments must have one `?` per binding. Debug builds
+    // already panic when a mismatched fragment is added; this reports the first one without panicking
+    pub fn validate_bindings(&self) -> Result<(), String> {
+        self.raw_fragments().into_iter().try_for_each(|(sql, bindings)| check_bindings(&sql, &bindings))
     }
 
-    pub async fn exists(self) -> Result<bool, sqlx::Error> {
-        let count = self.count().await?;
-        Ok(count > 0)
+    // Print the SQL and every raw fragment with its bindings to stderr, e.g. while chasing a
+    // placeholder mismatch; returns the query so it can sit in the middle of a chain
+    pub fn dd_bindings(self) -> Self {
+        eprintln!("{}", self.to_sql());
+        for (sql, bindings) in self.raw_fragments() {
+            let status = check_bindings(&sql, &bindings).err().unwrap_or_else(|| "ok".to_string());
+            eprintln!("  {} <- {:?} ({})", sql, bindings, status);
+        }
+        self
     }
 
-    pub async fn paginate(self, page: i64, per_page: i64) -> Result<Pagination<T>, sqlx::Error> {
-        let offset = (page - 1) * per_page;
-        let results = self.clone().skip(offset).take(per_page).get().await?;
-        let total = self.count().await?;
-        
-        let remaining = if total > offset { total - offset } else { 0 };
-        let to_value = offset + std::cmp::min(per_page, remaining);
-        
-        Ok(Pagination {
-            data: results,
-            current_page: page,
-            per_page,
-            total,
-            last_page: (total as f64 / per_page as f64).ceil() as i64,
-            from: offset + 1,
-            to: to_value,
-        })
+    // Fragments with their own bindings, in the order they appear in the SQL
+    fn raw_fragments(&self) -> Vec<(Cow<'_, str>, Cow<'_, [Value]>)> {
+        let selects = self.select_columns.iter().filter_map(|column| match column {
+            Expression::Bound(sql, bindings) => Some((Cow::Borrowed(sql.as_str()), Cow::Borrowed(bindings.as_slice()))),
+            Expression::Case(case) => Some((column.sql(), Cow::Owned(case.bindings()))),
+            _ => None,
+        });
+        let conditions = self.where_conditions.iter()
+            .chain(&self.having_conditions)
+            .filter(|condition| condition.operator == "RAW")
+            .map(|condition| match &condition.value {
+                Value::Array(bindings) => (Cow::Borrowed(condition.column.as_str()), Cow::Borrowed(bindings.as_slice())),
+                _ => (Cow::Borrowed(condition.column.as_str()), Cow::Borrowed(&[][..])),
+            });
+        selects.chain(conditions)
+            .chain(self.order_by.iter()
+                .filter(|order| !order.bindings.is_empty())
+                .map(|order| (Cow::Borrowed(order.column.as_str()), Cow::Borrowed(order.bindings.as_slice()))))
+            .collect()
     }
 
     // SQL generation (for debugging)
     pub fn to_sql(&self) -> String {
-        let table_name = T::table_name();
-        let select = self.select_columns.join(", ");
+        if !self.unions.is_empty() {
+            return self.union_sql();
+        }
+
+        // Postgres doesn't allow select aliases in HAVING and SQLite requires a GROUP BY
+        // before it, so conditions on aliases (e.g. with_count's posts_count) filter an
+        // outer query there. MySQL takes them in HAVING as written
+        if self.driver != Driver::MySql
+            && let Some(sql) = self.alias_having_sql()
+        {
+            return sql;
+        }
+
+        let table_name = self.table_name();
+        let select = self.select_columns.iter()
+            .map(|column| column.to_sql(self.driver))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let distinct = if self.distinct { "DISTINCT " } else { "" };
         
-        let mut sql = format!("SELECT {} FROM {}", select, table_name);
+        let hint = match (self.timeout, self.driver) {
+            (Some(duration), Driver::MySql) => format!("/*+ MAX_EXECUTION_TIME({}) */ ", duration.as_millis()),
+            _ => String::new(),
+        };
+
+        let mut sql = format!("SELECT {}{}{} FROM {}", hint, distinct, select, table_name);
+        if let Some(hint) = &self.index_hint {
+            match self.driver {
+                Driver::MySql => sql.push_str(&format!(" {} INDEX ({})", hint.kind, hint.index)),
+                Driver::Sqlite => sql.push_str(&format!(" INDEXED BY {}", hint.index)),
+                Driver::Postgres => sql.insert_str(0, &format!("/*+ IndexScan({} {}) */ ", table_name, hint.index)),
+            }
+        }
         
         // Add joins
         for join in &self.joins {
             sql.push_str(&format!(" {} JOIN {} ON {} {} {}", 
-                join.join_type, join.table, join.first, join.operator, join.second));
+                join.join_type, join.table, quote_identifier(&join.first, self.driver), join.operator,
+                quote_identifier(&join.second, self.driver)));
         }
         
         // Add where conditions
-        if !self.where_conditions.is_empty() {
+        if let Some(conditions) = self.compile_where() {
             sql.push_str(" WHERE ");
-            for (i, condition) in self.where_conditions.iter().enumerate() {
-                if i > 0 {
-                    sql.push_str(&format!(" {} ", condition.boolean));
-                }
-                sql.push_str(&format!("{} {} {}", condition.column, condition.operator, 
-                    match &condition.value {
-                        Value::String(s) => format!("'{}'", s),
-                        Value::Number(n) => n.to_string(),
-                        Value::Bool(b) => b.to_string(),
-                        Value::Null => "NULL".to_string(),
-                        Value::Array(arr) => format!("({})", 
-                            arr.iter()
-                               .map(|v| match v {
-                                   Value::String(s) => format!("'{}'", s),
-                                   Value::Number(n) => n.to_string(),
-                                   _ => "NULL".to_string(),
-                               })
-                               .collect::<Vec<_>>()
-                               .join(", ")),
-                        _ => "NULL".to_string(),
-                    }));
-            }
+            sql.push_str(&conditions);
         }
         
         // Add group by
         if !self.group_by.is_empty() {
-            sql.push_str(&format!(" GROUP BY {}", self.group_by.join(", ")));
+            sql.push_str(&format!(" GROUP BY {}", self.group_by.iter()
+                .map(|column| quote_identifier(column, self.driver))
+                .collect::<Vec<_>>()
+                .join(", ")));
         }
         
         // Add having
         if !self.having_conditions.is_empty() {
             sql.push_str(" HAVING ");
-            for (i, condition) in self.having_conditions.iter().enumerate() {
-                if i > 0 {
-                    sql.push_str(&format!(" {} ", condition.boolean));
-                }
-                sql.push_str(&format!("{} {} {}", condition.column, condition.operator, condition.value));
-            }
+            sql.push_str(&compile_conditions(&self.having_conditions, self.driver));
         }
         
+        self.push_order_and_limit(&mut sql);
+        sql
+    }
+
+    // (A) UNION (B) ORDER BY ... LIMIT ...: each member is parenthesized so the trailing
+    // clauses apply to the whole compound. SQLite doesn't accept parenthesized members,
+    // so there each one is selected from as a subquery instead
+    fn union_sql(&self) -> String {
+        let mut first = self.clone();
+        first.unions.clear();
+        first.order_by.clear();
+        first.limit_value = None;
+        first.offset_value = None;
+
+        let member = |sql: &str| match self.driver {
+            Driver::Sqlite => format!("SELECT * FROM ({})", sql),
+            Driver::MySql | Driver::Postgres => format!("({})", sql),
+        };
+        let mut sql = member(&first.to_sql());
+        for union in &self.unions {
+            sql.push_str(if union.all { " UNION ALL " } else { " UNION " });
+            sql.push_str(&member(&(union.render)(self.driver)));
+        }
+        self.push_order_and_limit(&mut sql);
+        sql
+    }
+
+    fn push_order_and_limit(&self, sql: &mut String) {
         // Add order by
         if !self.order_by.is_empty() {
             sql.push_str(" ORDER BY ");
             let order_clauses: Vec<String> = self.order_by.iter()
-                .map(|o| format!("{} {}", o.column, o.direction))
+                .map(|o| match o.direction.as_str() {
+                    "" => inline_bindings(&o.column, &o.bindings, self.driver),
+                    "RANDOM" if self.driver == Driver::MySql => "RAND()".to_string(),
+                    "RANDOM" => "RANDOM()".to_string(),
+                    direction if self.driver == Driver::MySql && direction.contains(" NULLS ") => {
+                        let column = quote_identifier(&o.column, self.driver);
+                        let (direction, nulls) = direction.split_once(" NULLS ").unwrap_or_default();
+                        let nulls = if nulls == "FIRST" { " DESC" } else { "" };
+                        format!("{} IS NULL{}, {} {}", column, nulls, column, direction)
+                    }
+                    _ => format!("{} {}", quote_identifier(&o.column, self.driver), o.direction),
+                })
                 .collect();
             sql.push_str(&order_clauses.join(", "));
         }
@@ -365,8 +2540,598 @@ where
         if let Some(offset) = self.offset_value {
             sql.push_str(&format!(" OFFSET {}", offset));
         }
-        
-        sql
+    }
+
+    // The query without its alias HAVING conditions, wrapped in a select that applies them
+    // as a WHERE; None when no HAVING condition names a select alias. Ordering and limits
+    // move to the outer query
+    fn alias_having_sql(&self) -> Option<String> {
+        let aliases = self.select_aliases();
+        let (outer, inner): (Vec<WhereCondition>, Vec<WhereCondition>) = self.having_conditions.iter()
+            .cloned()
+            .partition(|condition| references_alias(condition, &aliases));
+        if outer.is_empty() {
+            return None;
+        }
+
+        let mut query = self.clone();
+        query.having_conditions = inner;
+        query.order_by.clear();
+        query.limit_value = None;
+        query.offset_value = None;
+
+        let table_name = self.table_name();
+        let alias = table_name.rsplit('.').next().unwrap_or(&table_name);
+        let mut sql = format!(
+            "SELECT * FROM ({}) AS {} WHERE {}",
+            query.to_sql(), alias, compile_conditions(&outer, self.driver)
+        );
+        self.push_order_and_limit(&mut sql);
+        Some(sql)
+    }
+}
+
+// Render a value as an inline SQL literal
+fn format_value(value: &Value, driver: Driver) -> String {
+    match value {
+        Value::String(s) => quote_string(s, driver),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Null => "NULL".to_string(),
+        Value::Array(arr) => format!("({})", 
+            arr.iter()
+               .map(|v| match v {
+                   Value::String(s) => quote_string(s, driver),
+                   Value::Number(n) => n.to_string(),
+                   _ => "NULL".to_string(),
+               })
+               .collect::<Vec<_>>()
+               .join(", ")),
+        _ => "NULL".to_string(),
+    }
+}
+
+// The named relation on T; an undefined one panics, as Laravel throws for it
+fn relation_definition<T: Model>(relation: &str) -> RelationDefinition {
+    T::relation_definition(relation).unwrap_or_else(|| {
+        panic!("Call to undefined relationship [{}] on model [{}]", relation, T::table_name())
+    })
+}
+
+// The related table, joined to the pivot table for belongs_to_many
+fn relation_from(definition: &RelationDefinition) -> String {
+    match &definition.pivot {
+        Some(pivot) => format!("{} INNER JOIN {} ON {}.{} = {}.{}",
+            definition.related_table, pivot.table, pivot.table, pivot.related_pivot_key,
+            definition.related_table, pivot.related_key),
+        None => definition.related_table.clone(),
+    }
+}
+
+// Keys compared as text, so 1 and "1" (as a text-decoded column) match
+fn key_text(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+// String literal with quotes doubled. MySQL also treats backslash as an escape
+// character (unless NO_BACKSLASH_ESCAPES is set), so it is doubled there too
+fn quote_string(s: &str, driver: Driver) -> String {
+    let escaped = s.replace('\'', "''");
+    match driver {
+        Driver::MySql => format!("'{}'", escaped.replace('\\', "\\\\")),
+        Driver::Postgres | Driver::Sqlite => format!("'{}'", escaped),
+    }
+}
+
+// Whether two rows have the same columns, in any order
+fn same_columns(row: &HashMap<String, Value>, other: &HashMap<String, Value>) -> bool {
+    row.len() == other.len() && row.keys().all(|column| other.contains_key(column))
+}
+
+fn sorted_columns(row: &HashMap<String, Value>) -> String {
+    let mut columns: Vec<&str> = row.keys().map(String::as_str).collect();
+    columns.sort();
+    columns.join(", ")
+}
+
+// First column of a row as a number, e.g. COUNT(*) AS aggregate. NULL and anything else
+// that isn't an integer is a decode error rather than a zero
+fn first_number(row: Vec<(String, Value)>) -> Result<i64, sqlx::Error> {
+    let (column, value) = row.into_iter().next().ok_or(sqlx::Error::ColumnIndexOutOfBounds { index: 0, len: 0 })?;
+    integer(&column, value)
+}
+
+// An integer cell, which drivers may return as text (e.g. a count cast to CHAR); anything
+// else is a decode error rather than a silent 0
+fn integer(column: &str, value: Value) -> Result<i64, sqlx::Error> {
+    let number = match &value {
+        Value::String(text) => text.parse().ok(),
+        Value::Number(number) => number.as_i64(),
+        _ => None,
+    };
+    number.ok_or_else(|| sqlx::Error::Decode(format!("column [{}] is not an integer: {}", column, value).into()))
+}
+
+// The attributes that are written to the table, i.e. without Model::non_persistent() fields
+fn persistent<M: Model>(attributes: &HashMap<String, Value>) -> impl Iterator<Item = (&String, &Value)> {
+    let computed = M::non_persistent();
+    attributes.iter().filter(move |(column, _)| !computed.contains(&column.as_str()))
+}
+
+fn model_key<M: Model>(model: &M) -> Option<Value> {
+    model.get_key_value().filter(|key| !key.is_null())
+}
+
+fn null_condition(column: &str, operator: &str) -> WhereCondition {
+    WhereCondition {
+        column: column.to_string(),
+        operator: operator.to_string(),
+        value: Value::Null,
+        boolean: "AND".to_string(),
+    }
+}
+
+fn condition(column: &str, operator: &str, value: Value, boolean: &str) -> WhereCondition {
+    WhereCondition {
+        column: column.to_string(),
+        operator: operator.to_string(),
+        value,
+        boolean: boolean.to_string(),
+    }
+}
+
+fn between_condition(column: &str, operator: &str, min: Value, max: Value) -> WhereCondition {
+    WhereCondition {
+        column: column.to_string(),
+        operator: operator.to_string(),
+        value: Value::Array(vec![min, max]),
+        boolean: "AND".to_string(),
+    }
+}
+
+// Rendered per driver by compile_condition
+fn date_condition(column: &str, operator: &str) -> WhereCondition {
+    WhereCondition {
+        column: column.to_string(),
+        operator: operator.to_string(),
+        value: Value::Null,
+        boolean: "AND".to_string(),
+    }
+}
+
+// Raw conditions keep their SQL in `column` and their bindings in `value`
+fn raw_condition(sql: &str, bindings: Vec<Value>) -> WhereCondition {
+    WhereCondition {
+        column: sql.to_string(),
+        operator: "RAW".to_string(),
+        value: Value::Array(bindings),
+        boolean: "AND".to_string(),
+    }
+}
+
+// Deserialize a fetched row. Cells keep the JSON type they were decoded as, so each field
+// gets the column's own value and nothing is converted between fields
+fn hydrate<U>(columns: &[String], row: Vec<Value>) -> Result<U, serde_json::Error>
+where
+    U: for<'de> serde::Deserialize<'de>,
+{
+    hydrate_object(columns.iter().cloned().zip(row).collect())
+}
+
+fn hydrate_object<U>(attributes: serde_json::Map<String, Value>) -> Result<U, serde_json::Error>
+where
+    U: for<'de> serde::Deserialize<'de>,
+{
+    serde_json::from_value(Value::Object(attributes))
+}
+
+fn coerce_scalar(value: Value) -> Value {
+    let Value::String(text) = &value else {
+        return value;
+    };
+    if let Ok(number) = text.parse::<i64>() {
+        return Value::from(number);
+    }
+    if let Ok(number) = text.parse::<f64>()
+        && let Some(number) = serde_json::Number::from_f64(number)
+    {
+        return Value::Number(number);
+    }
+    match text.as_str() {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => value,
+    }
+}
+
+// Whether a HAVING condition is on one of the query's select aliases. Raw conditions
+// count when the alias appears in them as a whole word
+fn references_alias(condition: &WhereCondition, aliases: &[&str]) -> bool {
+    if condition.operator != "RAW" {
+        return aliases.contains(&condition.column.as_str());
+    }
+    condition.column
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .any(|word| aliases.contains(&word))
+}
+
+// Fill `?` placeholders with inline literals. A fragment without bindings is left as
+// written, so it can use Postgres' `?` JSON operators
+fn inline_bindings(sql: &str, bindings: &[Value], driver: Driver) -> String {
+    if bindings.is_empty() {
+        return sql.to_string();
+    }
+    let mut bindings = bindings.iter();
+    let mut segments = placeholder_segments(sql).into_iter();
+    let mut result = segments.next().unwrap_or_default().to_string();
+    for segment in segments {
+        match bindings.next() {
+            Some(value) => result.push_str(&format_value(value, driver)),
+            None => result.push('?'),
+        }
+        result.push_str(segment);
+    }
+    result
+}
+
+// sql split on its `?` placeholders. A `?` inside a quoted string or identifier isn't one
+fn placeholder_segments(sql: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    for (i, ch) in sql.char_indices() {
+        match (quote, ch) {
+            (None, '\'' | '"' | '`') => quote = Some(ch),
+            (Some(open), _) if ch == open => quote = None,
+            (None, '?') => {
+                segments.push(&sql[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    segments.push(&sql[start..]);
+    segments
+}
+
+// Whether sql has an OR outside quotes and parentheses
+fn has_top_level_or(sql: &str) -> bool {
+    let mut quote = None;
+    let mut depth = 0;
+    let mut word = String::new();
+    for ch in sql.chars().chain([' ']) {
+        match (quote, ch) {
+            (None, '\'' | '"' | '`') => quote = Some(ch),
+            (Some(open), _) if ch == open => quote = None,
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            _ => {}
+        }
+        if quote.is_none() && (ch.is_alphanumeric() || ch == '_') {
+            word.push(ch);
+            continue;
+        }
+        if depth == 0 && word.eq_ignore_ascii_case("or") {
+            return true;
+        }
+        word.clear();
+    }
+    false
+}
+
+// A fragment with bindings needs exactly one placeholder per binding
+fn check_bindings(sql: &str, bindings: &[Value]) -> Result<(), String> {
+    let placeholders = placeholder_segments(sql).len() - 1;
+    if bindings.is_empty() || placeholders == bindings.len() {
+        return Ok(());
+    }
+    Err(format!(
+        "raw SQL [{}] has {} `?` placeholder(s) but {} binding(s) were given",
+        sql, placeholders, bindings.len()
+    ))
+}
+
+fn debug_check_bindings(sql: &str, bindings: &[Value]) {
+    if cfg!(debug_assertions) && let Err(message) = check_bindings(sql, bindings) {
+        panic!("{}", message);
+    }
+}
+
+// Render conditions joined by their AND/OR booleans
+fn compile_conditions(conditions: &[WhereCondition], driver: Driver) -> String {
+    let mut sql = String::new();
+    for (i, condition) in conditions.iter().enumerate() {
+        if i > 0 {
+            sql.push_str(&format!(" {} ", condition.boolean));
+        }
+        sql.push_str(&compile_condition(condition, driver));
+    }
+    sql
+}
+
+fn compile_condition(condition: &WhereCondition, driver: Driver) -> String {
+    let column = &quote_identifier(&condition.column, driver);
+    match condition.operator.as_str() {
+        // The column holds the comma-separated columns, the value one array per row
+        "ROW IN" => match &condition.value {
+            Value::Array(rows) if rows.is_empty() => "0 = 1".to_string(),
+            Value::Array(rows) if driver == Driver::Sqlite => row_in_fallback(&condition.column, rows, driver),
+            Value::Array(rows) => format!("({}) IN ({})", quote_identifiers(&condition.column, driver),
+                rows.iter().map(|row| format_value(row, driver)).collect::<Vec<_>>().join(", ")),
+            _ => "0 = 1".to_string(),
+        },
+        // The column holds the subquery for existence checks, the value its bindings and the
+        // where_has constraints
+        "EXISTS" | "NOT EXISTS" => {
+            let (bindings, constraints) = match &condition.value {
+                Value::Array(parts) if parts.len() == 2 => (
+                    parts[0].as_array().cloned().unwrap_or_default(),
+                    serde_json::from_value::<Vec<WhereCondition>>(parts[1].clone()).unwrap_or_default(),
+                ),
+                _ => (Vec::new(), Vec::new()),
+            };
+            let mut subquery = inline_bindings(&condition.column, &bindings, driver);
+            match constraints.len() {
+                0 => {}
+                1 => subquery.push_str(&format!(" AND {}", compile_conditions(&constraints, driver))),
+                _ => subquery.push_str(&format!(" AND ({})", compile_conditions(&constraints, driver))),
+            }
+            format!("{} ({})", condition.operator, subquery)
+        }
+        "IS NULL" | "IS NOT NULL" => format!("{} {}", column, condition.operator),
+        "BETWEEN" | "NOT BETWEEN" => match &condition.value {
+            Value::Array(range) if range.len() == 2 => format!("{} {} {} AND {}",
+                column, condition.operator, format_value(&range[0], driver), format_value(&range[1], driver)),
+            _ => format!("{} {} NULL AND NULL", column, condition.operator),
+        },
+        // Parenthesized when it has its own top-level OR, so the conditions around it
+        // can't regroup it, e.g. `c = 1 AND (a = 1 OR b = 2)`
+        "RAW" => {
+            let sql = match &condition.value {
+                Value::Array(bindings) => inline_bindings(&condition.column, bindings, driver),
+                _ => condition.column.clone(),
+            };
+            if has_top_level_or(&sql) { format!("({})", sql) } else { sql }
+        }
+        "JSON_KEY" => {
+            let path = condition.value.as_str().unwrap_or_default();
+            match driver {
+                Driver::MySql => format!("JSON_CONTAINS_PATH({}, 'one', {}) = 1", column, json_path(path, driver)),
+                Driver::Postgres => format!("{} IS NOT NULL", json_path_expression(column, path)),
+                Driver::Sqlite => format!("json_type({}, {}) IS NOT NULL", column, json_path(path, driver)),
+            }
+        }
+        "FUTURE" => format!("{} > {}", column, current_timestamp(driver)),
+        "PAST" => format!("{} < {}", column, current_timestamp(driver)),
+        "TODAY" => match driver {
+            Driver::MySql => format!("DATE({}) = CURDATE()", column),
+            Driver::Postgres => format!("{}::date = CURRENT_DATE", column),
+            Driver::Sqlite => format!("date({}) = date('now')", column),
+        },
+        operator => match operator.split_once(' ') {
+            // Date part comparisons are stored as "<PART> <operator>"
+            Some((part @ ("DATE" | "TIME" | "YEAR" | "MONTH" | "DAY"), operator)) => format!("{} {} {}",
+                date_part(part, column, driver), operator, format_value(&condition.value, driver)),
+            // Aggregates from having_aggregate are stored as "<FUNCTION> <operator>"
+            Some((function @ ("COUNT" | "SUM" | "AVG" | "MIN" | "MAX"), operator)) => format!("{}({}) {} {}",
+                function, column, operator, format_value(&condition.value, driver)),
+            Some(("RAW", operator)) => {
+                let (expression, value) = match &condition.value {
+                    Value::Array(parts) if parts.len() == 2 => (serde_json::from_value::<Expression>(parts[0].clone()).ok(), &parts[1]),
+                    _ => (None, &Value::Null),
+                };
+                let expression = expression.map_or_else(|| condition.column.clone(), |expression| expression.to_sql(driver));
+                format!("{} {} {}", expression, operator, format_value(value, driver))
+            }
+            Some(("EXPR", operator)) => format!("{} {} {}", column, operator, condition.value.as_str().unwrap_or("NULL")),
+            // Typed comparisons are stored as "TYPED <operator>", the value as [value, type].
+            // JSON objects and arrays are compared as their JSON text
+            Some(("TYPED", operator)) => {
+                let (value, pg_type) = match &condition.value {
+                    Value::Array(typed) if typed.len() == 2 => (&typed[0], typed[1].as_str().unwrap_or_default()),
+                    _ => (&Value::Null, ""),
+                };
+                let literal = match value {
+                    Value::Object(_) | Value::Array(_) => quote_string(&value.to_string(), driver),
+                    _ => format_value(value, driver),
+                };
+                match driver {
+                    Driver::Postgres if !pg_type.is_empty() => format!("{} {} {}::{}", column, operator, literal, pg_type),
+                    _ => format!("{} {} {}", column, operator, literal),
+                }
+            }
+            // JSON lengths are stored as "JSON_LENGTH <operator>", the value as [path, value]
+            Some(("JSON_LENGTH", operator)) => {
+                let (path, value) = match &condition.value {
+                    Value::Array(length) if length.len() == 2 => (length[0].as_str().unwrap_or_default(), &length[1]),
+                    _ => ("", &Value::Null),
+                };
+                let length = match driver {
+                    Driver::MySql => format!("JSON_LENGTH({}, {})", column, json_path(path, driver)),
+                    Driver::Postgres => format!("jsonb_array_length({})", json_path_expression(column, path)),
+                    Driver::Sqlite => format!("json_array_length({}, {})", column, json_path(path, driver)),
+                };
+                format!("{} {} {}", length, operator, format_value(value, driver))
+            }
+            // Case-insensitive comparisons are stored as "CI <operator>". Postgres has ILIKE
+            // for patterns; everything else lowercases both sides
+            Some(("CI", operator)) => match (driver, operator.to_uppercase().as_str()) {
+                (Driver::Postgres, "LIKE") => format!("{} ILIKE {}", column, format_value(&condition.value, driver)),
+                (Driver::Postgres, "NOT LIKE") => format!("{} NOT ILIKE {}", column, format_value(&condition.value, driver)),
+                _ => format!("LOWER({}) {} LOWER({})", column, operator, format_value(&condition.value, driver)),
+            },
+            _ => format!("{} {} {}", column, condition.operator, format_value(&condition.value, driver)),
+        },
+    }
+}
+
+// A dot-separated path as a MySQL/SQLite JSON path literal: meta.tags.0 becomes '$."meta"."tags"[0]'
+fn json_path(path: &str, driver: Driver) -> String {
+    let mut json_path = String::from("$");
+    for segment in path.split('.').filter(|segment| !segment.is_empty()) {
+        if segment.chars().all(|c| c.is_ascii_digit()) {
+            json_path.push_str(&format!("[{}]", segment));
+        } else {
+            json_path.push_str(&format!(".\"{}\"", segment.replace('"', "\\\"")));
+        }
+    }
+    quote_string(&json_path, driver)
+}
+
+// The Postgres jsonb value at a dot-separated path, e.g. "settings"::jsonb -> 'meta' -> 0
+fn json_path_expression(column: &str, path: &str) -> String {
+    let mut expression = format!("{}::jsonb", column);
+    for segment in path.split('.').filter(|segment| !segment.is_empty()) {
+        if segment.chars().all(|c| c.is_ascii_digit()) {
+            expression.push_str(&format!(" -> {}", segment));
+        } else {
+            expression.push_str(&format!(" -> {}", quote_string(segment, Driver::Postgres)));
+        }
+    }
+    expression
+}
+
+fn date_part(part: &str, column: &str, driver: Driver) -> String {
+    match (part, driver) {
+        ("DATE", Driver::Postgres) => format!("{}::date", column),
+        ("DATE", Driver::Sqlite) => format!("date({})", column),
+        ("TIME", Driver::Postgres) => format!("{}::time", column),
+        ("TIME", Driver::Sqlite) => format!("time({})", column),
+        (_, Driver::Postgres) => format!("EXTRACT({} FROM {})", part, column),
+        (_, Driver::Sqlite) => {
+            let format = match part {
+                "YEAR" => "%Y",
+                "MONTH" => "%m",
+                _ => "%d",
+            };
+            format!("CAST(strftime('{}', {}) AS INTEGER)", format, column)
+        }
+        (_, Driver::MySql) => format!("{}({})", part, column),
+    }
+}
+
+// Quotes each dot-separated part of a plain name: users.id becomes "users"."id" (`users`.`id`
+// on MySQL) and users.* becomes "users".*. Anything else, such as expressions, aliases and
+// names that are already quoted, is left as written
+fn quote_identifier(identifier: &str, driver: Driver) -> String {
+    let plain = identifier.split('.').all(|part| {
+        part == "*"
+            || (part.starts_with(|c: char| c.is_alphabetic() || c == '_')
+                && part.chars().all(|c| c.is_alphanumeric() || c == '_'))
+    });
+    if !plain || identifier == "*" {
+        return identifier.to_string();
+    }
+
+    let quote = if driver == Driver::MySql { '`' } else { '"' };
+    identifier.split('.')
+        .map(|part| if part == "*" { part.to_string() } else { format!("{}{}{}", quote, part, quote) })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+// quote_identifier over a comma-separated list of names
+fn quote_identifiers(identifiers: &str, driver: Driver) -> String {
+    identifiers.split(", ").map(|identifier| quote_identifier(identifier, driver)).collect::<Vec<_>>().join(", ")
+}
+
+fn current_timestamp(driver: Driver) -> &'static str {
+    match driver {
+        Driver::MySql | Driver::Postgres => "NOW()",
+        Driver::Sqlite => "CURRENT_TIMESTAMP",
+    }
+}
+
+// (a = 1 AND b = 2) OR (a = 3 AND b = 4), for drivers without row-value IN
+fn row_in_fallback(columns: &str, rows: &[Value], driver: Driver) -> String {
+    let columns: Vec<String> = columns.split(", ").map(|column| quote_identifier(column, driver)).collect();
+    let rows: Vec<String> = rows.iter().map(|row| {
+        let values = row.as_array().map(Vec::as_slice).unwrap_or_default();
+        let pairs: Vec<String> = columns.iter().zip(values)
+            .map(|(column, value)| format!("{} = {}", column, format_value(value, driver)))
+            .collect();
+        format!("({})", pairs.join(" AND "))
+    }).collect();
+    format!("({})", rows.join(" OR "))
+}
+
+impl<T> Default for Query<T>
+where
+    T: Model + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Results of get_with_metrics
+#[derive(Debug, Clone)]
+pub struct QueryResult<T> {
+    pub data: T,
+    pub duration: Duration,
+    pub rows_returned: u64,
+}
+
+// Relations get_loaded loaded for one model, by name
+type LoadedRelations = HashMap<String, Vec<Loaded<serde_json::Map<String, Value>>>>;
+
+// A model with the relations get_loaded eager loaded for it. The related models' types
+// aren't known while loading, so their rows are kept as attributes until related() is called
+#[derive(Debug, Clone)]
+pub struct Loaded<T> {
+    model: T,
+    relations: LoadedRelations,
+}
+
+impl<T> Loaded<T> {
+    pub fn model(&self) -> &T {
+        &self.model
+    }
+
+    pub fn into_inner(self) -> T {
+        self.model
+    }
+
+    pub fn relation_loaded(&self, name: &str) -> bool {
+        self.relations.contains_key(name)
+    }
+
+    // The rows loaded for name as R, e.g. `post.related::<Comment>("comments")`; empty when
+    // name wasn't loaded
+    pub fn related<R: Model>(&self, name: &str) -> Result<Vec<R>, EloquentError> {
+        self.relations.get(name)
+            .into_iter()
+            .flatten()
+            .map(|row| hydrate_related(row.model.clone()))
+            .collect()
+    }
+
+    // As related, keeping the relations nested under name, e.g. the comments of
+    // `user.related_loaded::<Post>("posts")` for with(vec!["posts.comments"])
+    pub fn related_loaded<R: Model>(&self, name: &str) -> Result<Vec<Loaded<R>>, EloquentError> {
+        self.relations.get(name)
+            .into_iter()
+            .flatten()
+            .map(|row| Ok(Loaded { model: hydrate_related(row.model.clone())?, relations: row.relations.clone() }))
+            .collect()
+    }
+}
+
+fn hydrate_related<R: Model>(row: serde_json::Map<String, Value>) -> Result<R, EloquentError> {
+    hydrate_object(row).map_err(|error| EloquentError::Hydration {
+        table: R::qualified_table_name(),
+        error,
+    })
+}
+
+impl<T> std::ops::Deref for Loaded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.model
     }
 }
 
@@ -382,3 +3147,92 @@ pub struct Pagination<T> {
     pub to: i64,
 }
 
+impl<T> Pagination<T> {
+    pub fn has_more_pages(&self) -> bool {
+        self.current_page < self.last_page
+    }
+
+    pub fn on_first_page(&self) -> bool {
+        self.current_page <= 1
+    }
+
+    pub fn on_last_page(&self) -> bool {
+        !self.has_more_pages()
+    }
+
+    pub fn next_page(&self) -> Option<i64> {
+        self.has_more_pages().then_some(self.current_page + 1)
+    }
+
+    pub fn previous_page(&self) -> Option<i64> {
+        (!self.on_first_page()).then_some(self.current_page - 1)
+    }
+
+    // Page numbers to show around the current page, clamped to the valid range
+    pub fn links(&self, on_each_side: i64) -> Vec<i64> {
+        let start = (self.current_page - on_each_side).max(1);
+        let end = (self.current_page + on_each_side).min(self.last_page);
+        (start..=end).collect()
+    }
+}
+
+impl<T: Serialize> Pagination<T> {
+    // Laravel's paginator JSON envelope. URL fields are only included when base_url is non-empty
+    pub fn to_response(&self, base_url: &str) -> Value {
+        let (from, to) = if self.data.is_empty() {
+            (Value::Null, Value::Null)
+        } else {
+            (self.from.into(), self.to.into())
+        };
+
+        let mut response = serde_json::json!({
+            "data": serde_json::to_value(&self.data).unwrap_or(Value::Array(Vec::new())),
+            "current_page": self.current_page,
+            "per_page": self.per_page,
+            "total": self.total,
+            "last_page": self.last_page,
+            "from": from,
+            "to": to,
+        });
+
+        if !base_url.is_empty() {
+            let separator = if base_url.contains('?') { '&' } else { '?' };
+            let page_url = |page: i64| Value::String(format!("{}{}page={}", base_url, separator, page));
+            response["path"] = Value::String(base_url.to_string());
+            response["first_page_url"] = page_url(1);
+            response["last_page_url"] = page_url(self.last_page.max(1));
+            response["next_page_url"] = self.next_page().map(page_url).unwrap_or(Value::Null);
+            response["prev_page_url"] = self.previous_page().map(page_url).unwrap_or(Value::Null);
+        }
+        response
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn non_integer_aggregates_are_decode_errors() {
+        let number = |value: Value| first_number(vec![("aggregate".to_string(), value)]);
+        assert_eq!(number(json!(3)).unwrap(), 3);
+        assert_eq!(number(json!("42")).unwrap(), 42);
+        for value in [Value::Null, json!("many"), json!(1.5)] {
+            assert!(matches!(number(value), Err(sqlx::Error::Decode(_))));
+        }
+    }
+
+    #[test]
+    fn eager_loads_group_by_first_segment_and_reject_deep_paths() {
+        let load = |relation: &str, nested: Vec<EagerLoad>| EagerLoad { relation: relation.to_string(), nested };
+        assert_eq!(EagerLoad::parse(&["posts.comments", "posts.tags", "roles"]).unwrap(), [
+            load("posts", vec![load("comments", vec![]), load("tags", vec![])]),
+            load("roles", vec![]),
+        ]);
+        assert!(EagerLoad::parse(&["a.b.c.d.e"]).is_ok());
+        assert!(matches!(EagerLoad::parse(&["a.b.c.d.e.f"]),
+            Err(EloquentError::Configuration(message)) if message.contains("[a.b.c.d.e.f]")));
+    }
+}
@@ -1,9 +1,10 @@
 use async_trait::async_trait;
 use std::marker::PhantomData;
-use std::collections::HashMap;
+use crate::orm::error::EloquentError;
 use crate::orm::model::Model;
+use crate::orm::builder::QueryBuilder;
 use crate::orm::query::Query;
-use crate::orm::relations::Relation;
+use crate::orm::relations::{default_foreign_key, Relation};
 
 // BelongsTo relationship - similar to Laravel's BelongsTo
 #[derive(Debug)]
@@ -20,9 +21,7 @@ where
     R: Model + Send + Sync + 'static,
 {
     pub fn new(child: T, foreign_key: Option<String>, owner_key: Option<String>) -> Self {
-        let foreign_key = foreign_key.unwrap_or_else(|| {
-            format!("{}_id", R::table_name().trim_end_matches('s'))
-        });
+        let foreign_key = foreign_key.unwrap_or_else(default_foreign_key::<R, T>);
         let owner_key = owner_key.unwrap_or_else(|| R::primary_key().to_string());
         
         Self {
@@ -34,7 +33,7 @@ where
     }
 
     // Associate the child model with a parent
-    pub async fn associate(&mut self, parent: &R) -> Result<(), sqlx::Error> {
+    pub async fn associate(&mut self, _parent: &R) -> Result<(), sqlx::Error> {
         // This would set the foreign key on the child model
         // and save it
         Ok(())
@@ -46,10 +45,13 @@ where
         Ok(())
     }
 
-    // Get the foreign key value
+    // The child's foreign_key attribute; None when it's missing or null
     pub fn get_foreign_key_value(&self) -> Option<serde_json::Value> {
-        // This would extract the foreign key value from the child model
-        None
+        serde_json::to_value(&self.child)
+            .ok()?
+            .get(&self.foreign_key)
+            .filter(|value| !value.is_null())
+            .cloned()
     }
 
     // Check if the relationship is loaded
@@ -76,13 +78,35 @@ where
         self.get_query().first().await
     }
 
+    fn parent(&self) -> &T {
+        &self.child
+    }
+
+    // The owner whose owner_key matches the child's foreign key. Without a foreign key value
+    // nothing matches, rather than every owner
     fn get_query(&self) -> Query<R> {
-        let mut q = Query::new();
-        if let Some(val) = self.child.get_key_value() {
-            if let Some(id_str) = val.as_i64().map(|n| n.to_string()).or_else(|| val.as_str().map(|s| s.to_string())) {
-                q = q.where_clause(&self.owner_key, &id_str);
-            }
+        let q = Query::new();
+        match self.get_foreign_key_value() {
+            Some(val) => q.where_op(&self.owner_key, "=", val),
+            None => q.where_null(&self.owner_key),
+        }
+    }
+
+    // Bump updated_at on the owner. The child needn't be saved, only point at an owner
+    async fn touch(&self) -> Result<u64, EloquentError> {
+        if self.get_foreign_key_value().is_none() {
+            return Ok(0);
         }
-        q
+        self.get_query().touch().await
+    }
+}
+
+impl<T, R> QueryBuilder<R> for BelongsTo<T, R>
+where
+    T: Model + Send + Sync + 'static,
+    R: Model + Send + Sync + 'static,
+{
+    fn into_query(self) -> Query<R> {
+        self.get_query()
     }
 }
@@ -2,8 +2,10 @@ use async_trait::async_trait;
 use std::marker::PhantomData;
 use std::collections::HashMap;
 use crate::orm::model::Model;
+use crate::orm::builder::QueryBuilder;
 use crate::orm::query::Query;
 use crate::orm::relations::{Relation, AttachableRelation};
+use crate::orm::relations::pivot::{Pivoted, PIVOT_PREFIX};
 
 // BelongsToMany relationship - similar to Laravel's BelongsToMany
 #[derive(Debug)]
@@ -12,6 +14,7 @@ pub struct BelongsToMany<T, R> {
     table: String,           // Pivot table name
     foreign_pivot_key: String,
     related_pivot_key: String,
+    #[allow(dead_code)]
     parent_key: String,
     related_key: String,
     pivot_columns: Vec<String>,
@@ -32,9 +35,12 @@ where
         related_key: Option<String>,
     ) -> Self {
         let table = table.unwrap_or_else(|| {
-            let mut tables = vec![T::table_name(), R::table_name()];
+            let mut tables = [T::table_name(), R::table_name()];
             tables.sort();
-            tables.join("_")
+            match T::schema() {
+                Some(schema) => format!("{}.{}", schema, tables.join("_")),
+                None => tables.join("_"),
+            }
         });
         
         let foreign_pivot_key = foreign_pivot_key.unwrap_or_else(|| {
@@ -75,6 +81,22 @@ where
         self
     }
 
+    // Related models paired with their pivot rows: both pivot keys plus the with_pivot
+    // columns, readable through pivot().get(column)
+    pub async fn get_with_pivot(&self) -> Result<Vec<Pivoted<R>>, sqlx::Error> {
+        self.pivot_query().get_as().await
+    }
+
+    // get_query() selecting the related columns plus the pivot columns under PIVOT_PREFIX
+    pub fn pivot_query(&self) -> Query<R> {
+        let mut selects = vec![format!("{}.*", R::qualified_table_name())];
+        let keys = [&self.foreign_pivot_key, &self.related_pivot_key];
+        for column in keys.into_iter().chain(&self.pivot_columns) {
+            selects.push(format!("{}.{} AS {}{}", self.table, column, PIVOT_PREFIX, column));
+        }
+        self.get_query().select(selects.iter().map(String::as_str).collect())
+    }
+
     // Count related models
     pub async fn count(&self) -> Result<i64, sqlx::Error> {
         self.get_query().count().await
@@ -86,19 +108,19 @@ where
     }
 
     // Toggle attachment of models
-    pub async fn toggle(&self, ids: Vec<i64>) -> Result<(), sqlx::Error> {
+    pub async fn toggle(&self, _ids: Vec<i64>) -> Result<(), sqlx::Error> {
         // This would implement toggle functionality
         Ok(())
     }
 
     // Sync with additional pivot data
-    pub async fn sync_with_pivot_data(&self, data: HashMap<i64, HashMap<String, serde_json::Value>>) -> Result<(), sqlx::Error> {
+    pub async fn sync_with_pivot_data(&self, _data: HashMap<i64, HashMap<String, serde_json::Value>>) -> Result<(), sqlx::Error> {
         // This would implement sync with pivot data
         Ok(())
     }
 
     // Update existing pivot record
-    pub async fn update_existing_pivot(&self, id: i64, attributes: HashMap<String, serde_json::Value>) -> Result<(), sqlx::Error> {
+    pub async fn update_existing_pivot(&self, _id: i64, _attributes: HashMap<String, serde_json::Value>) -> Result<(), sqlx::Error> {
         // This would update pivot table data
         Ok(())
     }
@@ -118,28 +140,40 @@ where
         self.get_query().first().await
     }
 
+    fn parent(&self) -> &T {
+        &self.parent
+    }
+
     fn get_query(&self) -> Query<R> {
         // Build a base query for the related model
         let mut q = Query::new();
 
         // Try to get the parent's primary key value
-        if let Some(val) = self.parent.get_key_value() {
-            // Only handle simple number/string keys for now
-            if let Some(id_str) = val.as_i64().map(|n| n.to_string()).or_else(|| val.as_str().map(|s| s.to_string())) {
-                // Join pivot table to related table and filter by pivot foreign key
-                let pivot_foreign_col = format!("{}.{}", self.table, self.foreign_pivot_key);
-                let pivot_related_col = format!("{}.{}", self.table, self.related_pivot_key);
-                let related_full_key = format!("{}.{}", R::table_name(), &self.related_key);
-
-                q = q.join(&self.table, &pivot_related_col, "=", &related_full_key)
-                     .where_clause(&pivot_foreign_col, &id_str);
-            }
+        // The key keeps its JSON type, so numeric and string (e.g. UUID) keys render as such
+        if let Some(val) = self.parent.get_key_value().filter(|val| !val.is_null()) {
+            // Join pivot table to related table and filter by pivot foreign key
+            let pivot_foreign_col = format!("{}.{}", self.table, self.foreign_pivot_key);
+            let pivot_related_col = format!("{}.{}", self.table, self.related_pivot_key);
+            let related_full_key = format!("{}.{}", R::qualified_table_name(), &self.related_key);
+
+            q = q.join(&self.table, &pivot_related_col, "=", &related_full_key)
+                 .where_op(&pivot_foreign_col, "=", val);
         }
 
         q
     }
 }
 
+impl<T, R> QueryBuilder<R> for BelongsToMany<T, R>
+where
+    T: Model + Send + Sync + 'static,
+    R: Model + Send + Sync + 'static,
+{
+    fn into_query(self) -> Query<R> {
+        self.get_query()
+    }
+}
+
 #[async_trait]
 impl<T, R> AttachableRelation<T, R> for BelongsToMany<T, R>
 where
@@ -162,7 +196,7 @@ where
         Ok(())
     }
 
-    async fn sync(&self, ids: Vec<i64>) -> Result<(), sqlx::Error> {
+    async fn sync(&self, _ids: Vec<i64>) -> Result<(), sqlx::Error> {
         // This would:
         // 1. Get currently attached IDs
         // 2. Detach IDs not in the new list
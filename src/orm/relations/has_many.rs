@@ -1,15 +1,18 @@
 use async_trait::async_trait;
 use std::marker::PhantomData;
 use std::collections::HashMap;
+use crate::orm::error::EloquentError;
 use crate::orm::model::Model;
-use crate::orm::query::Query;
-use crate::orm::relations::{Relation, CreatableRelation};
+use crate::orm::builder::QueryBuilder;
+use crate::orm::query::{Aggregate, Query};
+use crate::orm::relations::{has_parent_key, save_related, default_foreign_key, HasOne, Relation, CreatableRelation};
 
 // HasMany relationship - similar to Laravel's HasMany
 #[derive(Debug)]
 pub struct HasMany<T, R> {
     parent: T,
     foreign_key: String,
+    #[allow(dead_code)]
     local_key: String,
     _marker: PhantomData<R>,
 }
@@ -20,9 +23,7 @@ where
     R: Model + Send + Sync + 'static,
 {
     pub fn new(parent: T, foreign_key: Option<String>, local_key: Option<String>) -> Self {
-        let foreign_key = foreign_key.unwrap_or_else(|| {
-            format!("{}_id", T::table_name().trim_end_matches('s'))
-        });
+        let foreign_key = foreign_key.unwrap_or_else(default_foreign_key::<T, R>);
         let local_key = local_key.unwrap_or_else(|| T::primary_key().to_string());
 
         Self {
@@ -33,17 +34,27 @@ where
         }
     }
 
-    // Additional query methods specific to HasMany
+    // The one related row with the greatest column value, e.g. a customer's latest order;
+    // ties go to the row with the highest primary key
+    pub fn latest_of_many(self, column: &str) -> HasOne<T, R> {
+        HasOne::of_many(self.parent, self.foreign_key, self.local_key, column, Aggregate::Max)
+    }
+
+    pub fn oldest_of_many(self, column: &str) -> HasOne<T, R> {
+        HasOne::of_many(self.parent, self.foreign_key, self.local_key, column, Aggregate::Min)
+    }
+
+    // Kept as inherent methods so callers don't need QueryBuilder in scope; they delegate to it
     pub fn where_clause(self, column: &str, value: &str) -> Query<R> {
-        self.get_query().where_clause(column, value)
+        QueryBuilder::where_clause(self, column, value)
     }
 
     pub fn order_by(self, column: &str, direction: &str) -> Query<R> {
-        self.get_query().order_by(column, direction)
+        QueryBuilder::order_by(self, column, direction)
     }
 
     pub fn limit(self, limit: i64) -> Query<R> {
-        self.get_query().limit(limit)
+        QueryBuilder::limit(self, limit)
     }
 
     // Count related models
@@ -56,16 +67,21 @@ where
         self.get_query().exists().await
     }
 
-    // Delete all related models
-    pub async fn delete(&self) -> Result<u64, sqlx::Error> {
-        // This would implement deletion of related models
-        Ok(0)
+    // Delete all related models, returning the deleted count. Without a parent key
+    // the relation can't be narrowed to this parent, so nothing is deleted
+    pub async fn delete(&self) -> Result<u64, EloquentError> {
+        if !has_parent_key(&self.parent) {
+            return Ok(0);
+        }
+        self.get_query().delete().await
     }
 
-    // Update all related models
-    pub async fn update(&self, attributes: HashMap<String, serde_json::Value>) -> Result<u64, sqlx::Error> {
-        // This would implement bulk update of related models
-        Ok(0)
+    // Update all related models, returning the updated count; nothing without a parent key
+    pub async fn update(&self, attributes: HashMap<String, serde_json::Value>) -> Result<u64, EloquentError> {
+        if !has_parent_key(&self.parent) {
+            return Ok(0);
+        }
+        self.get_query().update(attributes).await
     }
 }
 
@@ -83,32 +99,43 @@ where
         self.get_query().first().await
     }
 
+    fn parent(&self) -> &T {
+        &self.parent
+    }
+
     fn get_query(&self) -> Query<R> {
         let mut q = Query::new();
-        if let Some(val) = self.parent.get_key_value() {
-            // Only handle simple number/string keys for now
-            if let Some(id_str) = val.as_i64().map(|n| n.to_string()).or_else(|| val.as_str().map(|s| s.to_string())) {
-                q = q.where_clause(&self.foreign_key, &id_str);
-            }
+        if let Some(val) = self.parent.get_key_value().filter(|val| !val.is_null()) {
+            q = q.where_op(&self.foreign_key, "=", val);
         }
         q
     }
 }
 
+impl<T, R> QueryBuilder<R> for HasMany<T, R>
+where
+    T: Model + Send + Sync + 'static,
+    R: Model + Send + Sync + 'static,
+{
+    fn into_query(self) -> Query<R> {
+        self.get_query()
+    }
+}
+
 #[async_trait]
 impl<T, R> CreatableRelation<T, R> for HasMany<T, R>
 where
     T: Model + Send + Sync + 'static,
     R: Model + Send + Sync + 'static,
 {
-    async fn create(&self, mut attributes: HashMap<String, serde_json::Value>) -> Result<R, sqlx::Error> {
+    async fn create(&self, attributes: HashMap<String, serde_json::Value>) -> Result<R, sqlx::Error> {
         // Set the foreign key to the parent's primary key value
         // attributes.insert(self.foreign_key.clone(), parent_key_value);
         R::create(attributes).await
     }
 
+    // Saves the model pointing at the parent, e.g. `post.comments().save(&comment)`
     async fn save(&self, model: &R) -> Result<(), sqlx::Error> {
-        // This would save the model with the correct foreign key
-        Ok(())
+        save_related(&self.parent, model, |key| HashMap::from([(self.foreign_key.clone(), key)])).await
     }
 }
@@ -1,9 +1,11 @@
 use async_trait::async_trait;
 use std::marker::PhantomData;
 use std::collections::HashMap;
+use crate::orm::error::EloquentError;
 use crate::orm::model::Model;
+use crate::orm::builder::QueryBuilder;
 use crate::orm::query::Query;
-use crate::orm::relations::{Relation, CreatableRelation};
+use crate::orm::relations::{has_parent_key, save_related, Relation, CreatableRelation};
 
 // HasMorphMany relationship - similar to Laravel's morphMany
 #[derive(Debug)]
@@ -11,6 +13,7 @@ pub struct HasMorphMany<T, R> {
     parent: T,
     morph_type: String,     // Column that stores the model type
     morph_id: String,       // Column that stores the model ID
+    #[allow(dead_code)]
     local_key: String,
     _marker: PhantomData<R>,
 }
@@ -42,21 +45,20 @@ where
 
     // Get the morph type value for the parent model
     fn get_morph_type(&self) -> String {
-        // This would return the class name or a configured morph map value
-        T::table_name().to_string()
+        T::morph_class().to_string()
     }
 
-    // Additional query methods specific to HasMorphMany
+    // Kept as inherent methods so callers don't need QueryBuilder in scope; they delegate to it
     pub fn where_clause(self, column: &str, value: &str) -> Query<R> {
-        self.get_query().where_clause(column, value)
+        QueryBuilder::where_clause(self, column, value)
     }
 
     pub fn order_by(self, column: &str, direction: &str) -> Query<R> {
-        self.get_query().order_by(column, direction)
+        QueryBuilder::order_by(self, column, direction)
     }
 
     pub fn limit(self, limit: i64) -> Query<R> {
-        self.get_query().limit(limit)
+        QueryBuilder::limit(self, limit)
     }
 
     // Count related models
@@ -69,16 +71,21 @@ where
         self.get_query().exists().await
     }
 
-    // Delete all related models
-    pub async fn delete(&self) -> Result<u64, sqlx::Error> {
-        // This would implement deletion of related models
-        Ok(0)
+    // Delete all related models, returning the deleted count. Without a parent key
+    // the relation can't be narrowed to this parent, so nothing is deleted
+    pub async fn delete(&self) -> Result<u64, EloquentError> {
+        if !has_parent_key(&self.parent) {
+            return Ok(0);
+        }
+        self.get_query().delete().await
     }
 
-    // Update all related models
-    pub async fn update(&self, attributes: HashMap<String, serde_json::Value>) -> Result<u64, sqlx::Error> {
-        // This would implement bulk update of related models
-        Ok(0)
+    // Update all related models, returning the updated count; nothing without a parent key
+    pub async fn update(&self, attributes: HashMap<String, serde_json::Value>) -> Result<u64, EloquentError> {
+        if !has_parent_key(&self.parent) {
+            return Ok(0);
+        }
+        self.get_query().update(attributes).await
     }
 
     // Get results with pagination
@@ -101,14 +108,16 @@ where
         self.get_query().first().await
     }
 
+    fn parent(&self) -> &T {
+        &self.parent
+    }
+
     fn get_query(&self) -> Query<R> {
         let mut q = Query::new();
         // Add polymorphic constraints if parent key exists
-        if let Some(val) = self.parent.get_key_value() {
-            if let Some(id_str) = val.as_i64().map(|n| n.to_string()).or_else(|| val.as_str().map(|s| s.to_string())) {
-                q = q.where_clause(&self.morph_type, &self.get_morph_type())
-                     .where_clause(&self.morph_id, &id_str);
-            }
+        if let Some(val) = self.parent.get_key_value().filter(|val| !val.is_null()) {
+            q = q.where_clause(&self.morph_type, &self.get_morph_type())
+                 .where_op(&self.morph_id, "=", val);
         } else {
             q = q.where_clause(&self.morph_type, &self.get_morph_type());
         }
@@ -116,6 +125,16 @@ where
     }
 }
 
+impl<T, R> QueryBuilder<R> for HasMorphMany<T, R>
+where
+    T: Model + Send + Sync + 'static,
+    R: Model + Send + Sync + 'static,
+{
+    fn into_query(self) -> Query<R> {
+        self.get_query()
+    }
+}
+
 #[async_trait]
 impl<T, R> CreatableRelation<T, R> for HasMorphMany<T, R>
 where
@@ -129,8 +148,11 @@ where
         R::create(attributes).await
     }
 
+    // Saves the model pointing at the parent through both morph columns
     async fn save(&self, model: &R) -> Result<(), sqlx::Error> {
-        // This would save the model with the correct morph fields
-        Ok(())
+        save_related(&self.parent, model, |key| HashMap::from([
+            (self.morph_type.clone(), serde_json::Value::String(self.get_morph_type())),
+            (self.morph_id.clone(), key),
+        ])).await
     }
 }
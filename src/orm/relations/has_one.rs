@@ -1,16 +1,20 @@
 use async_trait::async_trait;
 use std::marker::PhantomData;
 use std::collections::HashMap;
+use crate::orm::error::EloquentError;
 use crate::orm::model::Model;
-use crate::orm::query::Query;
-use crate::orm::relations::{Relation, CreatableRelation};
+use crate::orm::builder::QueryBuilder;
+use crate::orm::query::{Aggregate, Query};
+use crate::orm::relations::{has_parent_key, save_related, default_foreign_key, Relation, CreatableRelation};
 
 // HasOne relationship - similar to Laravel's HasOne
 #[derive(Debug)]
 pub struct HasOne<T, R> {
     parent: T,
     foreign_key: String,
+    #[allow(dead_code)]
     local_key: String,
+    of_many: Option<(String, Aggregate)>, // Column and MAX/MIN for latest_of_many / oldest_of_many
     _marker: PhantomData<R>,
 }
 
@@ -20,22 +24,32 @@ where
     R: Model + Send + Sync + 'static,
 {
     pub fn new(parent: T, foreign_key: Option<String>, local_key: Option<String>) -> Self {
-        let foreign_key = foreign_key.unwrap_or_else(|| {
-            format!("{}_id", T::table_name().trim_end_matches('s'))
-        });
+        let foreign_key = foreign_key.unwrap_or_else(default_foreign_key::<T, R>);
         let local_key = local_key.unwrap_or_else(|| T::primary_key().to_string());
         
         Self {
             parent,
             foreign_key,
             local_key,
+            of_many: None,
             _marker: PhantomData,
         }
     }
 
-    // Additional query methods specific to HasOne
+    // The single related row with the MAX/MIN of column, from HasMany::latest_of_many
+    pub(crate) fn of_many(parent: T, foreign_key: String, local_key: String, column: &str, function: Aggregate) -> Self {
+        Self {
+            parent,
+            foreign_key,
+            local_key,
+            of_many: Some((column.to_string(), function)),
+            _marker: PhantomData,
+        }
+    }
+
+    // Kept as an inherent method so callers don't need QueryBuilder in scope; it delegates to it
     pub fn where_clause(self, column: &str, value: &str) -> Query<R> {
-        self.get_query().where_clause(column, value)
+        QueryBuilder::where_clause(self, column, value)
     }
 
     // Check if the related model exists
@@ -43,16 +57,21 @@ where
         self.get_query().exists().await
     }
 
-    // Delete the related model
-    pub async fn delete(&self) -> Result<u64, sqlx::Error> {
-        // This would implement deletion of the related model
-        Ok(0)
+    // Delete the related model, returning the deleted count. Without a parent key
+    // the relation can't be narrowed to this parent, so nothing is deleted
+    pub async fn delete(&self) -> Result<u64, EloquentError> {
+        if !has_parent_key(&self.parent) {
+            return Ok(0);
+        }
+        self.get_query().delete().await
     }
 
-    // Update the related model
-    pub async fn update(&self, attributes: HashMap<String, serde_json::Value>) -> Result<(), sqlx::Error> {
-        // This would implement update of the related model
-        Ok(())
+    // Update the related model, returning the updated count; nothing without a parent key
+    pub async fn update(&self, attributes: HashMap<String, serde_json::Value>) -> Result<u64, EloquentError> {
+        if !has_parent_key(&self.parent) {
+            return Ok(0);
+        }
+        self.get_query().update(attributes).await
     }
 }
 
@@ -73,31 +92,69 @@ where
         self.get_query().first().await
     }
 
+    fn parent(&self) -> &T {
+        &self.parent
+    }
+
     fn get_query(&self) -> Query<R> {
         let mut q = Query::new();
-        if let Some(val) = self.parent.get_key_value() {
-            if let Some(id_str) = val.as_i64().map(|n| n.to_string()).or_else(|| val.as_str().map(|s| s.to_string())) {
-                q = q.where_clause(&self.foreign_key, &id_str);
-            }
+        let parent_key = self.parent.get_key_value().filter(|val| !val.is_null());
+        if let Some(val) = &parent_key {
+            q = q.where_op(&self.foreign_key, "=", val.clone());
+        }
+
+        // Picks the primary key of the row holding the extreme value, ties going to the highest
+        // key, so each parent gets exactly one row. With a parent key the subquery is a derived
+        // table over that key: LIMIT keeps MySQL from merging it into the outer statement, which
+        // lets delete/update read the table they write (error 1093). Without one it correlates
+        // on the foreign key, so it still picks one row per parent across many parents
+        if let Some((column, function)) = &self.of_many {
+            let table = R::qualified_table_name();
+            let key = R::primary_key();
+            let direction = if matches!(function, Aggregate::Min) { "ASC" } else { "DESC" };
+            let pick = |scope: &str| format!(
+                "SELECT of_many.{key} FROM {table} AS of_many WHERE {scope} ORDER BY of_many.{column} {direction}, of_many.{key} DESC LIMIT 1",
+                key = key, table = table, scope = scope, column = column, direction = direction,
+            );
+            q = match parent_key {
+                Some(val) => q.where_raw(&format!(
+                    "{table}.{key} = (SELECT of_many_key.{key} FROM ({pick}) AS of_many_key)",
+                    table = table, key = key, pick = pick(&format!("of_many.{} = ?", self.foreign_key)),
+                ), vec![val]),
+                None => q.where_raw(&format!(
+                    "{table}.{key} = ({pick})",
+                    table = table, key = key, pick = pick(&format!("of_many.{fk} = {table}.{fk}", fk = self.foreign_key, table = table)),
+                ), Vec::new()),
+            };
         }
         q
     }
 }
 
+impl<T, R> QueryBuilder<R> for HasOne<T, R>
+where
+    T: Model + Send + Sync + 'static,
+    R: Model + Send + Sync + 'static,
+{
+    fn into_query(self) -> Query<R> {
+        self.get_query()
+    }
+}
+
 #[async_trait]
 impl<T, R> CreatableRelation<T, R> for HasOne<T, R>
 where
     T: Model + Send + Sync + 'static,
     R: Model + Send + Sync + 'static,
 {
-    async fn create(&self, mut attributes: HashMap<String, serde_json::Value>) -> Result<R, sqlx::Error> {
+    async fn create(&self, attributes: HashMap<String, serde_json::Value>) -> Result<R, sqlx::Error> {
         // Set the foreign key to the parent's primary key value
         // attributes.insert(self.foreign_key.clone(), parent_key_value);
         R::create(attributes).await
     }
 
+    // Saves the model pointing at the parent, e.g. `post.comments().save(&comment)`
     async fn save(&self, model: &R) -> Result<(), sqlx::Error> {
-        // This would save the model with the correct foreign key
-        Ok(())
+        save_related(&self.parent, model, |key| HashMap::from([(self.foreign_key.clone(), key)])).await
     }
 }
@@ -4,9 +4,14 @@ pub mod has_one;
 pub mod belongs_to_many;
 pub mod has_morph_one;
 pub mod has_morph_many;
+pub mod morph_to;
+pub mod pivot;
 
 use async_trait::async_trait;
-use crate::orm::model::Model;
+use std::collections::HashMap;
+use crate::orm::diagnostics;
+use crate::orm::error::EloquentError;
+use crate::orm::model::{field_names, Model};
 use crate::orm::query::Query;
 
 // Base relationship trait
@@ -19,6 +24,30 @@ where
     async fn get(&self) -> Result<Vec<R>, sqlx::Error>;
     async fn first(&self) -> Result<Option<R>, sqlx::Error>;
     fn get_query(&self) -> Query<R>;
+    // The model the relation was taken from; the child for belongs_to
+    fn parent(&self) -> &T;
+
+    // Primary keys of the related rows, e.g. the ids attached through a pivot, without
+    // hydrating the related models
+    async fn all_related_ids(&self) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+        self.get_query().pluck(&format!("{}.{}", R::qualified_table_name(), R::primary_key())).await
+    }
+
+    async fn exists(&self) -> Result<bool, sqlx::Error> {
+        self.get_query().exists().await
+    }
+
+    async fn doesnt_exist(&self) -> Result<bool, sqlx::Error> {
+        self.get_query().doesnt_exist().await
+    }
+
+    // Bump updated_at on the related rows; nothing without a parent key, as for delete
+    async fn touch(&self) -> Result<u64, EloquentError> {
+        if !has_parent_key(self.parent()) {
+            return Ok(0);
+        }
+        self.get_query().touch().await
+    }
 }
 
 // Trait for relationships that can be created/updated
@@ -44,10 +73,171 @@ where
     async fn sync(&self, ids: Vec<i64>) -> Result<(), sqlx::Error>;
 }
 
+// Key metadata for a relation, used where a relation is referenced by name
+// (e.g. where_relation) rather than through a loaded parent instance
+#[derive(Debug, Clone)]
+pub struct RelationDefinition {
+    pub related_table: String,
+    pub related_key: String,  // Column on the related (or pivot) table
+    pub parent_key: String,   // Column on the parent table it matches
+    pub pivot: Option<PivotDefinition>,
+    pub constraints: Vec<(String, serde_json::Value)>, // Extra equality checks, e.g. morph type
+    pub related_deleted_at: Option<String>, // The related model's deleted_at column, if it soft deletes
+    pub related_relations: fn(&str) -> Option<RelationDefinition>, // The related model's relation_definition, for nested eager loads
+}
+
+#[derive(Debug, Clone)]
+pub struct PivotDefinition {
+    pub table: String,
+    pub related_pivot_key: String,
+    pub related_key: String,
+}
+
+impl RelationDefinition {
+    pub fn has_one<T: Model, R: Model>(foreign_key: Option<String>, local_key: Option<String>) -> Self {
+        Self::has_many::<T, R>(foreign_key, local_key)
+    }
+
+    pub fn has_many<T: Model, R: Model>(foreign_key: Option<String>, local_key: Option<String>) -> Self {
+        Self {
+            related_table: R::qualified_table_name(),
+            related_key: foreign_key.unwrap_or_else(default_foreign_key::<T, R>),
+            parent_key: local_key.unwrap_or_else(|| T::primary_key().to_string()),
+            pivot: None,
+            constraints: Vec::new(),
+            related_deleted_at: soft_delete_column::<R>(),
+            related_relations: R::relation_definition,
+        }
+    }
+
+    pub fn belongs_to<T: Model, R: Model>(foreign_key: Option<String>, owner_key: Option<String>) -> Self {
+        Self {
+            related_table: R::qualified_table_name(),
+            related_key: owner_key.unwrap_or_else(|| R::primary_key().to_string()),
+            parent_key: foreign_key.unwrap_or_else(default_foreign_key::<R, T>),
+            pivot: None,
+            constraints: Vec::new(),
+            related_deleted_at: soft_delete_column::<R>(),
+            related_relations: R::relation_definition,
+        }
+    }
+
+    pub fn belongs_to_many<T: Model, R: Model>(
+        table: Option<String>,
+        foreign_pivot_key: Option<String>,
+        related_pivot_key: Option<String>,
+    ) -> Self {
+        let table = table.unwrap_or_else(|| {
+            let mut tables = [T::table_name(), R::table_name()];
+            tables.sort();
+            match T::schema() {
+                Some(schema) => format!("{}.{}", schema, tables.join("_")),
+                None => tables.join("_"),
+            }
+        });
+
+        Self {
+            related_table: R::qualified_table_name(),
+            related_key: foreign_pivot_key.unwrap_or_else(|| format!("{}_id", T::table_name().trim_end_matches('s'))),
+            parent_key: T::primary_key().to_string(),
+            pivot: Some(PivotDefinition {
+                related_pivot_key: related_pivot_key.unwrap_or_else(|| format!("{}_id", R::table_name().trim_end_matches('s'))),
+                related_key: R::primary_key().to_string(),
+                table,
+            }),
+            constraints: Vec::new(),
+            related_deleted_at: soft_delete_column::<R>(),
+            related_relations: R::relation_definition,
+        }
+    }
+
+    pub fn morph_one<T: Model, R: Model>(name: &str) -> Self {
+        Self::morph_many::<T, R>(name)
+    }
+
+    pub fn morph_many<T: Model, R: Model>(name: &str) -> Self {
+        Self {
+            related_table: R::qualified_table_name(),
+            related_key: format!("{}_id", name),
+            parent_key: T::primary_key().to_string(),
+            pivot: None,
+            constraints: vec![(
+                format!("{}_type", name),
+                serde_json::Value::String(T::morph_class().to_string()),
+            )],
+            related_deleted_at: soft_delete_column::<R>(),
+            related_relations: R::relation_definition,
+        }
+    }
+
+    // The table holding `related_key` (the pivot table for many-to-many)
+    pub fn key_table(&self) -> &str {
+        match &self.pivot {
+            Some(pivot) => &pivot.table,
+            None => &self.related_table,
+        }
+    }
+}
+
+// Whether the parent has a key to narrow a relation's query with; without one get_query
+// isn't constrained to the parent, so bulk updates and deletes must not run
+pub(crate) fn has_parent_key<T: Model>(parent: &T) -> bool {
+    parent.get_key_value().is_some_and(|key| !key.is_null())
+}
+
+// A relation's save: a copy of model with keys (those pointing at the parent) filled in is
+// saved, then the owners it lists in touches() are touched. Fails with MissingKey when the
+// parent has no key yet, since the copy couldn't point at it
+pub(crate) async fn save_related<T: Model, R: Model>(
+    parent: &T,
+    model: &R,
+    keys: impl FnOnce(serde_json::Value) -> HashMap<String, serde_json::Value>,
+) -> Result<(), sqlx::Error> {
+    let Some(key) = parent.get_key_value().filter(|key| !key.is_null()) else {
+        return Err(EloquentError::MissingKey(T::qualified_table_name()).into_sqlx());
+    };
+    let mut model = model.clone();
+    model.force_fill(keys(key)).map_err(EloquentError::into_sqlx)?;
+    model.save().await?;
+    model.touch_owners().await.map_err(EloquentError::into_sqlx)
+}
+
+fn soft_delete_column<M: Model>() -> Option<String> {
+    M::soft_deletes().then(|| M::deleted_at_column().to_string())
+}
+
+// Foreign key on Holder's table pointing at Owner. The conventional `{owner}_id` is used
+// unless Holder's struct lacks that field and has `{owner}_{primary key}` or `{owner table}_id`.
+// When no field matches, `{owner}_id` is still assumed and a warning goes to the query log
+pub(crate) fn default_foreign_key<Owner: Model, Holder: Model>() -> String {
+    let singular = Owner::table_name().trim_end_matches('s');
+    let candidates = [
+        format!("{}_id", singular),
+        format!("{}_{}", singular, Owner::primary_key()),
+        format!("{}_id", Owner::table_name()),
+    ];
+
+    let Some(fields) = field_names::<Holder>() else {
+        return candidates[0].clone();
+    };
+    match candidates.iter().find(|candidate| fields.contains(&candidate.as_str())) {
+        Some(candidate) => candidate.clone(),
+        None => {
+            diagnostics::log_warning(&format!(
+                "no field on [{}] matches a foreign key for [{}]; assuming {} - pass the key explicitly",
+                Holder::table_name(), Owner::table_name(), candidates[0],
+            ));
+            candidates[0].clone()
+        }
+    }
+}
+
 pub use belongs_to::BelongsTo;
 pub use has_many::HasMany;
 pub use has_one::HasOne;
 pub use belongs_to_many::BelongsToMany;
 pub use has_morph_one::HasMorphOne;
 pub use has_morph_many::HasMorphMany;
+pub use morph_to::{morph_map, morph_table, MorphTo};
+pub use pivot::{Pivot, Pivoted};
 
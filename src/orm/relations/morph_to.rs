@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use serde_json::Value;
+use crate::orm::dynamic::DynamicModel;
+use crate::orm::model::{Attributable, Model};
+use crate::orm::query::Query;
+
+// Morph type -> table, for models whose morph_class isn't their table name
+static MORPH_MAP: RwLock<Option<HashMap<String, String>>> = RwLock::new(None);
+
+// Register morph types and the tables they live in, e.g. morph_map(vec![("post", "posts")]).
+// Entries are added to (or replace) earlier ones
+pub fn morph_map(entries: Vec<(&str, &str)>) {
+    let mut map = MORPH_MAP.write().unwrap_or_else(|e| e.into_inner());
+    let map = map.get_or_insert_with(HashMap::new);
+    for (morph_type, table) in entries {
+        map.insert(morph_type.to_string(), table.to_string());
+    }
+}
+
+// The table a stored morph type points at; unmapped types are taken to be table names
+pub fn morph_table(morph_type: &str) -> String {
+    let map = MORPH_MAP.read().unwrap_or_else(|e| e.into_inner());
+    map.as_ref()
+        .and_then(|map| map.get(morph_type))
+        .cloned()
+        .unwrap_or_else(|| morph_type.to_string())
+}
+
+// MorphTo relationship - similar to Laravel's morphTo. The owner's type is only known at
+// runtime, so it is either asked for as a concrete model (get_as) or loaded dynamically
+#[derive(Debug)]
+pub struct MorphTo<T> {
+    child: T,
+    morph_type: String,     // Column that stores the owner's type
+    morph_id: String,       // Column that stores the owner's ID
+}
+
+impl<T> MorphTo<T>
+where
+    T: Model + Send + Sync + 'static,
+{
+    pub fn new(child: T, name: &str, type_column: Option<String>, id_column: Option<String>) -> Self {
+        let morph_type = type_column.unwrap_or_else(|| format!("{}_type", name));
+        let morph_id = id_column.unwrap_or_else(|| format!("{}_id", name));
+
+        Self {
+            child,
+            morph_type,
+            morph_id,
+        }
+    }
+
+    fn child_attribute(&self, column: &str) -> Option<Value> {
+        serde_json::to_value(&self.child).ok()?
+            .get(column)
+            .filter(|value| !value.is_null())
+            .cloned()
+    }
+
+    // The stored type, e.g. "posts"; None when the child has no owner
+    pub fn morph_type(&self) -> Option<String> {
+        self.child_attribute(&self.morph_type)?.as_str().map(str::to_string)
+    }
+
+    // Query for the owner as R. None when the stored type isn't R::morph_class() or the
+    // child has no owner id, so a mismatched type never loads an unrelated row
+    pub fn query_as<R: Model>(&self) -> Option<Query<R>> {
+        if self.morph_type()? != R::morph_class() {
+            return None;
+        }
+        let id = self.child_attribute(&self.morph_id)?;
+        Some(Query::new().where_op(R::primary_key(), "=", id))
+    }
+
+    pub async fn get_as<R: Model>(&self) -> Result<Option<R>, sqlx::Error> {
+        match self.query_as::<R>() {
+            Some(query) => query.first().await,
+            None => Ok(None),
+        }
+    }
+
+    // The stored type and a query on the table it maps to, for when the owner's model
+    // isn't known at compile time
+    pub fn dynamic_query(&self) -> Option<(String, Query<DynamicModel>)> {
+        let morph_type = self.morph_type()?;
+        let id = self.child_attribute(&self.morph_id)?;
+        let query = DynamicModel::query_table(&morph_table(&morph_type))
+            .where_op(DynamicModel::primary_key(), "=", id);
+        Some((morph_type, query))
+    }
+
+    // The owner's stored type and attributes
+    pub async fn get_dynamic(&self) -> Result<Option<(String, HashMap<String, Value>)>, sqlx::Error> {
+        let Some((morph_type, query)) = self.dynamic_query() else {
+            return Ok(None);
+        };
+        Ok(query.first().await?.map(|owner| (morph_type, owner.get_attributes().clone())))
+    }
+}
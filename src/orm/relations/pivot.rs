@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+// Pivot columns are selected under this prefix so they can't collide with the related model's
+pub const PIVOT_PREFIX: &str = "pivot_";
+
+// The pivot table row a related model was loaded through
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Pivot {
+    attributes: HashMap<String, Value>,
+}
+
+impl Pivot {
+    // None when the column wasn't loaded (only the two pivot keys and with_pivot columns are);
+    // Some(Value::Null) when it was loaded but is NULL
+    pub fn get(&self, column: &str) -> Option<&Value> {
+        self.attributes.get(column)
+    }
+
+    pub fn attributes(&self) -> &HashMap<String, Value> {
+        &self.attributes
+    }
+}
+
+// A related model from BelongsToMany::get_with_pivot together with its pivot row
+#[derive(Debug, Clone)]
+pub struct Pivoted<R> {
+    model: R,
+    pivot: Pivot,
+}
+
+impl<R> Pivoted<R> {
+    pub fn model(&self) -> &R {
+        &self.model
+    }
+
+    pub fn pivot(&self) -> &Pivot {
+        &self.pivot
+    }
+
+    pub fn into_inner(self) -> R {
+        self.model
+    }
+}
+
+impl<R> std::ops::Deref for Pivoted<R> {
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        &self.model
+    }
+}
+
+// A row is split on PIVOT_PREFIX: prefixed columns go to the pivot, the rest to the model
+impl<'de, R> Deserialize<'de> for Pivoted<R>
+where
+    R: for<'a> Deserialize<'a>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let row = serde_json::Map::<String, Value>::deserialize(deserializer)?;
+        let (pivot, fields): (Vec<_>, Vec<_>) = row.into_iter().partition(|(key, _)| key.starts_with(PIVOT_PREFIX));
+
+        let model = serde_json::from_value(Value::Object(fields.into_iter().collect()))
+            .map_err(serde::de::Error::custom)?;
+        let attributes = pivot
+            .into_iter()
+            .map(|(key, value)| (key[PIVOT_PREFIX.len()..].to_string(), value))
+            .collect();
+        Ok(Self { model, pivot: Pivot { attributes } })
+    }
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn increment_renders_per_driver() {
+    let query = Post::query().where_op("user_id", "=", json!(1));
+    let sql = [Driver::MySql, Driver::Postgres, Driver::Sqlite].map(|driver| query.clone().driver(driver).to_increment_sql("user_id", 2));
+    assert_eq!(sql, [
+        "UPDATE posts SET `user_id` = `user_id` + 2, updated_at = CURRENT_TIMESTAMP WHERE `user_id` = 1",
+        "UPDATE posts SET \"user_id\" = \"user_id\" + 2, updated_at = CURRENT_TIMESTAMP WHERE \"user_id\" = 1",
+        "UPDATE posts SET \"user_id\" = \"user_id\" + 2, updated_at = CURRENT_TIMESTAMP WHERE \"user_id\" = 1",
+    ]);
+}
+
+#[tokio::test]
+async fn query_mutations_return_affected_counts() {
+    let connection = seeded().await;
+    let posts = || Post::query().on_connection(connection.clone());
+
+    let rows = ["x", "y", "z"].map(|title| attributes(&[("user_id", json!(3)), ("title", json!(title))]));
+    assert_eq!(posts().insert(rows.to_vec()).await.unwrap(), 3);
+    assert_eq!(posts().where_op("user_id", "=", json!(1)).increment("user_id", 10).await.unwrap(), 2);
+    assert_eq!(posts().where_op("user_id", "=", json!(11)).decrement("user_id", 10).await.unwrap(), 2);
+    assert_eq!(posts().where_op("user_id", "=", json!(9)).update(attributes(&[("title", json!("none"))])).await.unwrap(), 0);
+    assert_eq!(posts().where_op("user_id", "=", json!(3)).delete().await.unwrap(), 3);
+    assert_eq!(posts().count().await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn relation_mutations_return_affected_counts() {
+    with_registered("affected_counts", |_| async {
+        let ada = User { id: Some(1), name: "ada".into() };
+        assert_eq!(ada.has_many::<Post>(None, None).update(attributes(&[("title", json!("renamed"))])).await.unwrap(), 2);
+        assert_eq!(ada.has_many::<Post>(None, None).delete().await.unwrap(), 2);
+
+        // Without a key the relation isn't narrowed to the parent, so nothing is touched
+        let unsaved = User { id: None, name: "new".into() };
+        assert_eq!(unsaved.has_many::<Post>(None, None).delete().await.unwrap(), 0);
+        assert_eq!(unsaved.has_one::<Post>(None, None).update(attributes(&[("title", json!("x"))])).await.unwrap(), 0);
+        assert_eq!(Post::query().count().await.unwrap(), 1);
+    })
+    .await;
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use common::*;
+use RustEloquent::orm::Tracked;
+
+#[test]
+fn count_distinct_quotes_the_column_per_driver() {
+    let query = Post::query();
+    let sql = [Driver::MySql, Driver::Postgres, Driver::Sqlite]
+        .map(|driver| query.clone().driver(driver).to_count_distinct_sql("user_id"));
+    assert_eq!(sql, [
+        "SELECT COUNT(DISTINCT `user_id`) AS aggregate FROM posts",
+        "SELECT COUNT(DISTINCT \"user_id\") AS aggregate FROM posts",
+        "SELECT COUNT(DISTINCT \"user_id\") AS aggregate FROM posts",
+    ]);
+}
+
+#[tokio::test]
+async fn counts_run_on_sqlite() {
+    let connection = seeded().await;
+    let posts = || Post::query().on_connection(connection.clone());
+
+    assert_eq!(posts().count().await.unwrap(), 3);
+    assert_eq!(posts().where_op("user_id", "=", json!(9)).count().await.unwrap(), 0);
+    assert_eq!(posts().count_distinct("user_id").await.unwrap(), 2);
+    assert_eq!(posts().scalar::<i64>("MAX(id)").await.unwrap(), Some(3));
+    assert_eq!(posts().where_op("user_id", "=", json!(9)).scalar::<i64>("MAX(id)").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn load_count_counts_each_models_relation_on_sqlite() {
+    let connection = seeded().await;
+    let mut users: Vec<Tracked<User>> = User::query().on_connection(connection.clone()).order_by("id", "asc")
+        .get().await.unwrap()
+        .into_iter().map(|user| Tracked::new(user).unwrap()).collect();
+
+    User::query().on_connection(connection).load_count(&mut users, vec!["posts"]).await.unwrap();
+    assert_eq!(users.iter().map(|user| user.loaded_count("posts")).collect::<Vec<_>>(), [Some(2), Some(1), Some(0)]);
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use common::*;
+use RustEloquent::orm::query::{raw, Aggregate, NullsOrder};
+
+type Builder = fn(Query<Post>) -> Query<Post>;
+
+// One call per builder method, with `?` inside string values to check they aren't taken
+// as placeholders
+const BUILDERS: &[(&str, Builder)] = &[
+    ("where_clause", |q| q.where_clause("title", "why?")),
+    ("where_op", |q| q.where_op("title", "=", json!("why?"))),
+    ("where_op raw", |q| q.where_op(raw("LOWER(title)"), "=", json!("why?"))),
+    ("where_expr", |q| q.where_expr("user_id", "<", raw("2 + 1"))),
+    ("where_in", |q| q.where_in("id", vec![json!(1), json!(2)])),
+    ("where_not_in", |q| q.where_not_in("id", vec![json!(1)])),
+    ("where_in_columns", |q| q.where_in_columns(vec!["id", "user_id"], vec![vec![json!(1), json!(1)]])),
+    ("where_null", |q| q.where_null("deleted_at")),
+    ("where_not_null", |q| q.where_not_null("title")),
+    ("where_many", |q| q.where_many(vec![("user_id", json!(1)), ("title", json!("first"))])),
+    ("where_null_many", |q| q.where_null_many(vec!["deleted_at", "updated_at"])),
+    ("where_not_null_many", |q| q.where_not_null_many(vec!["title", "user_id"])),
+    ("where_op_typed", |q| q.where_op_typed("title", "=", json!("why?"), "text")),
+    ("where_json_length", |q| q.where_json_length("title", "tags", ">", json!(1))),
+    ("where_json_contains_key", |q| q.where_json_contains_key("title", "tags")),
+    ("or_where", |q| q.where_op("id", "=", json!(1)).or_where("title", "=", json!("why?"))),
+    ("where_between", |q| q.where_between("id", json!(1), json!(2))),
+    ("where_not_between", |q| q.where_not_between("id", json!(1), json!(2))),
+    ("or_where_between", |q| q.where_op("id", "=", json!(3)).or_where_between("id", json!(1), json!(2))),
+    ("or_where_not_between", |q| q.where_op("id", "=", json!(3)).or_where_not_between("id", json!(1), json!(2))),
+    ("or_where_in", |q| q.where_op("id", "=", json!(3)).or_where_in("id", vec![json!(1)])),
+    ("or_where_not_in", |q| q.where_op("id", "=", json!(3)).or_where_not_in("id", vec![json!(1)])),
+    ("or_where_null", |q| q.where_op("id", "=", json!(3)).or_where_null("deleted_at")),
+    ("or_where_not_null", |q| q.where_op("id", "=", json!(3)).or_where_not_null("title")),
+    ("where_like", |q| q.where_like("title", "%?%")),
+    ("where_not_like", |q| q.where_not_like("title", "%?%")),
+    ("or_where_like", |q| q.where_op("id", "=", json!(3)).or_where_like("title", "f%")),
+    ("or_where_not_like", |q| q.where_op("id", "=", json!(3)).or_where_not_like("title", "f%")),
+    ("where_ci", |q| q.where_ci("title", "=", json!("FIRST"))),
+    ("or_where_ci", |q| q.where_op("id", "=", json!(3)).or_where_ci("title", "=", json!("FIRST"))),
+    ("where_future", |q| q.where_future("created_at")),
+    ("where_past", |q| q.where_past("created_at")),
+    ("where_today", |q| q.where_today("created_at")),
+    ("where_date", |q| q.where_date("created_at", json!("2024-01-01"))),
+    ("where_date_op", |q| q.where_date_op("created_at", ">", json!("2024-01-01"))),
+    ("where_time", |q| q.where_time("created_at", ">", "10:00:00")),
+    ("where_year", |q| q.where_year("created_at", json!(2024))),
+    ("where_year_op", |q| q.where_year_op("created_at", ">", json!(2024))),
+    ("where_month", |q| q.where_month("created_at", json!(1))),
+    ("where_month_op", |q| q.where_month_op("created_at", ">", json!(1))),
+    ("where_day", |q| q.where_day("created_at", json!(1))),
+    ("where_day_op", |q| q.where_day_op("created_at", ">", json!(1))),
+    ("where_raw", |q| q.where_raw("title = ? OR user_id = ?", vec![json!("why?"), json!(2)])),
+    ("where_relation", |q| q.where_relation("user", "name", "=", json!("ada"))),
+    ("or_where_relation", |q| q.where_op("id", "=", json!(3)).or_where_relation("user", "name", "=", json!("ada"))),
+    ("where_has", |q| q.where_has("comments", |c| c.where_op("body", "=", json!("nice?")))),
+    ("where_doesnt_have", |q| q.where_doesnt_have("comments", |c| c)),
+    ("or_where_has", |q| q.where_op("id", "=", json!(3)).or_where_has("comments", |c| c)),
+    ("or_where_doesnt_have", |q| q.where_op("id", "=", json!(3)).or_where_doesnt_have("comments", |c| c)),
+    ("has", |q| q.has("comments", ">=", 2)),
+    ("where_belongs_to", |q| q.where_belongs_to(&User { id: Some(1), name: "ada".into() })),
+    ("select", |q| q.select(vec!["id", "title"])),
+    ("select_raw", |q| q.select_raw("COUNT(*) AS posts")),
+    ("select_raw_bound", |q| q.select_raw_bound("title = ? AS matches", vec![json!("why?")])),
+    ("set_select_raw", |q| q.set_select_raw("id + ? AS next", vec![json!(1)])),
+    ("select_window", |q| q.select_window("rn", "ROW_NUMBER()", vec!["user_id"], vec![("id", "DESC")])),
+    ("select_case", |q| q.select_case("label", vec![("user_id = 1", json!("ada?"))], json!("other"))),
+    ("distinct", |q| q.select(vec!["user_id"]).distinct()),
+    ("join", |q| q.join("users", "users.id", "=", "posts.user_id")),
+    ("left_join", |q| q.left_join("users", "users.id", "=", "posts.user_id")),
+    ("right_join", |q| q.right_join("users", "users.id", "=", "posts.user_id")),
+    ("order_by", |q| q.order_by("title", "desc")),
+    ("order_by raw", |q| q.order_by(raw("LENGTH(title)"), "asc")),
+    ("order_by_nulls", |q| q.order_by_nulls("title", "asc", NullsOrder::Last)),
+    ("order_by_raw", |q| q.order_by_raw("LENGTH(title) DESC")),
+    ("order_by_raw_bound", |q| q.order_by_raw_bound("title = ? DESC", vec![json!("why?")])),
+    ("in_random_order", |q| q.in_random_order()),
+    ("latest", |q| q.latest(None)),
+    ("oldest", |q| q.oldest(Some("id"))),
+    ("reorder", |q| q.order_by("id", "asc").reorder()),
+    ("limit offset", |q| q.limit(2).offset(1)),
+    ("for_page", |q| q.for_page(2, 1)),
+    ("group_by having", |q| q.select(vec!["user_id"]).group_by(vec!["user_id"]).having("user_id", ">", json!(0))),
+    ("having_count", |q| q.select(vec!["user_id"]).group_by(vec!["user_id"]).having_count(">", 1)),
+    ("having_aggregate", |q| q.select(vec!["user_id"]).group_by(vec!["user_id"]).having_aggregate(Aggregate::Max, "id", ">", json!(1))),
+    ("having_in", |q| q.select(vec!["user_id"]).group_by(vec!["user_id"]).having_in("user_id", vec![json!(1)])),
+    ("having_between", |q| q.select(vec!["user_id"]).group_by(vec!["user_id"]).having_between("user_id", json!(1), json!(2))),
+    ("having_raw", |q| q.select(vec!["user_id"]).group_by(vec!["user_id"]).having_raw("COUNT(*) > ?", vec![json!(1)])),
+    ("with_count", |q| q.with_count("comments")),
+    ("with_aggregate", |q| q.with_aggregate("comments", "id", Aggregate::Max)),
+    ("union", |q| q.where_op("id", "=", json!(1)).union(Post::query().where_op("title", "=", json!("why?")))),
+    ("union_all", |q| q.where_op("id", "=", json!(1)).union_all(Post::query().where_op("id", "=", json!(2)))),
+];
+
+// sql with quoted strings and identifiers removed, so any `?` left is a placeholder
+fn unquoted(sql: &str) -> String {
+    let mut quote = None;
+    sql.chars()
+        .filter(|&ch| match quote {
+            Some(open) => {
+                if ch == open {
+                    quote = None;
+                }
+                false
+            }
+            None if matches!(ch, '\'' | '"' | '`') => {
+                quote = Some(ch);
+                false
+            }
+            None => true,
+        })
+        .collect()
+}
+
+#[test]
+fn every_builder_method_leaves_no_placeholder_unbound() {
+    for (name, build) in BUILDERS {
+        let query = build(Post::query());
+        assert_eq!(query.validate_bindings(), Ok(()), "{}", name);
+        for sql in sql_per_driver(&query) {
+            assert!(!unquoted(&sql).contains('?'), "{} left a placeholder in {}", name, sql);
+        }
+    }
+}
+
+// Also checks the SQL is accepted by SQLite, except where it renders a dialect SQLite lacks
+#[tokio::test]
+async fn every_builder_method_runs_on_sqlite() {
+    let connection = seeded().await;
+    for (name, build) in BUILDERS {
+        if ["where_op_typed", "where_json_length", "where_json_contains_key"].contains(name) {
+            continue;
+        }
+        let query = build(Post::query()).on_connection(connection.clone());
+        let sql = query.clone().driver(Driver::Sqlite).to_sql();
+        let rows: Result<Vec<std::collections::HashMap<String, Value>>, _> = query.driver(Driver::Sqlite).get_as().await;
+        assert!(rows.is_ok(), "{} failed: {} ({:?})", name, sql, rows.err());
+    }
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "placeholder")]
+fn debug_builds_panic_on_a_mismatched_fragment() {
+    let _ = Post::query().where_raw("title = ? AND user_id = ?", vec![json!("why?")]);
+}
@@ -0,0 +1,136 @@
+#![allow(dead_code, unused_imports)]
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use RustEloquent::db::connection::{ConnectionManager, DatabaseConnection, SqliteConnection};
+
+pub use RustEloquent::db::connection::Driver;
+pub use RustEloquent::orm::query::Query;
+pub use RustEloquent::orm::{Eloquent, Model, RelationDefinition};
+pub use serde::{Deserialize, Serialize};
+pub use serde_json::{json, Value};
+
+// Implements Model and Eloquent for a test struct. Reads and creates go through the query
+// builder, so they run on whatever connection the model's queries resolve to
+#[macro_export]
+macro_rules! model {
+    ($t:ty, $table:expr, $relations:expr $(, $extra:item)*) => {
+        #[async_trait::async_trait]
+        impl $crate::common::Model for $t {
+            fn table_name() -> &'static str { $table }
+            fn fillable() -> Vec<&'static str> { vec!["name", "title", "user_id", "post_id", "body", "email"] }
+            fn relation_definition(name: &str) -> Option<$crate::common::RelationDefinition> { ($relations)(name) }
+            fn get_key_value(&self) -> Option<serde_json::Value> { self.id.map(serde_json::Value::from) }
+            async fn find(id: i64) -> Result<Option<Self>, sqlx::Error> {
+                <Self as $crate::common::Eloquent>::query().where_op("id", "=", serde_json::Value::from(id)).first().await
+            }
+            async fn all() -> Result<Vec<Self>, sqlx::Error> {
+                <Self as $crate::common::Eloquent>::query().get().await
+            }
+            async fn create(attributes: std::collections::HashMap<String, serde_json::Value>) -> Result<Self, sqlx::Error> {
+                <Self as $crate::common::Eloquent>::query().create_many(vec![attributes]).await?
+                    .pop()
+                    .ok_or(sqlx::Error::RowNotFound)
+            }
+            async fn save(&mut self) -> Result<(), sqlx::Error> { Ok(()) }
+            async fn delete(&self) -> Result<(), sqlx::Error> { Ok(()) }
+            async fn update(&mut self, _attributes: std::collections::HashMap<String, serde_json::Value>) -> Result<(), sqlx::Error> { Ok(()) }
+            $($extra)*
+        }
+        impl $crate::common::Eloquent for $t {}
+    };
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct User {
+    pub id: Option<i64>,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct Post {
+    pub id: Option<i64>,
+    pub user_id: i64,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct Comment {
+    pub id: Option<i64>,
+    pub post_id: i64,
+    pub body: String,
+}
+
+model!(User, "users", |name: &str| match name {
+    "posts" => Some(RelationDefinition::has_many::<User, Post>(None, None)),
+    _ => None,
+}, fn connection() -> &'static str { scoped_connection() });
+model!(Post, "posts", |name: &str| match name {
+    "user" => Some(RelationDefinition::belongs_to::<Post, User>(None, None)),
+    "comments" => Some(RelationDefinition::has_many::<Post, Comment>(None, None)),
+    _ => None,
+}, fn connection() -> &'static str { scoped_connection() });
+model!(Comment, "comments", |name: &str| match name {
+    "post" => Some(RelationDefinition::belongs_to::<Comment, Post>(None, None)),
+    _ => None,
+}, fn connection() -> &'static str { scoped_connection() });
+
+tokio::task_local! {
+    static CONNECTION: &'static str;
+}
+
+// Connection name for User, Post and Comment: the one set by with_registered, so tests
+// running in parallel each get their own database
+pub fn scoped_connection() -> &'static str {
+    CONNECTION.try_with(|name| *name).unwrap_or("default")
+}
+
+pub const SCHEMA: &[&str] = &[
+    "CREATE TABLE users (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL, email TEXT, \
+     created_at TEXT, updated_at TEXT, deleted_at TEXT)",
+    "CREATE TABLE posts (id INTEGER PRIMARY KEY AUTOINCREMENT, user_id INTEGER NOT NULL, title TEXT NOT NULL, \
+     created_at TEXT, updated_at TEXT, deleted_at TEXT)",
+    "CREATE TABLE comments (id INTEGER PRIMARY KEY AUTOINCREMENT, post_id INTEGER NOT NULL, body TEXT NOT NULL, \
+     created_at TEXT, updated_at TEXT, deleted_at TEXT)",
+];
+
+// A fresh in-memory SQLite database with SCHEMA and the given statements applied
+pub async fn sqlite(statements: &[&str]) -> Arc<dyn DatabaseConnection> {
+    let connection: Arc<dyn DatabaseConnection> =
+        Arc::new(SqliteConnection::new("sqlite::memory:").await.expect("in-memory SQLite"));
+    for sql in SCHEMA.iter().chain(statements) {
+        connection.execute(sql).await.expect(sql);
+    }
+    connection
+}
+
+// Users 1 "ada", 2 "bob" and 3 "cy"; ada has posts 1 and 2, bob has post 3; post 1 has two comments
+pub const SEED: &[&str] = &[
+    "INSERT INTO users (id, name) VALUES (1, 'ada'), (2, 'bob'), (3, 'cy')",
+    "INSERT INTO posts (id, user_id, title) VALUES (1, 1, 'first'), (2, 1, 'second'), (3, 2, 'third')",
+    "INSERT INTO comments (id, post_id, body) VALUES (1, 1, 'nice'), (2, 1, 'great')",
+];
+
+pub async fn seeded() -> Arc<dyn DatabaseConnection> {
+    sqlite(SEED).await
+}
+
+// Registers a seeded in-memory database as name and runs test with the unbound queries of
+// User, Post and Comment resolving to it, for APIs that don't take a bound connection
+pub async fn with_registered<F: Future>(name: &'static str, test: impl FnOnce(Arc<dyn DatabaseConnection>) -> F) -> F::Output {
+    let connection = ConnectionManager::new().connect(name, "sqlite::memory:").await.expect("in-memory SQLite");
+    for sql in SCHEMA.iter().chain(SEED) {
+        connection.execute(sql).await.expect(sql);
+    }
+    CONNECTION.scope(name, test(connection)).await
+}
+
+pub fn attributes(pairs: &[(&str, Value)]) -> HashMap<String, Value> {
+    pairs.iter().map(|(key, value)| (key.to_string(), value.clone())).collect()
+}
+
+// The query rendered for each driver, in MySQL, Postgres, SQLite order
+pub fn sql_per_driver<T: Model>(query: &Query<T>) -> [String; 3] {
+    [Driver::MySql, Driver::Postgres, Driver::Sqlite].map(|driver| query.clone().driver(driver).to_sql())
+}
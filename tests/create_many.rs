@@ -0,0 +1,33 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn rows_render_as_one_insert_with_timestamps() {
+    let rows = [attributes(&[("name", json!("dee"))]), attributes(&[("name", json!("eve"))])];
+    let sql = [Driver::MySql, Driver::Postgres].map(|driver| User::query().driver(driver).to_create_many_sql(&rows));
+    let expected = "INSERT INTO users (created_at, name, updated_at) VALUES (NOW(), 'dee', NOW()), (NOW(), 'eve', NOW())";
+    assert_eq!(sql, [expected, expected]);
+}
+
+#[tokio::test]
+async fn created_rows_come_back_with_their_keys_on_sqlite() {
+    let rows = || vec![attributes(&[("name", json!("dee"))]), attributes(&[("name", json!("eve"))])];
+
+    let bound = User::query().on_connection(seeded().await).create_many(rows()).await.unwrap();
+    assert_eq!(bound.iter().map(|user| (user.id, user.name.as_str())).collect::<Vec<_>>(), [(Some(4), "dee"), (Some(5), "eve")]);
+
+    with_registered("create_many", |_| async {
+        let unbound = User::create_many(rows()).await.unwrap();
+        assert_eq!(unbound.iter().map(|user| user.id).collect::<Vec<_>>(), [Some(4), Some(5)]);
+        assert_eq!(User::query().count().await.unwrap(), 5);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn rows_with_different_columns_are_rejected() {
+    let rows = vec![attributes(&[("name", json!("dee"))]), attributes(&[("name", json!("eve")), ("email", json!("e@x"))])];
+    let error = User::query().on_connection(seeded().await).create_many(rows).await.unwrap_err();
+    assert!(error.to_string().contains("row 1 has columns [email, name] but row 0 has [name]"), "{}", error);
+}
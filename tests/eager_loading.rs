@@ -0,0 +1,80 @@
+mod common;
+
+use common::*;
+use RustEloquent::orm::EloquentError;
+
+#[test]
+fn eager_loads_select_every_parents_rows_in_one_query() {
+    let keys = [json!(1), json!(2)];
+    let sql = [Driver::MySql, Driver::Postgres, Driver::Sqlite]
+        .map(|driver| User::query().driver(driver).to_eager_load_sql("posts", &keys));
+    assert_eq!(sql, [
+        "SELECT posts.*, CAST(posts.user_id AS CHAR) AS eager_parent_key FROM posts WHERE posts.user_id IN (1, 2)",
+        "SELECT posts.*, CAST(posts.user_id AS TEXT) AS eager_parent_key FROM posts WHERE posts.user_id IN (1, 2)",
+        "SELECT posts.*, CAST(posts.user_id AS TEXT) AS eager_parent_key FROM posts WHERE posts.user_id IN (1, 2)",
+    ]);
+}
+
+#[tokio::test]
+async fn get_loaded_attaches_related_rows_to_each_model() {
+    let connection = seeded().await;
+
+    let users = User::query().on_connection(connection.clone()).with(vec!["posts"]).order_by("id", "asc").get_loaded().await.unwrap();
+    let titles: Vec<(&str, Vec<String>)> = users.iter()
+        .map(|user| (user.model().name.as_str(), user.related::<Post>("posts").unwrap().into_iter().map(|post| post.title).collect()))
+        .collect();
+    assert_eq!(titles, [
+        ("ada", vec!["first".to_string(), "second".to_string()]),
+        ("bob", vec!["third".to_string()]),
+        ("cy", vec![]),
+    ]);
+    assert!(users[2].relation_loaded("posts"));
+    assert!(!users[2].relation_loaded("comments"));
+
+    let posts = Post::query().on_connection(connection).with(vec!["user", "comments"]).where_op("id", "<", json!(3)).get_loaded().await.unwrap();
+    let authors: Vec<Vec<User>> = posts.iter().map(|post| post.related::<User>("user").unwrap()).collect();
+    assert_eq!(authors, [vec![User { id: Some(1), name: "ada".into() }], vec![User { id: Some(1), name: "ada".into() }]]);
+    assert_eq!(posts.iter().map(|post| post.related::<Comment>("comments").unwrap().len()).collect::<Vec<_>>(), [2, 0]);
+}
+
+#[tokio::test]
+async fn get_loaded_loads_nested_relations() {
+    let connection = seeded().await;
+
+    let users = User::query().on_connection(connection.clone()).with(vec!["posts.comments", "posts.user"]).order_by("id", "asc").get_loaded().await.unwrap();
+    let comments: Vec<Vec<(String, usize)>> = users.iter()
+        .map(|user| user.related_loaded::<Post>("posts").unwrap().iter()
+            .map(|post| (post.title.clone(), post.related::<Comment>("comments").unwrap().len()))
+            .collect())
+        .collect();
+    assert_eq!(comments, [
+        vec![("first".to_string(), 2), ("second".to_string(), 0)],
+        vec![("third".to_string(), 0)],
+        vec![],
+    ]);
+    let bob = users[1].related_loaded::<Post>("posts").unwrap();
+    assert_eq!(bob[0].related::<User>("user").unwrap(), [User { id: Some(2), name: "bob".into() }]);
+
+    let comments = Comment::query().on_connection(connection).with(vec!["post.user"]).get_loaded().await.unwrap();
+    let authors: Vec<Vec<User>> = comments.iter()
+        .flat_map(|comment| comment.related_loaded::<Post>("post").unwrap())
+        .map(|post| post.related::<User>("user").unwrap())
+        .collect();
+    assert_eq!(authors, [vec![User { id: Some(1), name: "ada".into() }], vec![User { id: Some(1), name: "ada".into() }]]);
+}
+
+#[tokio::test]
+async fn get_loaded_reports_an_undefined_nested_relation() {
+    let connection = seeded().await;
+
+    let error = User::query().on_connection(connection).with(vec!["posts.tags"]).get_loaded().await.unwrap_err();
+    assert!(matches!(EloquentError::from(error), EloquentError::Configuration(message) if message.contains("[tags] on model [posts]")));
+}
+
+#[tokio::test]
+async fn get_loaded_reports_eager_loads_nested_too_deep() {
+    let connection = seeded().await;
+
+    let error = User::query().on_connection(connection).with(vec!["posts.user.posts.user.posts.user"]).get_loaded().await.unwrap_err();
+    assert!(matches!(EloquentError::from(error), EloquentError::Configuration(message) if message.contains("deeper than 5")));
+}
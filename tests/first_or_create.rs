@@ -0,0 +1,89 @@
+mod common;
+
+use common::*;
+use std::collections::HashMap;
+use RustEloquent::db::connection::ConnectionManager;
+
+#[test]
+fn postgres_inserts_with_on_conflict_do_nothing() {
+    let attributes = attributes(&[("name", json!("dee"))]);
+    let sql = User::query().driver(Driver::Postgres).to_create_or_ignore_sql(&attributes);
+    assert!(sql.starts_with("INSERT INTO users ("), "{}", sql);
+    assert!(sql.ends_with(" ON CONFLICT DO NOTHING RETURNING *"), "{}", sql);
+}
+
+#[tokio::test]
+async fn sqlite_selects_then_creates() {
+    with_registered("first_or_create", |connection| async move {
+        let (existing, created) = User::first_or_create_reporting(attributes(&[("name", json!("bob"))])).await.unwrap();
+        assert_eq!((existing.id, created), (Some(2), false));
+
+        let (dee, created) = User::first_or_create_reporting(attributes(&[("name", json!("dee"))])).await.unwrap();
+        assert_eq!((dee.id, created), (Some(4), true));
+        let again = User::first_or_create(attributes(&[("name", json!("dee"))])).await.unwrap();
+        assert_eq!(again.id, Some(4));
+        assert_eq!(User::query().on_connection(connection).count().await.unwrap(), 4);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn first_or_create_with_only_uses_values_when_creating() {
+    with_registered("first_or_create_with", |_| async move {
+        let values = || attributes(&[("email", json!("new@x")), ("id", json!(50))]);
+        let bob = User::first_or_create_with(attributes(&[("name", json!("bob"))]), values).await.unwrap();
+        assert_eq!(bob.id, Some(2));
+
+        // id isn't fillable, so the row gets the next id, with the email from values
+        let dee = User::first_or_create_with(attributes(&[("name", json!("dee"))]), values).await.unwrap();
+        assert_eq!(dee, User { id: Some(4), name: "dee".into() });
+        let email = User::query().where_op("id", "=", json!(4)).pluck("email").await.unwrap();
+        assert_eq!(email, vec![json!("new@x")]);
+    })
+    .await;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+struct Tag {
+    id: Option<i64>,
+    name: String,
+}
+
+model!(Tag, "first_or_create_tags", |_: &str| None,
+    fn connection() -> &'static str { "first_or_create_postgres" },
+    fn timestamps() -> bool { false });
+
+// Runs against the database in POSTGRES_URL, and is skipped when that isn't set
+#[tokio::test]
+async fn postgres_upserts_and_keeps_the_transaction_usable() {
+    let Ok(url) = std::env::var("POSTGRES_URL") else {
+        return;
+    };
+    let connection = ConnectionManager::new().connect("first_or_create_postgres", &url).await.unwrap();
+    connection.execute("DROP TABLE IF EXISTS first_or_create_tags").await.unwrap();
+    connection.execute("CREATE TABLE first_or_create_tags (id BIGSERIAL PRIMARY KEY, name TEXT NOT NULL UNIQUE)").await.unwrap();
+
+    let (rust, created) = Tag::first_or_create_reporting(attributes(&[("name", json!("rust"))])).await.unwrap();
+    assert!(created);
+    RustEloquent::transaction_on("first_or_create_postgres", |_| async move {
+        let (found, created) = Tag::first_or_create_reporting(attributes(&[("name", json!("rust"))])).await?;
+        assert_eq!((found.id, created), (rust.id, false));
+        // A conflicting insert leaves the transaction usable for the select that follows
+        assert_eq!(Tag::query().create_or_ignore(attributes(&[("name", json!("rust"))])).await?, None);
+        assert_eq!(Tag::query().count().await?, 1);
+
+        // first_or_create_with inserts the same way: id isn't fillable, so the insert of
+        // "rust" conflicts, nothing matches id 99 afterwards, and the transaction survives
+        let error = Tag::first_or_create_with(attributes(&[("id", json!(99)), ("name", json!("rust"))]), HashMap::new)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, sqlx::Error::RowNotFound), "{:?}", error);
+        let (created, _) = Tag::first_or_create_reporting(attributes(&[("name", json!("go"))])).await?;
+        assert_eq!(Tag::first_or_create_with(attributes(&[("name", json!("go"))]), HashMap::new).await?.id, created.id);
+        Ok(())
+    })
+    .await
+    .unwrap();
+
+    connection.execute("DROP TABLE first_or_create_tags").await.unwrap();
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use common::*;
+use RustEloquent::orm::query::Aggregate;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Author {
+    user_id: i64,
+}
+
+fn posts_per_user() -> Query<Post> {
+    Post::query().select(vec!["user_id"]).group_by(vec!["user_id"]).order_by("user_id", "asc")
+}
+
+async fn authors(query: Query<Post>) -> Vec<i64> {
+    let rows: Vec<Author> = query.on_connection(seeded().await).get_as().await.unwrap();
+    rows.into_iter().map(|row| row.user_id).collect()
+}
+
+#[tokio::test]
+async fn having_between_in_and_raw_render_per_driver_and_run_on_sqlite() {
+    let query = posts_per_user()
+        .having_between("COUNT(*)", json!(1), json!(2))
+        .having_in("user_id", vec![json!(1), json!(2)])
+        .having_raw("MAX(id) > ?", vec![json!(0)]);
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT `user_id` FROM posts GROUP BY `user_id` HAVING COUNT(*) BETWEEN 1 AND 2 \
+         AND `user_id` IN (1, 2) AND MAX(id) > 0 ORDER BY `user_id` ASC",
+        "SELECT \"user_id\" FROM posts GROUP BY \"user_id\" HAVING COUNT(*) BETWEEN 1 AND 2 \
+         AND \"user_id\" IN (1, 2) AND MAX(id) > 0 ORDER BY \"user_id\" ASC",
+        "SELECT \"user_id\" FROM posts GROUP BY \"user_id\" HAVING COUNT(*) BETWEEN 1 AND 2 \
+         AND \"user_id\" IN (1, 2) AND MAX(id) > 0 ORDER BY \"user_id\" ASC",
+    ]);
+    assert_eq!(authors(query).await, vec![1, 2]);
+
+    assert_eq!(authors(posts_per_user().having_between("COUNT(*)", json!(2), json!(5))).await, vec![1]);
+    assert_eq!(authors(posts_per_user().having_in("user_id", vec![json!(2)])).await, vec![2]);
+    assert_eq!(authors(posts_per_user().having_raw("MAX(id) >= ?", vec![json!(3)])).await, vec![2]);
+}
+
+#[tokio::test]
+async fn having_on_a_with_count_alias_wraps_the_query_except_on_mysql() {
+    let query = User::query().with_count("posts").having("posts_count", ">", json!(0)).order_by("id", "asc");
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT *, (SELECT COUNT(*) FROM posts WHERE posts.user_id = users.id) AS posts_count FROM users \
+         HAVING `posts_count` > 0 ORDER BY `id` ASC",
+        "SELECT * FROM (SELECT *, (SELECT COUNT(*) FROM posts WHERE posts.user_id = users.id) AS posts_count FROM users) \
+         AS users WHERE \"posts_count\" > 0 ORDER BY \"id\" ASC",
+        "SELECT * FROM (SELECT *, (SELECT COUNT(*) FROM posts WHERE posts.user_id = users.id) AS posts_count FROM users) \
+         AS users WHERE \"posts_count\" > 0 ORDER BY \"id\" ASC",
+    ]);
+
+    let users = query.on_connection(seeded().await).get().await.unwrap();
+    assert_eq!(users.iter().map(|user| user.id).collect::<Vec<_>>(), vec![Some(1), Some(2)]);
+
+    let query = User::query().with_count("posts").having_raw("posts_count >= ?", vec![json!(2)]);
+    let users = query.on_connection(seeded().await).get().await.unwrap();
+    assert_eq!(users.iter().map(|user| user.id).collect::<Vec<_>>(), vec![Some(1)]);
+}
+
+#[tokio::test]
+async fn having_count_and_having_aggregate_render_per_driver_and_run_on_sqlite() {
+    let query = posts_per_user().having_count(">=", 2);
+    assert_eq!(sql_per_driver(&query)[1],
+        "SELECT \"user_id\" FROM posts GROUP BY \"user_id\" HAVING COUNT(*) >= 2 ORDER BY \"user_id\" ASC");
+    assert_eq!(authors(query).await, vec![1]);
+
+    let query = posts_per_user().having_aggregate(Aggregate::Max, "id", ">", json!(2));
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT `user_id` FROM posts GROUP BY `user_id` HAVING MAX(`id`) > 2 ORDER BY `user_id` ASC",
+        "SELECT \"user_id\" FROM posts GROUP BY \"user_id\" HAVING MAX(\"id\") > 2 ORDER BY \"user_id\" ASC",
+        "SELECT \"user_id\" FROM posts GROUP BY \"user_id\" HAVING MAX(\"id\") > 2 ORDER BY \"user_id\" ASC",
+    ]);
+    assert_eq!(authors(query).await, vec![2]);
+    assert_eq!(authors(posts_per_user().having_aggregate(Aggregate::Sum, "id", "=", json!(3))).await, vec![1, 2]);
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use common::*;
+use std::sync::Arc;
+use RustEloquent::db::connection::{DatabaseConnection, PostgresConnection};
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct PostsPerUser {
+    user_id: i64,
+    posts: i64,
+}
+
+#[tokio::test]
+async fn integer_columns_hydrate_on_sqlite() {
+    let connection = seeded().await;
+
+    let users = User::query().on_connection(connection.clone()).order_by("id", "asc").get().await.unwrap();
+    assert_eq!(users.iter().map(|user| user.id).collect::<Vec<_>>(), vec![Some(1), Some(2), Some(3)]);
+
+    let post = Post::query().on_connection(connection).where_op("id", "=", json!(3)).first().await.unwrap();
+    assert_eq!(post, Some(Post { id: Some(3), user_id: 2, title: "third".into() }));
+}
+
+#[tokio::test]
+async fn grouped_aggregates_hydrate_into_report_rows() {
+    let connection = seeded().await;
+
+    let report: Vec<PostsPerUser> = Post::query()
+        .on_connection(connection)
+        .select(vec!["user_id"])
+        .select_raw("COUNT(*) AS posts")
+        .group_by(vec!["user_id"])
+        .order_by("user_id", "asc")
+        .get_as()
+        .await
+        .unwrap();
+    assert_eq!(report, vec![PostsPerUser { user_id: 1, posts: 2 }, PostsPerUser { user_id: 2, posts: 1 }]);
+}
+
+#[tokio::test]
+async fn sqlite_values_decode_by_storage_class() {
+    let connection = sqlite(&[
+        "CREATE TABLE readings (id INTEGER PRIMARY KEY, score REAL, active BOOLEAN, note TEXT)",
+        "INSERT INTO readings VALUES (1, 1.5, 1, 'x'), (2, NULL, 0, NULL)",
+    ])
+    .await;
+
+    let (columns, rows) = connection.fetch_all("SELECT * FROM readings ORDER BY id").await.unwrap();
+    assert_eq!(&*columns, ["id", "score", "active", "note"]);
+    assert_eq!(rows, vec![
+        vec![json!(1), json!(1.5), json!(true), json!("x")],
+        vec![json!(2), Value::Null, json!(false), Value::Null],
+    ]);
+}
+
+#[tokio::test]
+async fn text_that_looks_like_a_number_stays_text() {
+    let connection = sqlite(&["INSERT INTO users (id, name) VALUES (1, '42'), (2, 'true'), (3, '007')"]).await;
+
+    let users = User::query().on_connection(connection).order_by("id", "asc").get().await.unwrap();
+    assert_eq!(users.iter().map(|user| (user.id, user.name.as_str())).collect::<Vec<_>>(),
+        [(Some(1), "42"), (Some(2), "true"), (Some(3), "007")]);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+enum Mood {
+    #[default]
+    Happy,
+    Sad,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+struct Person {
+    id: Option<i64>,
+    mood: Mood,
+    score: i64,
+}
+
+model!(Person, "hydration_people", |_: &str| None);
+
+const PEOPLE: &[&str] = &[
+    "INSERT INTO hydration_people (id, mood, score) VALUES (1, 'happy', 7), (2, 'sad', 3)",
+];
+
+fn sad_people() -> Query<Person> {
+    Person::query().where_op("mood", "=", json!("sad"))
+}
+
+#[tokio::test]
+async fn enum_labels_hydrate_into_rust_enums() {
+    assert_eq!(sql_per_driver(&sad_people()), [
+        "SELECT * FROM hydration_people WHERE `mood` = 'sad'",
+        "SELECT * FROM hydration_people WHERE \"mood\" = 'sad'",
+        "SELECT * FROM hydration_people WHERE \"mood\" = 'sad'",
+    ]);
+
+    let connection = sqlite(&[
+        "CREATE TABLE hydration_people (id INTEGER PRIMARY KEY, mood TEXT NOT NULL, score INTEGER NOT NULL)",
+        PEOPLE[0],
+    ])
+    .await;
+    let people = sad_people().on_connection(connection).get().await.unwrap();
+    assert_eq!(people, vec![Person { id: Some(2), mood: Mood::Sad, score: 3 }]);
+}
+
+// Postgres reports ENUM and domain columns by their own type names. Runs against the
+// database in POSTGRES_URL, and is skipped when that isn't set
+#[tokio::test]
+async fn postgres_enums_and_domains_hydrate() {
+    let Ok(url) = std::env::var("POSTGRES_URL") else {
+        return;
+    };
+    let connection: Arc<dyn DatabaseConnection> = Arc::new(PostgresConnection::new(&url).await.unwrap());
+    for sql in [
+        "DROP TABLE IF EXISTS hydration_people",
+        "DROP TYPE IF EXISTS hydration_mood",
+        "DROP DOMAIN IF EXISTS hydration_score",
+        "CREATE TYPE hydration_mood AS ENUM ('happy', 'sad')",
+        "CREATE DOMAIN hydration_score AS INTEGER CHECK (VALUE >= 0)",
+        "CREATE TABLE hydration_people (id BIGINT PRIMARY KEY, mood hydration_mood NOT NULL, score hydration_score NOT NULL)",
+        PEOPLE[0],
+    ] {
+        connection.execute(sql).await.expect(sql);
+    }
+
+    let people = sad_people().on_connection(connection.clone()).get().await.unwrap();
+    assert_eq!(people, vec![Person { id: Some(2), mood: Mood::Sad, score: 3 }]);
+
+    for sql in ["DROP TABLE hydration_people", "DROP TYPE hydration_mood", "DROP DOMAIN hydration_score"] {
+        connection.execute(sql).await.expect(sql);
+    }
+}
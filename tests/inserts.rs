@@ -0,0 +1,24 @@
+mod common;
+
+use common::*;
+
+#[tokio::test]
+async fn insert_or_ignore_skips_conflicting_rows() {
+    let rows = vec![
+        attributes(&[("id", json!(1)), ("name", json!("ada again"))]),
+        attributes(&[("id", json!(4)), ("name", json!("dee"))]),
+    ];
+    let sql = [Driver::MySql, Driver::Postgres, Driver::Sqlite]
+        .map(|driver| User::query().driver(driver).to_insert_or_ignore_sql(&rows));
+    assert_eq!(sql, [
+        "INSERT IGNORE INTO users (id, name) VALUES (1, 'ada again'), (4, 'dee')",
+        "INSERT INTO users (id, name) VALUES (1, 'ada again'), (4, 'dee') ON CONFLICT DO NOTHING",
+        "INSERT INTO users (id, name) VALUES (1, 'ada again'), (4, 'dee') ON CONFLICT DO NOTHING",
+    ]);
+
+    let connection = seeded().await;
+    assert_eq!(User::query().on_connection(connection.clone()).insert_or_ignore(rows).await.unwrap(), 1);
+    assert_eq!(User::query().on_connection(connection.clone()).insert_or_ignore(Vec::new()).await.unwrap(), 0);
+    let users = User::query().order_by("id", "asc").on_connection(connection).get().await.unwrap();
+    assert_eq!(users.iter().map(|user| user.name.as_str()).collect::<Vec<_>>(), ["ada", "bob", "cy", "dee"]);
+}
@@ -0,0 +1,22 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn builder_methods_record_their_clauses() {
+    let query = Post::query()
+        .where_between("id", json!(1), json!(3))
+        .join("users", "users.id", "=", "posts.user_id")
+        .order_by_desc("title")
+        .limit(2);
+    let clauses = query.inspect_clauses();
+
+    assert_eq!(clauses.where_conditions().len(), 1);
+    assert_eq!(clauses.where_conditions()[0].operator, "BETWEEN");
+    assert_eq!(clauses.where_conditions()[0].value, json!([1, 3]));
+    assert_eq!(clauses.joins()[0].table, "users");
+    assert_eq!(clauses.joins()[0].join_type, "INNER");
+    assert_eq!(clauses.order_by().len(), 1);
+    assert_eq!((clauses.order_by()[0].column.as_str(), clauses.order_by()[0].direction.as_str()), ("title", "DESC"));
+    assert_eq!(clauses.limit_value(), Some(2));
+}
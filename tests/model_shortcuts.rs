@@ -0,0 +1,30 @@
+mod common;
+
+use common::*;
+
+#[tokio::test]
+async fn shortcuts_go_through_the_model_query() {
+    with_registered("model_shortcuts", |_| async {
+        assert_eq!(User::count().await.unwrap(), 3);
+        assert_eq!(User::count_all().await.unwrap(), 3);
+        assert_eq!(User::first().await.unwrap().map(|user| user.name), Some("ada".to_string()));
+        assert_eq!(Post::pluck("title").await.unwrap(), vec![json!("first"), json!("second"), json!("third")]);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn fresh_with_eager_loads_the_copy() {
+    with_registered("fresh_with", |_| async {
+        let ada = User { id: Some(1), name: "stale".into() };
+        let fresh = ada.fresh_with(vec!["posts.comments"]).await.unwrap().unwrap();
+        assert_eq!(fresh.name, "ada");
+        let posts = fresh.related_loaded::<Post>("posts").unwrap();
+        assert_eq!(posts.iter().map(|post| post.title.as_str()).collect::<Vec<_>>(), ["first", "second"]);
+        assert_eq!(posts[0].related::<Comment>("comments").unwrap().len(), 2);
+
+        assert_eq!(ada.fresh().await.unwrap().map(|user| user.name), Some("ada".to_string()));
+        assert!(User { id: None, name: "new".into() }.fresh_with(vec!["posts"]).await.unwrap().is_none());
+    })
+    .await;
+}
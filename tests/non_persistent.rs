@@ -0,0 +1,46 @@
+mod common;
+
+use common::*;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+struct Ranked {
+    id: Option<i64>,
+    name: String,
+    #[serde(default)]
+    name_length: Option<i64>,
+}
+
+model!(Ranked, "users", |_: &str| None,
+    fn non_persistent() -> Vec<&'static str> { vec!["name_length"] },
+    fn timestamps() -> bool { false });
+
+#[tokio::test]
+async fn computed_fields_are_never_written() {
+    let row = attributes(&[("name", json!("dee")), ("name_length", json!(3))]);
+    let expected = "INSERT INTO users (name) VALUES ('dee')";
+    assert_eq!([Driver::MySql, Driver::Postgres, Driver::Sqlite].map(|driver| Ranked::query().driver(driver).to_create_sql(&row)),
+        [expected, expected, expected]);
+    assert_eq!([Driver::MySql, Driver::Postgres, Driver::Sqlite]
+        .map(|driver| Ranked::query().driver(driver).where_op("id", "=", json!(1)).to_update_sql(&row)), [
+        "UPDATE users SET name = 'dee' WHERE `id` = 1",
+        "UPDATE users SET name = 'dee' WHERE \"id\" = 1",
+        "UPDATE users SET name = 'dee' WHERE \"id\" = 1",
+    ]);
+
+    let connection = seeded().await;
+    let created = Ranked::query().on_connection(connection.clone()).create_many(vec![row.clone()]).await.unwrap();
+    assert_eq!(created, vec![Ranked { id: Some(4), name: "dee".into(), name_length: None }]);
+    Ranked::query().on_connection(connection.clone()).where_op("id", "=", json!(1)).update(row).await.unwrap();
+
+    // Still read when selected
+    let ranked = Ranked::query()
+        .on_connection(connection)
+        .select(vec!["id", "name"])
+        .select_raw("LENGTH(name) AS name_length")
+        .order_by("id", "asc")
+        .get()
+        .await
+        .unwrap();
+    assert_eq!(ranked.iter().map(|row| (row.name.as_str(), row.name_length)).collect::<Vec<_>>(),
+        [("dee", Some(3)), ("bob", Some(3)), ("cy", Some(2)), ("dee", Some(3))]);
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use common::*;
+use RustEloquent::orm::query::NullsOrder;
+
+async fn post_ids(query: Query<Post>) -> Vec<Option<i64>> {
+    let posts = query.on_connection(seeded().await).get().await.unwrap();
+    posts.iter().map(|post| post.id).collect()
+}
+
+#[tokio::test]
+async fn order_by_raw_is_used_as_written() {
+    let query = Post::query().order_by_raw("LENGTH(title) DESC, id ASC");
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT * FROM posts ORDER BY LENGTH(title) DESC, id ASC",
+        "SELECT * FROM posts ORDER BY LENGTH(title) DESC, id ASC",
+        "SELECT * FROM posts ORDER BY LENGTH(title) DESC, id ASC",
+    ]);
+    assert_eq!(post_ids(query).await, vec![Some(2), Some(1), Some(3)]);
+}
+
+#[tokio::test]
+async fn order_by_raw_bound_formats_bindings_for_the_final_driver() {
+    let query = Post::query()
+        .order_by_raw_bound("title = ? DESC", vec![json!("it's")])
+        .order_by_raw_bound("user_id = ? DESC", vec![json!(2)])
+        .order_by("id", "asc");
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT * FROM posts ORDER BY title = 'it''s' DESC, user_id = 2 DESC, `id` ASC",
+        "SELECT * FROM posts ORDER BY title = 'it''s' DESC, user_id = 2 DESC, \"id\" ASC",
+        "SELECT * FROM posts ORDER BY title = 'it''s' DESC, user_id = 2 DESC, \"id\" ASC",
+    ]);
+    assert_eq!(post_ids(query).await, vec![Some(3), Some(1), Some(2)]);
+
+    // Built for Postgres, then switched to MySQL, which also doubles backslashes
+    let query = Post::query().driver(Driver::Postgres).order_by_raw_bound("title = ? DESC", vec![json!("a\\b")]);
+    assert_eq!(query.clone().to_sql(), "SELECT * FROM posts ORDER BY title = 'a\\b' DESC");
+    assert_eq!(query.driver(Driver::MySql).to_sql(), "SELECT * FROM posts ORDER BY title = 'a\\\\b' DESC");
+}
+
+#[tokio::test]
+async fn order_by_nulls_places_nulls_whatever_the_direction() {
+    let query = User::query().order_by_nulls("email", "asc", NullsOrder::Last).order_by("id", "asc");
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT * FROM users ORDER BY `email` IS NULL, `email` ASC, `id` ASC",
+        "SELECT * FROM users ORDER BY \"email\" ASC NULLS LAST, \"id\" ASC",
+        "SELECT * FROM users ORDER BY \"email\" ASC NULLS LAST, \"id\" ASC",
+    ]);
+    let query_first = User::query().order_by_nulls("email", "desc", NullsOrder::First).order_by("id", "asc");
+    assert_eq!(sql_per_driver(&query_first)[0], "SELECT * FROM users ORDER BY `email` IS NULL DESC, `email` DESC, `id` ASC");
+
+    let mut statements = SEED.to_vec();
+    statements.push("UPDATE users SET email = CASE id WHEN 2 THEN 'b@x' WHEN 3 THEN 'c@x' END WHERE id > 1");
+    let connection = sqlite(&statements).await;
+    let ids = |users: Vec<User>| users.iter().map(|user| user.id).collect::<Vec<_>>();
+    assert_eq!(ids(query.on_connection(connection.clone()).get().await.unwrap()), vec![Some(2), Some(3), Some(1)]);
+    assert_eq!(ids(query_first.on_connection(connection).get().await.unwrap()), vec![Some(1), Some(3), Some(2)]);
+}
+
+#[tokio::test]
+async fn for_page_skips_the_earlier_pages() {
+    let page = |page, per_page| Post::query().order_by("id", "asc").for_page(page, per_page);
+    assert_eq!(sql_per_driver(&page(2, 2)), [
+        "SELECT * FROM posts ORDER BY `id` ASC LIMIT 2 OFFSET 2",
+        "SELECT * FROM posts ORDER BY \"id\" ASC LIMIT 2 OFFSET 2",
+        "SELECT * FROM posts ORDER BY \"id\" ASC LIMIT 2 OFFSET 2",
+    ]);
+    assert_eq!(post_ids(page(1, 2)).await, vec![Some(1), Some(2)]);
+    assert_eq!(post_ids(page(2, 2)).await, vec![Some(3)]);
+    assert_eq!(post_ids(page(3, 2)).await, vec![]);
+
+    // Pages below 1 are the first page, and a page holds at least one row
+    assert_eq!(sql_per_driver(&page(0, 0))[2], "SELECT * FROM posts ORDER BY \"id\" ASC LIMIT 1 OFFSET 0");
+    assert_eq!(post_ids(page(-3, 0)).await, vec![Some(1)]);
+}
+
+#[tokio::test]
+async fn reorder_drops_every_earlier_ordering() {
+    let base = Post::query()
+        .order_by("user_id", "desc")
+        .order_by_raw_bound("title = ? DESC", vec![json!("second")])
+        .in_random_order();
+
+    let query = base.clone().reorder();
+    assert_eq!(sql_per_driver(&query), ["SELECT * FROM posts", "SELECT * FROM posts", "SELECT * FROM posts"]);
+    assert_eq!(post_ids(query).await.len(), 3);
+
+    let query = base.reorder_by("title", "desc");
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT * FROM posts ORDER BY `title` DESC",
+        "SELECT * FROM posts ORDER BY \"title\" DESC",
+        "SELECT * FROM posts ORDER BY \"title\" DESC",
+    ]);
+    assert_eq!(post_ids(query).await, vec![Some(3), Some(2), Some(1)]);
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::*;
+use RustEloquent::orm::relations::Relation;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+// A Write sink the test can read back
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// Has no field pointing at users
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Note {
+    id: Option<i64>,
+    body: String,
+}
+
+model!(Note, "notes", |_: &str| None);
+
+#[test]
+fn guessed_foreign_keys_are_logged_as_warnings() {
+    let buffer = Buffer::default();
+    RustEloquent::log_queries_to_writer(buffer.clone());
+    let notes = User { id: Some(1), name: "ada".into() }.has_many::<Note>(None, None);
+    RustEloquent::stop_logging_queries();
+
+    assert_eq!(notes.get_query().to_sql(), "SELECT * FROM notes WHERE `user_id` = 1");
+    let log = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    assert_eq!(log, "-- warning: no field on [notes] matches a foreign key for [users]; assuming user_id - pass the key explicitly\n");
+}
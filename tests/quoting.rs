@@ -0,0 +1,65 @@
+mod common;
+
+use common::*;
+
+const AWKWARD: &str = "O'Brien \\ C:\\dir\\'x";
+
+#[tokio::test]
+async fn string_literals_escape_quotes_and_mysql_backslashes() {
+    let query = User::query().where_op("name", "=", json!(AWKWARD));
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT * FROM users WHERE `name` = 'O''Brien \\\\ C:\\\\dir\\\\''x'",
+        "SELECT * FROM users WHERE \"name\" = 'O''Brien \\ C:\\dir\\''x'",
+        "SELECT * FROM users WHERE \"name\" = 'O''Brien \\ C:\\dir\\''x'",
+    ]);
+
+    let rename = attributes(&[("name", json!(AWKWARD))]);
+    assert_eq!(User::query().driver(Driver::MySql).where_op("id", "=", json!(2)).to_update_sql(&rename),
+        "UPDATE users SET name = 'O''Brien \\\\ C:\\\\dir\\\\''x' WHERE `id` = 2");
+
+    let connection = seeded().await;
+    User::query().on_connection(connection.clone()).where_op("id", "=", json!(2)).update(rename).await.unwrap();
+    User::query().on_connection(connection.clone()).insert_or_ignore(vec![attributes(&[("name", json!("it's"))])]).await.unwrap();
+
+    let found = query.on_connection(connection.clone()).first().await.unwrap();
+    assert_eq!(found, Some(User { id: Some(2), name: AWKWARD.into() }));
+    let found = User::query().on_connection(connection).where_op("name", "=", json!("it's")).first().await.unwrap();
+    assert_eq!(found.map(|user| user.id), Some(Some(4)));
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Byline {
+    name: String,
+    title: String,
+}
+
+#[tokio::test]
+async fn qualified_identifiers_quote_each_part() {
+    let query = User::query()
+        .select(vec!["users.name", "posts.title"])
+        .join("posts", "posts.user_id", "=", "users.id")
+        .where_op("posts.id", ">", json!(1))
+        .order_by("posts.id", "asc");
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT `users`.`name`, `posts`.`title` FROM users INNER JOIN posts ON `posts`.`user_id` = `users`.`id` \
+         WHERE `posts`.`id` > 1 ORDER BY `posts`.`id` ASC",
+        "SELECT \"users\".\"name\", \"posts\".\"title\" FROM users INNER JOIN posts ON \"posts\".\"user_id\" = \"users\".\"id\" \
+         WHERE \"posts\".\"id\" > 1 ORDER BY \"posts\".\"id\" ASC",
+        "SELECT \"users\".\"name\", \"posts\".\"title\" FROM users INNER JOIN posts ON \"posts\".\"user_id\" = \"users\".\"id\" \
+         WHERE \"posts\".\"id\" > 1 ORDER BY \"posts\".\"id\" ASC",
+    ]);
+
+    let rows: Vec<Byline> = query.on_connection(seeded().await).get_as().await.unwrap();
+    assert_eq!(rows, vec![
+        Byline { name: "ada".into(), title: "second".into() },
+        Byline { name: "bob".into(), title: "third".into() },
+    ]);
+
+    // Wildcards, aliases and expressions are left as written
+    let query = User::query().select(vec!["users.*", "name AS label", "COUNT(posts.id)"]);
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT `users`.*, name AS label, COUNT(posts.id) FROM users",
+        "SELECT \"users\".*, name AS label, COUNT(posts.id) FROM users",
+        "SELECT \"users\".*, name AS label, COUNT(posts.id) FROM users",
+    ]);
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::*;
+
+async fn user_ids(query: Query<User>) -> Vec<Option<i64>> {
+    let users = query.order_by("id", "asc").on_connection(seeded().await).get().await.unwrap();
+    users.iter().map(|user| user.id).collect()
+}
+
+#[tokio::test]
+async fn where_has_and_where_doesnt_have_render_per_driver_and_run_on_sqlite() {
+    let query = User::query().where_has("posts", |posts| posts.where_op("title", "=", json!("third")));
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT * FROM users WHERE EXISTS (SELECT * FROM posts WHERE posts.user_id = users.id AND `title` = 'third')",
+        "SELECT * FROM users WHERE EXISTS (SELECT * FROM posts WHERE posts.user_id = users.id AND \"title\" = 'third')",
+        "SELECT * FROM users WHERE EXISTS (SELECT * FROM posts WHERE posts.user_id = users.id AND \"title\" = 'third')",
+    ]);
+    assert_eq!(user_ids(query).await, vec![Some(2)]);
+
+    let query = User::query().where_doesnt_have("posts", |posts| posts);
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT * FROM users WHERE NOT EXISTS (SELECT * FROM posts WHERE posts.user_id = users.id)",
+        "SELECT * FROM users WHERE NOT EXISTS (SELECT * FROM posts WHERE posts.user_id = users.id)",
+        "SELECT * FROM users WHERE NOT EXISTS (SELECT * FROM posts WHERE posts.user_id = users.id)",
+    ]);
+    assert_eq!(user_ids(query).await, vec![Some(3)]);
+}
+
+#[tokio::test]
+async fn where_has_groups_several_constraints() {
+    let query = User::query().where_has("posts", |posts| {
+        posts.where_op("title", "=", json!("first")).or_where("title", "=", json!("third"))
+    });
+    assert_eq!(sql_per_driver(&query)[1],
+        "SELECT * FROM users WHERE EXISTS (SELECT * FROM posts WHERE posts.user_id = users.id \
+         AND (\"title\" = 'first' OR \"title\" = 'third'))");
+    assert_eq!(user_ids(query).await, vec![Some(1), Some(2)]);
+
+    let query = User::query().where_doesnt_have("posts", |posts| posts.where_op("title", "=", json!("first")));
+    assert_eq!(user_ids(query).await, vec![Some(2), Some(3)]);
+}
+
+#[tokio::test]
+async fn has_compares_the_number_of_related_rows() {
+    let query = User::query().has("posts", ">=", 2);
+    let expected = "SELECT * FROM users WHERE (SELECT COUNT(*) FROM posts WHERE posts.user_id = users.id) >= 2";
+    assert_eq!(sql_per_driver(&query), [expected, expected, expected]);
+    assert_eq!(user_ids(query).await, vec![Some(1)]);
+
+    assert_eq!(user_ids(User::query().has("posts", "=", 0)).await, vec![Some(3)]);
+    assert_eq!(user_ids(User::query().has("posts", "<", 2).where_op("id", "!=", json!(3))).await, vec![Some(2)]);
+}
+
+#[tokio::test]
+async fn or_where_has_is_one_or_ed_condition() {
+    let query = User::query()
+        .where_op("name", "=", json!("cy"))
+        .or_where_has("posts", |posts| posts.where_op("title", "=", json!("third")));
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT * FROM users WHERE `name` = 'cy' OR EXISTS (SELECT * FROM posts WHERE posts.user_id = users.id AND `title` = 'third')",
+        "SELECT * FROM users WHERE \"name\" = 'cy' OR EXISTS (SELECT * FROM posts WHERE posts.user_id = users.id AND \"title\" = 'third')",
+        "SELECT * FROM users WHERE \"name\" = 'cy' OR EXISTS (SELECT * FROM posts WHERE posts.user_id = users.id AND \"title\" = 'third')",
+    ]);
+    assert_eq!(user_ids(query).await, vec![Some(2), Some(3)]);
+
+    // `a AND b OR NOT EXISTS (...)` reads as `(a AND b) OR NOT EXISTS (...)`
+    let query = User::query()
+        .where_op("id", ">", json!(1))
+        .where_op("name", "=", json!("bob"))
+        .or_where_doesnt_have("posts", |posts| posts);
+    assert_eq!(sql_per_driver(&query)[2],
+        "SELECT * FROM users WHERE \"id\" > 1 AND \"name\" = 'bob' OR NOT EXISTS (SELECT * FROM posts WHERE posts.user_id = users.id)");
+    assert_eq!(user_ids(query).await, vec![Some(2), Some(3)]);
+}
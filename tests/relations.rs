@@ -0,0 +1,124 @@
+mod common;
+
+use common::*;
+use RustEloquent::orm::EloquentError;
+use RustEloquent::orm::relations::{CreatableRelation, Relation};
+
+#[tokio::test]
+async fn touch_is_scoped_to_the_parent() {
+    with_registered("touch", |connection| async move {
+        let touched = || Post::query().on_connection(connection.clone()).where_not_null("updated_at").pluck("id");
+
+        let unsaved = User { id: None, name: "new".into() };
+        assert_eq!(unsaved.has_many::<Post>(None, None).touch().await.unwrap(), 0);
+        assert!(touched().await.unwrap().is_empty());
+
+        let ada = User { id: Some(1), name: "ada".into() };
+        assert_eq!(ada.has_many::<Post>(None, None).touch().await.unwrap(), 2);
+        assert_eq!(touched().await.unwrap(), vec![json!(1), json!(2)]);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn belongs_to_matches_the_childs_foreign_key() {
+    with_registered("belongs_to", |connection| async move {
+        // Comment 2 belongs to post 1, so its own id must not pick post 2
+        let comment = Comment { id: Some(2), post_id: 1, body: "great".into() };
+        let post = comment.belongs_to::<Post>(None, None).first().await.unwrap().unwrap();
+        assert_eq!(post.id, Some(1));
+
+        assert_eq!(comment.belongs_to::<Post>(None, None).touch().await.unwrap(), 1);
+        let touched = Post::query().on_connection(connection).where_not_null("updated_at").pluck("id").await.unwrap();
+        assert_eq!(touched, vec![json!(1)]);
+    })
+    .await;
+}
+
+// A comment that touches its post whenever it's saved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Reply {
+    id: Option<i64>,
+    post_id: i64,
+    body: String,
+}
+
+model!(Reply, "comments", |name: &str| match name {
+    "post" => Some(RelationDefinition::belongs_to::<Reply, Post>(None, None)),
+    _ => None,
+}, fn connection() -> &'static str { scoped_connection() },
+   fn touches() -> Vec<&'static str> { vec!["post"] });
+
+// Lists a relation it never defines
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Orphan {
+    id: Option<i64>,
+    post_id: i64,
+    body: String,
+}
+
+model!(Orphan, "comments", |_: &str| None, fn touches() -> Vec<&'static str> { vec!["post"] });
+
+#[tokio::test]
+async fn saves_touch_the_owners_in_touches() {
+    with_registered("touch_owners", |connection| async move {
+        let touched = || Post::query().on_connection(connection.clone()).where_not_null("updated_at").pluck("id");
+
+        Reply { id: Some(2), post_id: 1, body: "great".into() }.save_returning().await.unwrap();
+        assert_eq!(touched().await.unwrap(), vec![json!(1)]);
+
+        // The relation's save points the reply at post 3 before touching
+        let bob_post = Post { id: Some(3), user_id: 2, title: "third".into() };
+        let reply = Reply { id: None, post_id: 0, body: "new".into() };
+        bob_post.has_many::<Reply>(None, None).save(&reply).await.unwrap();
+        assert_eq!(touched().await.unwrap(), vec![json!(1), json!(3)]);
+
+        let unsaved = Post { id: None, user_id: 2, title: "draft".into() };
+        let error = unsaved.has_many::<Reply>(None, None).save(&reply).await.unwrap_err();
+        assert!(matches!(EloquentError::from(error), EloquentError::MissingKey(table) if table == "posts"));
+
+        let error = Orphan { id: Some(1), post_id: 1, body: "nice".into() }.save_returning().await.unwrap_err();
+        assert!(matches!(EloquentError::from(error), EloquentError::Configuration(message) if message.contains("[post]")));
+    })
+    .await;
+}
+
+#[test]
+fn of_many_picks_one_key_through_a_derived_table() {
+    let ada = User { id: Some(1), name: "ada".into() };
+    assert_eq!(ada.has_many::<Post>(None, None).latest_of_many("created_at").get_query().to_sql(),
+        "SELECT * FROM posts WHERE `user_id` = 1 AND posts.id = (SELECT of_many_key.id FROM \
+         (SELECT of_many.id FROM posts AS of_many WHERE of_many.user_id = 1 ORDER BY of_many.created_at DESC, of_many.id DESC LIMIT 1) \
+         AS of_many_key)");
+}
+
+#[tokio::test]
+async fn of_many_breaks_ties_on_the_primary_key() {
+    with_registered("of_many", |connection| async move {
+        let ada = User { id: Some(1), name: "ada".into() };
+        // Both of ada's posts share user_id, so only the key decides
+        let latest = ada.has_many::<Post>(None, None).latest_of_many("user_id");
+        assert_eq!(latest.get().await.unwrap().iter().map(|post| post.id).collect::<Vec<_>>(), [Some(2)]);
+        let oldest = ada.has_many::<Post>(None, None).oldest_of_many("title");
+        assert_eq!(oldest.first().await.unwrap().map(|post| post.id), Some(Some(1)));
+
+        assert_eq!(latest.update(std::collections::HashMap::from([("title".to_string(), json!("edited"))])).await.unwrap(), 1);
+        assert_eq!(latest.delete().await.unwrap(), 1);
+        let left = Post::query().on_connection(connection).where_op("user_id", "=", json!(1)).pluck("title").await.unwrap();
+        assert_eq!(left, vec![json!("first")]);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn relations_narrow_without_query_builder_in_scope() {
+    with_registered("inherent_builders", |_| async move {
+        let ada = User { id: Some(1), name: "ada".into() };
+        let newest = ada.has_many::<Post>(None, None).order_by("id", "desc").limit(1).get().await.unwrap();
+        assert_eq!(newest.iter().map(|post| post.id).collect::<Vec<_>>(), [Some(2)]);
+
+        let first = ada.has_many::<Post>(None, None).where_clause("title", "first").get().await.unwrap();
+        assert_eq!(first.iter().map(|post| post.id).collect::<Vec<_>>(), [Some(1)]);
+    })
+    .await;
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use async_trait::async_trait;
+use common::*;
+use std::sync::Arc;
+use RustEloquent::db::connection::DatabaseConnection;
+use RustEloquent::db::migration::{Migration, Migrator};
+use RustEloquent::db::schema::Schema;
+
+struct AddTitleIndexAndAudit;
+
+#[async_trait]
+impl Migration for AddTitleIndexAndAudit {
+    fn name(&self) -> &str {
+        "2026_01_01_000000_add_title_index_and_audit"
+    }
+
+    async fn up(&self, schema: &Schema) -> Result<(), sqlx::Error> {
+        schema.raw(
+            "CREATE INDEX posts_title_lower ON posts (LOWER(title)); \
+             CREATE TABLE post_audits (post_id INTEGER NOT NULL); \
+             CREATE TRIGGER posts_audit AFTER INSERT ON posts BEGIN INSERT INTO post_audits VALUES (NEW.id); END",
+        ).await
+    }
+
+    async fn down(&self, schema: &Schema) -> Result<(), sqlx::Error> {
+        schema.raw("DROP TRIGGER posts_audit; DROP TABLE post_audits; DROP INDEX posts_title_lower").await
+    }
+}
+
+struct FailsHalfway;
+
+#[async_trait]
+impl Migration for FailsHalfway {
+    fn name(&self) -> &str {
+        "2026_01_02_000000_fails_halfway"
+    }
+
+    async fn up(&self, schema: &Schema) -> Result<(), sqlx::Error> {
+        schema.raw("CREATE TABLE half_done (id INTEGER)").await?;
+        schema.raw("CREATE TABLE posts (id INTEGER)").await
+    }
+
+    async fn down(&self, _schema: &Schema) -> Result<(), sqlx::Error> {
+        Ok(())
+    }
+}
+
+async fn table_count(connection: &Arc<dyn DatabaseConnection>, name: &str) -> i64 {
+    let sql = format!("SELECT COUNT(*) FROM sqlite_master WHERE name = '{}'", name);
+    let (_, rows) = connection.fetch_all(&sql).await.unwrap();
+    rows[0][0].as_i64().unwrap_or_default()
+}
+
+#[tokio::test]
+async fn raw_runs_several_statements_in_the_migration() {
+    let connection = seeded().await;
+    let migrator = Migrator::new(connection.clone()).register(AddTitleIndexAndAudit);
+    assert_eq!(migrator.run().await.unwrap(), ["2026_01_01_000000_add_title_index_and_audit"]);
+
+    Post::query().on_connection(connection.clone())
+        .insert_or_ignore(vec![attributes(&[("user_id", json!(3)), ("title", json!("fourth"))])])
+        .await
+        .unwrap();
+    let (_, audits) = connection.fetch_all("SELECT post_id FROM post_audits").await.unwrap();
+    assert_eq!(audits, vec![vec![json!(4)]]);
+    assert_eq!(table_count(&connection, "posts_title_lower").await, 1);
+
+    migrator.rollback().await.unwrap();
+    assert_eq!(table_count(&connection, "post_audits").await, 0);
+    assert_eq!(table_count(&connection, "posts_title_lower").await, 0);
+}
+
+#[tokio::test]
+async fn raw_is_rolled_back_with_a_failed_migration() {
+    let connection = seeded().await;
+    let migrator = Migrator::new(connection.clone()).register(FailsHalfway);
+    let error = migrator.run().await.unwrap_err();
+    assert!(error.to_string().contains("already exists"), "{}", error);
+
+    assert_eq!(table_count(&connection, "half_done").await, 0);
+    assert_eq!(migrator.pending().await.unwrap(), ["2026_01_02_000000_fails_halfway"]);
+}
+
+#[tokio::test]
+async fn unreadable_migration_rows_are_decode_errors() {
+    let connection = seeded().await;
+    let migrator = Migrator::new(connection.clone()).register(AddTitleIndexAndAudit);
+    assert_eq!(migrator.pending().await.unwrap().len(), 1);
+
+    // Skipping the row would report the migration as pending and run it twice
+    connection.execute("INSERT INTO migrations (migration, batch) VALUES ('2026_01_01_000000_add_title_index_and_audit', 'one')")
+        .await
+        .unwrap();
+    let error = migrator.pending().await.unwrap_err();
+    assert!(matches!(&error, sqlx::Error::Decode(message) if message.to_string().contains("[batch]")), "{}", error);
+    assert!(migrator.run().await.is_err());
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use async_trait::async_trait;
+use common::*;
+use std::sync::Arc;
+use RustEloquent::db::connection::{DatabaseConnection, DatabaseTransaction, IsolationLevel};
+use RustEloquent::orm::EloquentError;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct TitleMatch {
+    id: i64,
+    title: String,
+    is_first: i64,
+    plus: i64,
+}
+
+#[tokio::test]
+async fn raw_selects_append_to_structured_ones_with_their_own_bindings() {
+    let query = Post::query()
+        .select(vec!["id", "title"])
+        .select_raw_bound("title = ? AS is_first", vec![json!("first")])
+        .select_raw_bound("id + ? AS plus", vec![json!(10)])
+        .order_by("id", "asc");
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT `id`, `title`, title = 'first' AS is_first, id + 10 AS plus FROM posts ORDER BY `id` ASC",
+        "SELECT \"id\", \"title\", title = 'first' AS is_first, id + 10 AS plus FROM posts ORDER BY \"id\" ASC",
+        "SELECT \"id\", \"title\", title = 'first' AS is_first, id + 10 AS plus FROM posts ORDER BY \"id\" ASC",
+    ]);
+
+    let rows: Vec<TitleMatch> = query.on_connection(seeded().await).limit(2).get_as().await.unwrap();
+    assert_eq!(rows, vec![
+        TitleMatch { id: 1, title: "first".into(), is_first: 1, plus: 11 },
+        TitleMatch { id: 2, title: "second".into(), is_first: 0, plus: 12 },
+    ]);
+}
+
+#[test]
+fn set_select_raw_replaces_the_select_list() {
+    let query = Post::query().select(vec!["id"]).set_select_raw("COUNT(*) > ? AS many", vec![json!(1)]);
+    assert_eq!(sql_per_driver(&query)[2], "SELECT COUNT(*) > 1 AS many FROM posts");
+}
+
+#[test]
+fn bindings_are_formatted_for_the_driver_the_query_ends_up_with() {
+    // Built for Postgres, then switched to MySQL, which also doubles backslashes
+    let query = Post::query().driver(Driver::Postgres).select_raw_bound("? AS path", vec![json!("a\\b")]);
+    assert_eq!(query.clone().to_sql(), "SELECT 'a\\b' AS path FROM posts");
+    assert_eq!(query.driver(Driver::MySql).to_sql(), "SELECT 'a\\\\b' AS path FROM posts");
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct RankedPost {
+    id: i64,
+    rn: i64,
+}
+
+#[tokio::test]
+async fn window_columns_are_quoted_for_the_final_driver() {
+    // Built for MySQL, rendered for each driver
+    let query = Post::query()
+        .driver(Driver::MySql)
+        .select(vec!["id"])
+        .select_window("rn", "ROW_NUMBER()", vec!["user_id"], vec![("id", "desc")])
+        .order_by("id", "asc");
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT `id`, ROW_NUMBER() OVER (PARTITION BY `user_id` ORDER BY `id` DESC) AS `rn` FROM posts ORDER BY `id` ASC",
+        "SELECT \"id\", ROW_NUMBER() OVER (PARTITION BY \"user_id\" ORDER BY \"id\" DESC) AS \"rn\" FROM posts ORDER BY \"id\" ASC",
+        "SELECT \"id\", ROW_NUMBER() OVER (PARTITION BY \"user_id\" ORDER BY \"id\" DESC) AS \"rn\" FROM posts ORDER BY \"id\" ASC",
+    ]);
+
+    let rows: Vec<RankedPost> = query.driver(Driver::Sqlite).on_connection(seeded().await).get_as().await.unwrap();
+    assert_eq!(rows, [RankedPost { id: 1, rn: 2 }, RankedPost { id: 2, rn: 1 }, RankedPost { id: 3, rn: 1 }]);
+}
+
+// A SQLite connection that reports a server without window functions
+struct WithoutWindows(Arc<dyn DatabaseConnection>);
+
+#[async_trait]
+impl DatabaseConnection for WithoutWindows {
+    fn driver(&self) -> Driver {
+        self.0.driver()
+    }
+    async fn execute(&self, sql: &str) -> Result<u64, sqlx::Error> {
+        self.0.execute(sql).await
+    }
+    async fn fetch_one(&self, sql: &str) -> Result<Vec<(String, Value)>, sqlx::Error> {
+        self.0.fetch_one(sql).await
+    }
+    async fn fetch_all(&self, sql: &str) -> Result<(Arc<[String]>, Vec<Vec<Value>>), sqlx::Error> {
+        self.0.fetch_all(sql).await
+    }
+    async fn begin(&self) -> Result<Box<dyn DatabaseTransaction>, sqlx::Error> {
+        self.0.begin().await
+    }
+    async fn begin_with_isolation(&self, level: IsolationLevel) -> Result<Box<dyn DatabaseTransaction>, sqlx::Error> {
+        self.0.begin_with_isolation(level).await
+    }
+    async fn close(&self) {
+        self.0.close().await
+    }
+    fn supports_window_functions(&self) -> bool {
+        false
+    }
+}
+
+#[tokio::test]
+async fn window_functions_are_refused_on_servers_without_them() {
+    let connection: Arc<dyn DatabaseConnection> = Arc::new(WithoutWindows(seeded().await));
+    let query = Post::query().on_connection(connection).select_window("rn", "ROW_NUMBER()", vec![], vec![("id", "asc")]);
+
+    let error = query.clone().get().await.unwrap_err();
+    assert!(matches!(EloquentError::from(error), EloquentError::Unsupported(message) if message.contains("MySQL 8.0+")));
+    assert_eq!(query.select(vec!["id"]).count().await.unwrap(), 3);
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct LabelledPost {
+    id: i64,
+    label: String,
+}
+
+#[tokio::test]
+async fn case_values_are_bound_in_order_for_the_final_driver() {
+    // Built for Postgres, then rendered for each driver; MySQL also doubles the backslash
+    let query = Post::query()
+        .driver(Driver::Postgres)
+        .select(vec!["id"])
+        .select_case("label", vec![("user_id = 1", json!("ada's")), ("id = 3", json!("a\\b"))], json!("other"))
+        .order_by("id", "asc");
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT `id`, CASE WHEN user_id = 1 THEN 'ada''s' WHEN id = 3 THEN 'a\\\\b' ELSE 'other' END AS `label` FROM posts ORDER BY `id` ASC",
+        "SELECT \"id\", CASE WHEN user_id = 1 THEN 'ada''s' WHEN id = 3 THEN 'a\\b' ELSE 'other' END AS \"label\" FROM posts ORDER BY \"id\" ASC",
+        "SELECT \"id\", CASE WHEN user_id = 1 THEN 'ada''s' WHEN id = 3 THEN 'a\\b' ELSE 'other' END AS \"label\" FROM posts ORDER BY \"id\" ASC",
+    ]);
+    assert_eq!(query.to_ast().select[1], "CASE WHEN user_id = 1 THEN ? WHEN id = 3 THEN ? ELSE ? END AS label");
+    assert_eq!(query.validate_bindings(), Ok(()));
+
+    let rows: Vec<LabelledPost> = query.driver(Driver::Sqlite).on_connection(seeded().await).get_as().await.unwrap();
+    assert_eq!(rows, [
+        LabelledPost { id: 1, label: "ada's".into() },
+        LabelledPost { id: 2, label: "ada's".into() },
+        LabelledPost { id: 3, label: "a\\b".into() },
+    ]);
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use common::*;
+use std::sync::Arc;
+use RustEloquent::db::connection::DatabaseConnection;
+use RustEloquent::orm::relations::Relation;
+use RustEloquent::orm::{QueryBuilder, SoftDeletes};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+struct Draft {
+    id: Option<i64>,
+    user_id: i64,
+    title: String,
+}
+
+model!(Draft, "posts", |_: &str| None,
+    fn soft_deletes() -> bool { true },
+    fn connection() -> &'static str { scoped_connection() });
+
+const TRASH_POST_2: &str = "UPDATE posts SET deleted_at = '2026-01-01 00:00:00' WHERE id = 2";
+
+// The seeded posts, with post 2 trashed
+async fn trashed() -> Arc<dyn DatabaseConnection> {
+    let mut statements = SEED.to_vec();
+    statements.push(TRASH_POST_2);
+    sqlite(&statements).await
+}
+
+#[tokio::test]
+async fn restore_only_touches_trashed_rows() {
+    let query = Draft::query().where_in("id", vec![json!(1), json!(2)]);
+    assert_eq!([Driver::MySql, Driver::Postgres, Driver::Sqlite].map(|driver| query.clone().driver(driver).to_restore_sql()), [
+        "UPDATE posts SET deleted_at = NULL WHERE `id` IN (1, 2) AND `posts`.`deleted_at` IS NOT NULL",
+        "UPDATE posts SET deleted_at = NULL WHERE \"id\" IN (1, 2) AND \"posts\".\"deleted_at\" IS NOT NULL",
+        "UPDATE posts SET deleted_at = NULL WHERE \"id\" IN (1, 2) AND \"posts\".\"deleted_at\" IS NOT NULL",
+    ]);
+
+    let connection = trashed().await;
+    assert_eq!(Draft::query().on_connection(connection.clone()).count().await.unwrap(), 2);
+    assert_eq!(query.on_connection(connection.clone()).restore().await.unwrap(), 1);
+    assert_eq!(Draft::query().on_connection(connection).count().await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn force_delete_removes_trashed_rows_too() {
+    let query = Draft::query().where_in("id", vec![json!(2), json!(3)]);
+    assert_eq!([Driver::MySql, Driver::Postgres, Driver::Sqlite].map(|driver| query.clone().driver(driver).with_trashed().to_delete_sql()), [
+        "DELETE FROM posts WHERE `id` IN (2, 3)",
+        "DELETE FROM posts WHERE \"id\" IN (2, 3)",
+        "DELETE FROM posts WHERE \"id\" IN (2, 3)",
+    ]);
+
+    let connection = trashed().await;
+    assert_eq!(query.on_connection(connection.clone()).force_delete().await.unwrap(), 2);
+    let left = Draft::query().with_trashed().on_connection(connection).get().await.unwrap();
+    assert_eq!(left.iter().map(|draft| draft.id).collect::<Vec<_>>(), vec![Some(1)]);
+}
+
+#[tokio::test]
+async fn restore_is_a_no_op_without_soft_deletes() {
+    let connection = trashed().await;
+    assert_eq!(Post::query().on_connection(connection.clone()).restore().await.unwrap(), 0);
+    assert_eq!(Post::query().where_op("deleted_at", "IS NOT", json!(null)).on_connection(connection).count().await.unwrap(), 1);
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+struct Archive {
+    id: Option<i64>,
+    title: String,
+    removed_at: Option<String>,
+}
+
+model!(Archive, "archives", |_: &str| None,
+    fn soft_deletes() -> bool { true },
+    fn deleted_at_column() -> &'static str { "removed_at" });
+
+#[async_trait::async_trait]
+impl SoftDeletes for Archive {
+    async fn restore(&mut self) -> Result<(), sqlx::Error> { Ok(()) }
+    async fn force_delete(&self) -> Result<(), sqlx::Error> { Ok(()) }
+}
+
+#[tokio::test]
+async fn a_custom_deleted_at_column_is_used_everywhere() {
+    let query = Archive::query().where_op("id", "=", json!(1));
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT * FROM archives WHERE `id` = 1 AND `archives`.`removed_at` IS NULL",
+        "SELECT * FROM archives WHERE \"id\" = 1 AND \"archives\".\"removed_at\" IS NULL",
+        "SELECT * FROM archives WHERE \"id\" = 1 AND \"archives\".\"removed_at\" IS NULL",
+    ]);
+    assert_eq!([Driver::MySql, Driver::Postgres, Driver::Sqlite].map(|driver| query.clone().driver(driver).to_soft_delete_sql()), [
+        "UPDATE archives SET removed_at = NOW() WHERE `id` = 1 AND `archives`.`removed_at` IS NULL",
+        "UPDATE archives SET removed_at = NOW() WHERE \"id\" = 1 AND \"archives\".\"removed_at\" IS NULL",
+        "UPDATE archives SET removed_at = CURRENT_TIMESTAMP WHERE \"id\" = 1 AND \"archives\".\"removed_at\" IS NULL",
+    ]);
+
+    let connection = sqlite(&[
+        "CREATE TABLE archives (id INTEGER PRIMARY KEY, title TEXT NOT NULL, removed_at TEXT)",
+        "INSERT INTO archives (id, title) VALUES (1, 'old'), (2, 'older')",
+    ])
+    .await;
+    let archives = || Archive::query().on_connection(connection.clone());
+
+    assert_eq!(archives().where_op("id", "=", json!(1)).soft_delete().await.unwrap(), 1);
+    assert_eq!(archives().count().await.unwrap(), 1);
+    let trashed = archives().only_trashed().get().await.unwrap();
+    assert_eq!(trashed.iter().map(|archive| (archive.id, archive.trashed())).collect::<Vec<_>>(), [(Some(1), true)]);
+    assert!(!archives().first().await.unwrap().unwrap().trashed());
+
+    assert_eq!(archives().restore().await.unwrap(), 1);
+    assert_eq!(archives().count().await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn relations_toggle_trashed_rows_for_one_query() {
+    let ada = User { id: Some(1), name: "ada".into() };
+    let drafts = || ada.has_many::<Draft>(None, None);
+    assert_eq!(sql_per_driver(&drafts().only_trashed()), [
+        "SELECT * FROM posts WHERE `user_id` = 1 AND `posts`.`deleted_at` IS NOT NULL",
+        "SELECT * FROM posts WHERE \"user_id\" = 1 AND \"posts\".\"deleted_at\" IS NOT NULL",
+        "SELECT * FROM posts WHERE \"user_id\" = 1 AND \"posts\".\"deleted_at\" IS NOT NULL",
+    ]);
+    assert_eq!(sql_per_driver(&drafts().with_trashed())[2], "SELECT * FROM posts WHERE \"user_id\" = 1");
+
+    with_registered("relation_trashed", |connection| async move {
+        connection.execute(TRASH_POST_2).await.unwrap();
+        let ids = |drafts: Vec<Draft>| drafts.iter().map(|draft| draft.id).collect::<Vec<_>>();
+        assert_eq!(ids(drafts().get().await.unwrap()), vec![Some(1)]);
+        assert_eq!(ids(drafts().with_trashed().order_by("id", "asc").get().await.unwrap()), vec![Some(1), Some(2)]);
+        assert_eq!(ids(drafts().only_trashed().get().await.unwrap()), vec![Some(2)]);
+        assert_eq!(ids(drafts().with_trashed().without_trashed().get().await.unwrap()), vec![Some(1)]);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn a_raw_or_cannot_escape_the_soft_delete_filter() {
+    let query = Draft::query().where_raw("id = ? OR id = ?", vec![json!(2), json!(3)]);
+    assert_eq!(sql_per_driver(&query)[2],
+        "SELECT * FROM posts WHERE (id = 2 OR id = 3) AND \"posts\".\"deleted_at\" IS NULL");
+    let drafts = query.on_connection(trashed().await).get().await.unwrap();
+    assert_eq!(drafts.iter().map(|draft| draft.id).collect::<Vec<_>>(), vec![Some(3)]);
+}
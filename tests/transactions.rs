@@ -0,0 +1,139 @@
+mod common;
+
+use common::*;
+use std::sync::{Arc, Mutex};
+use RustEloquent::db::connection::{ConnectionEvent, ConnectionManager};
+use RustEloquent::orm::EloquentError;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+struct Entry {
+    id: Option<i64>,
+    name: String,
+}
+
+model!(Entry, "users", |_: &str| None, fn connection() -> &'static str { "transactions" });
+
+async fn register() {
+    let connection = ConnectionManager::new().connect("transactions", "sqlite::memory:").await.unwrap();
+    for sql in SCHEMA {
+        connection.execute(sql).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn queries_join_the_open_transaction_and_otherwise_use_the_pool() {
+    register().await;
+
+    // Outside a transaction, unbound queries run on the registered pool
+    Entry::create(attributes(&[("name", json!("kept"))])).await.unwrap();
+    assert_eq!(Entry::query().count().await.unwrap(), 1);
+
+    let result: Result<(), sqlx::Error> = RustEloquent::transaction_on("transactions", |_| async {
+        Entry::create(attributes(&[("name", json!("discarded"))])).await?;
+        // Visible inside, as the count runs on the same transaction
+        assert_eq!(Entry::query().count().await?, 2);
+        Err(sqlx::Error::RowNotFound)
+    })
+    .await;
+    assert!(result.is_err());
+
+    let names = Entry::query().pluck("name").await.unwrap();
+    assert_eq!(names, vec![json!("kept")]);
+
+    // Spawned tasks don't inherit the transaction, so they go to the pool
+    RustEloquent::transaction_on("transactions", |_| async {
+        let outside = tokio::spawn(async { RustEloquent::current_transaction().is_none() }).await.unwrap();
+        assert!(outside);
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+struct Orphan {
+    id: Option<i64>,
+    name: String,
+}
+
+model!(Orphan, "users", |_: &str| None, fn connection() -> &'static str { "unregistered" });
+
+#[tokio::test]
+async fn queries_on_an_unregistered_connection_fail() {
+    let error = Orphan::query().count().await.unwrap_err();
+    assert!(matches!(
+        EloquentError::from(error),
+        EloquentError::Configuration(message) if message.contains("[unregistered]")
+    ));
+
+    let error = Orphan::query().where_op("id", "=", json!(1)).delete().await.unwrap_err();
+    assert!(matches!(error, EloquentError::Configuration(_)));
+}
+
+#[tokio::test]
+async fn every_transaction_reaches_the_managers_listeners() {
+    let manager = ConnectionManager::new();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let seen = events.clone();
+    manager.listen(move |event| seen.lock().unwrap().push(event.clone())).await;
+    manager.connect("transaction_events", "sqlite::memory:").await.unwrap();
+
+    // Opened without the manager, with a savepoint inside that rolls back
+    RustEloquent::transaction_on("transaction_events", |_| async {
+        let nested: Result<(), sqlx::Error> =
+            RustEloquent::transaction_on("transaction_events", |_| async { Err(sqlx::Error::RowNotFound) }).await;
+        assert!(nested.is_err());
+        Ok(())
+    })
+    .await
+    .unwrap();
+
+    // The rollback fails as the closure already committed, but its own error is kept
+    let error = manager.transaction(Some("transaction_events"), |transaction| async move {
+        transaction.commit().await?;
+        Err::<(), _>(sqlx::Error::RowNotFound)
+    })
+    .await
+    .unwrap_err();
+    assert!(matches!(error, sqlx::Error::RowNotFound));
+
+    let name = "transaction_events".to_string();
+    assert_eq!(*events.lock().unwrap(), [
+        ConnectionEvent::Connected { name: name.clone() },
+        ConnectionEvent::TransactionBeginning { name: name.clone() },
+        ConnectionEvent::TransactionBeginning { name: name.clone() },
+        ConnectionEvent::TransactionRolledBack { name: name.clone() },
+        ConnectionEvent::TransactionCommitted { name: name.clone() },
+        ConnectionEvent::TransactionBeginning { name: name.clone() },
+        ConnectionEvent::TransactionRolledBack { name },
+    ]);
+}
+
+#[tokio::test]
+async fn a_failing_savepoint_statement_keeps_the_closures_result() {
+    let connection = ConnectionManager::new().connect("savepoint_failures", "sqlite::memory:").await.unwrap();
+    connection.execute("CREATE TABLE entries (name TEXT NOT NULL)").await.unwrap();
+
+    let names = RustEloquent::transaction_on("savepoint_failures", |outer| async move {
+        // Each closure releases its own savepoint, so the RELEASE / ROLLBACK TO after it fails
+        let failed: Result<(), sqlx::Error> = RustEloquent::transaction_on("savepoint_failures", |inner| async move {
+            inner.unprepared("RELEASE SAVEPOINT trans1").await?;
+            Err(sqlx::Error::RowNotFound)
+        })
+        .await;
+        assert!(matches!(failed, Err(sqlx::Error::RowNotFound)));
+
+        let kept = RustEloquent::transaction_on("savepoint_failures", |inner| async move {
+            inner.execute("INSERT INTO entries (name) VALUES ('kept')").await?;
+            inner.unprepared("RELEASE SAVEPOINT trans1").await?;
+            Ok(7)
+        })
+        .await;
+        assert_eq!(kept.unwrap(), 7);
+
+        outer.fetch_all("SELECT name FROM entries").await
+    })
+    .await
+    .unwrap();
+    assert_eq!(names.1, vec![vec![json!("kept")]]);
+}
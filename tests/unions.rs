@@ -0,0 +1,46 @@
+mod common;
+
+use common::*;
+
+fn titles_union() -> Query<Post> {
+    Post::query()
+        .select(vec!["id", "title"])
+        .where_op("user_id", "=", json!(1))
+        .union(Post::query().select(vec!["id", "title"]).where_op("user_id", "=", json!(2)))
+        .order_by("id", "desc")
+        .limit(2)
+}
+
+#[test]
+fn order_and_limit_wrap_the_compound_statement() {
+    assert_eq!(sql_per_driver(&titles_union()), [
+        "(SELECT `id`, `title` FROM posts WHERE `user_id` = 1) UNION (SELECT `id`, `title` FROM posts WHERE `user_id` = 2) \
+         ORDER BY `id` DESC LIMIT 2",
+        "(SELECT \"id\", \"title\" FROM posts WHERE \"user_id\" = 1) UNION (SELECT \"id\", \"title\" FROM posts WHERE \"user_id\" = 2) \
+         ORDER BY \"id\" DESC LIMIT 2",
+        "SELECT * FROM (SELECT \"id\", \"title\" FROM posts WHERE \"user_id\" = 1) UNION SELECT * FROM (SELECT \"id\", \"title\" FROM posts WHERE \"user_id\" = 2) \
+         ORDER BY \"id\" DESC LIMIT 2",
+    ]);
+}
+
+#[tokio::test]
+async fn the_limit_applies_to_the_combined_rows_on_sqlite() {
+    let connection = seeded().await;
+    let rows: Vec<std::collections::HashMap<String, Value>> = titles_union().on_connection(connection.clone()).get_as().await.unwrap();
+    assert_eq!(rows.iter().map(|row| &row["id"]).collect::<Vec<_>>(), [&json!(3), &json!(2)]);
+
+    let all = Post::query()
+        .on_connection(connection)
+        .where_op("id", "=", json!(1))
+        .union_all(Post::query().where_op("id", "=", json!(1)));
+    assert_eq!(all.count().await.unwrap(), 2);
+}
+
+#[test]
+fn members_render_for_the_driver_the_query_ends_up_with() {
+    let query = Post::query()
+        .driver(Driver::MySql)
+        .union(Post::query().where_op("title", "=", json!("a\\b")))
+        .driver(Driver::Postgres);
+    assert_eq!(query.to_sql(), "(SELECT * FROM posts) UNION (SELECT * FROM posts WHERE \"title\" = 'a\\b')");
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::*;
+use RustEloquent::db::connection::{DatabaseConnection, PostgresConnection};
+use RustEloquent::orm::{is_unique_violation, EloquentError};
+
+const UNIQUE_NAMES: &str = "CREATE UNIQUE INDEX users_name_unique ON users (name)";
+
+#[test]
+fn create_unique_looks_up_the_conflicting_row_by_its_unique_columns() {
+    let unique = attributes(&[("name", json!("bob")), ("id", json!(2))]);
+    assert_eq!(sql_per_driver(&User::attributes_query(&unique)), [
+        "SELECT * FROM users WHERE `id` = 2 AND `name` = 'bob'",
+        "SELECT * FROM users WHERE \"id\" = 2 AND \"name\" = 'bob'",
+        "SELECT * FROM users WHERE \"id\" = 2 AND \"name\" = 'bob'",
+    ]);
+}
+
+#[tokio::test]
+async fn create_unique_returns_the_existing_row_on_a_conflict() {
+    with_registered("create_unique", |connection| async move {
+        connection.execute(UNIQUE_NAMES).await.unwrap();
+
+        let existing = User::create_unique(attributes(&[("name", json!("bob")), ("email", json!("b@x"))]), &["name"]).await.unwrap();
+        assert_eq!(existing, User { id: Some(2), name: "bob".into() });
+
+        let created = User::create_unique(attributes(&[("name", json!("dee"))]), &["name"]).await.unwrap();
+        assert_eq!(created.id, Some(4));
+        assert_eq!(User::query().count().await.unwrap(), 4);
+
+        let error = User::create_unique(attributes(&[("name", json!("eve"))]), &["email"]).await.unwrap_err();
+        assert!(error.to_string().contains("unique column [email] is not among the attributes"), "{}", error);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn is_unique_violation_tells_constraint_errors_apart() {
+    let connection = seeded().await;
+    connection.execute(UNIQUE_NAMES).await.unwrap();
+
+    let duplicate = connection.execute("INSERT INTO users (name) VALUES ('ada')").await.unwrap_err();
+    assert!(is_unique_violation(&duplicate), "{}", duplicate);
+    let primary_key = connection.execute("INSERT INTO users (id, name) VALUES (1, 'dee')").await.unwrap_err();
+    assert!(is_unique_violation(&primary_key), "{}", primary_key);
+    let not_null = connection.execute("INSERT INTO users (id) VALUES (9)").await.unwrap_err();
+    assert!(!is_unique_violation(&not_null), "{}", not_null);
+    assert!(!is_unique_violation(&sqlx::Error::RowNotFound));
+}
+
+#[tokio::test]
+async fn unique_violations_name_the_columns_on_sqlite() {
+    let connection = seeded().await;
+    connection.execute("CREATE UNIQUE INDEX posts_user_title_unique ON posts (user_id, title)").await.unwrap();
+
+    let error = Post::query().on_connection(connection.clone())
+        .where_op("id", "=", json!(2))
+        .update(attributes(&[("title", json!("first"))]))
+        .await
+        .unwrap_err();
+    match error {
+        EloquentError::UniqueViolation { constraint, columns } => {
+            assert_eq!((constraint, columns), (None, vec!["user_id".to_string(), "title".to_string()]));
+        }
+        other => panic!("expected UniqueViolation, got {:?}", other),
+    }
+
+    let duplicate_key = Post::query().on_connection(connection.clone())
+        .create_many(vec![attributes(&[("id", json!(1)), ("user_id", json!(3)), ("title", json!("x"))])])
+        .await
+        .unwrap_err();
+    assert_eq!(EloquentError::from(duplicate_key).to_string(), "unique constraint violated on (id)");
+
+    let not_null = connection.execute("INSERT INTO posts (user_id) VALUES (1)").await.unwrap_err();
+    assert!(matches!(EloquentError::from(not_null), EloquentError::Database(_)));
+}
+
+// Runs against the database in POSTGRES_URL, and is skipped when that isn't set
+#[tokio::test]
+async fn unique_violations_name_the_constraint_on_postgres() {
+    let Ok(url) = std::env::var("POSTGRES_URL") else {
+        return;
+    };
+    let connection = PostgresConnection::new(&url).await.unwrap();
+    for sql in [
+        "DROP TABLE IF EXISTS unique_members",
+        "CREATE TABLE unique_members (team_id BIGINT NOT NULL, name TEXT NOT NULL, \
+         CONSTRAINT unique_members_team_name UNIQUE (team_id, name))",
+        "INSERT INTO unique_members VALUES (1, 'ada')",
+    ] {
+        connection.execute(sql).await.expect(sql);
+    }
+
+    let error = connection.execute("INSERT INTO unique_members VALUES (1, 'ada')").await.unwrap_err();
+    assert_eq!(EloquentError::from(error).to_string(),
+        "unique constraint violated [unique_members_team_name] on (team_id, name)");
+
+    connection.execute("DROP TABLE unique_members").await.unwrap();
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use common::*;
+use RustEloquent::orm::query::Expression;
+
+#[tokio::test]
+async fn where_in_models_without_keys_matches_nothing() {
+    let unsaved = [User { id: None, name: "new".into() }];
+    let query = Post::query().where_in_models("user_id", &unsaved);
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT * FROM posts WHERE 0 = 1",
+        "SELECT * FROM posts WHERE 0 = 1",
+        "SELECT * FROM posts WHERE 0 = 1",
+    ]);
+    assert!(query.on_connection(seeded().await).get().await.unwrap().is_empty());
+
+    let users = [User { id: Some(2), name: "bob".into() }, unsaved[0].clone()];
+    let query = Post::query().where_in_models("user_id", &users);
+    assert_eq!(sql_per_driver(&query)[0], "SELECT * FROM posts WHERE `user_id` IN (2)");
+    let posts = query.on_connection(seeded().await).get().await.unwrap();
+    assert_eq!(posts.iter().map(|post| post.id).collect::<Vec<_>>(), vec![Some(3)]);
+}
+
+#[tokio::test]
+async fn where_many_adds_one_equality_per_pair_in_order() {
+    let query = Post::query().where_many(vec![("user_id", json!(1)), ("title", json!("second"))]);
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT * FROM posts WHERE `user_id` = 1 AND `title` = 'second'",
+        "SELECT * FROM posts WHERE \"user_id\" = 1 AND \"title\" = 'second'",
+        "SELECT * FROM posts WHERE \"user_id\" = 1 AND \"title\" = 'second'",
+    ]);
+    let posts = query.on_connection(seeded().await).get().await.unwrap();
+    assert_eq!(posts.iter().map(|post| post.id).collect::<Vec<_>>(), vec![Some(2)]);
+}
+
+#[tokio::test]
+async fn where_in_columns_uses_row_values_except_on_sqlite() {
+    let query = Post::query()
+        .where_in_columns(vec!["user_id", "title"], vec![vec![json!(1), json!("second")], vec![json!(2), json!("third")]]);
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT * FROM posts WHERE (`user_id`, `title`) IN ((1, 'second'), (2, 'third'))",
+        "SELECT * FROM posts WHERE (\"user_id\", \"title\") IN ((1, 'second'), (2, 'third'))",
+        "SELECT * FROM posts WHERE ((\"user_id\" = 1 AND \"title\" = 'second') OR (\"user_id\" = 2 AND \"title\" = 'third'))",
+    ]);
+    let posts = query.order_by("id", "asc").on_connection(seeded().await).get().await.unwrap();
+    assert_eq!(posts.iter().map(|post| post.id).collect::<Vec<_>>(), vec![Some(2), Some(3)]);
+
+    let query = Post::query().where_in_columns(vec!["user_id", "title"], Vec::new());
+    assert_eq!(sql_per_driver(&query)[2], "SELECT * FROM posts WHERE 0 = 1");
+    assert!(query.on_connection(seeded().await).get().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn where_ci_ignores_case_on_every_driver() {
+    let query = User::query().where_ci("name", "LIKE", json!("%A%")).or_where_ci("name", "=", json!("BOB"));
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT * FROM users WHERE LOWER(`name`) LIKE LOWER('%A%') OR LOWER(`name`) = LOWER('BOB')",
+        "SELECT * FROM users WHERE \"name\" ILIKE '%A%' OR LOWER(\"name\") = LOWER('BOB')",
+        "SELECT * FROM users WHERE LOWER(\"name\") LIKE LOWER('%A%') OR LOWER(\"name\") = LOWER('BOB')",
+    ]);
+    let users = query.order_by("id", "asc").on_connection(seeded().await).get().await.unwrap();
+    assert_eq!(users.iter().map(|user| user.id).collect::<Vec<_>>(), vec![Some(1), Some(2)]);
+
+    let query = User::query().where_ci("name", "not like", json!("%Y"));
+    assert_eq!(sql_per_driver(&query)[1], "SELECT * FROM users WHERE \"name\" NOT ILIKE '%Y'");
+    let users = query.order_by("id", "asc").on_connection(seeded().await).get().await.unwrap();
+    assert_eq!(users.iter().map(|user| user.id).collect::<Vec<_>>(), vec![Some(1), Some(2)]);
+}
+
+#[tokio::test]
+async fn raw_fragments_with_a_top_level_or_keep_their_grouping() {
+    let query = Post::query()
+        .where_op("user_id", "=", json!(1))
+        .where_raw("title = ? OR title = ?", vec![json!("first"), json!("third")]);
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT * FROM posts WHERE `user_id` = 1 AND (title = 'first' OR title = 'third')",
+        "SELECT * FROM posts WHERE \"user_id\" = 1 AND (title = 'first' OR title = 'third')",
+        "SELECT * FROM posts WHERE \"user_id\" = 1 AND (title = 'first' OR title = 'third')",
+    ]);
+    let posts = query.on_connection(seeded().await).get().await.unwrap();
+    assert_eq!(posts.iter().map(|post| post.id).collect::<Vec<_>>(), vec![Some(1)]);
+
+    // Quoted text, nested parentheses and words that merely contain "or" are left alone
+    for raw in ["title = 'a or b'", "(id = 1 OR id = 2)", "title <> 'x' AND user_id IN (SELECT id FROM users WHERE name = 'ada' OR id = 2)"] {
+        let query = Post::query().where_op("id", ">", json!(0)).where_raw(raw, Vec::new());
+        assert_eq!(sql_per_driver(&query)[2], format!("SELECT * FROM posts WHERE \"id\" > 0 AND {}", raw));
+        query.on_connection(seeded().await).get().await.unwrap();
+    }
+
+    let query = Post::query().having_raw("COUNT(*) > 1 or MAX(id) = 3", Vec::new()).select(vec!["user_id"]).group_by(vec!["user_id"]);
+    assert_eq!(sql_per_driver(&query)[1],
+        "SELECT \"user_id\" FROM posts GROUP BY \"user_id\" HAVING (COUNT(*) > 1 or MAX(id) = 3)");
+    assert_eq!(query.on_connection(seeded().await).count().await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn raw_left_hand_sides_render_for_the_final_driver() {
+    // Built for Postgres; the bound expression's backslash is doubled only on MySQL
+    let lhs = Expression::Bound("REPLACE(title, ?, '')".to_string(), vec![json!("\\")]);
+    let query = Post::query().driver(Driver::Postgres).where_op(lhs, "=", json!("first")).select(vec!["id"]);
+    assert_eq!(sql_per_driver(&query), [
+        "SELECT `id` FROM posts WHERE REPLACE(title, '\\\\', '') = 'first'",
+        "SELECT \"id\" FROM posts WHERE REPLACE(title, '\\', '') = 'first'",
+        "SELECT \"id\" FROM posts WHERE REPLACE(title, '\\', '') = 'first'",
+    ]);
+
+    let ids = query.driver(Driver::Sqlite).on_connection(seeded().await).pluck("id").await.unwrap();
+    assert_eq!(ids, vec![json!(1)]);
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::*;
+
+type Variant = (fn(Query<Post>) -> Query<Post>, &'static str, &'static [i64]);
+
+// Each variant after `id = 1`, its MySQL WHERE clause and the seeded posts it matches.
+// Postgres and SQLite render the same with double-quoted identifiers
+const VARIANTS: &[Variant] = &[
+    (|q| q.where_like("title", "%ir%"), "`id` = 1 AND `title` LIKE '%ir%'", &[1]),
+    (|q| q.where_not_like("title", "%ir%"), "`id` = 1 AND `title` NOT LIKE '%ir%'", &[]),
+    (|q| q.or_where_like("title", "s%"), "`id` = 1 OR `title` LIKE 's%'", &[1, 2]),
+    (|q| q.or_where_not_like("title", "%ir%"), "`id` = 1 OR `title` NOT LIKE '%ir%'", &[1, 2]),
+    (|q| q.or_where_in("user_id", vec![json!(2)]), "`id` = 1 OR `user_id` IN (2)", &[1, 3]),
+    (|q| q.or_where_not_in("user_id", vec![json!(1)]), "`id` = 1 OR `user_id` NOT IN (1)", &[1, 3]),
+    (|q| q.or_where_null("deleted_at"), "`id` = 1 OR `deleted_at` IS NULL", &[1, 2, 3]),
+    (|q| q.or_where_not_null("deleted_at"), "`id` = 1 OR `deleted_at` IS NOT NULL", &[1]),
+    (|q| q.or_where_between("id", json!(3), json!(5)), "`id` = 1 OR `id` BETWEEN 3 AND 5", &[1, 3]),
+    (|q| q.or_where_not_between("id", json!(1), json!(2)), "`id` = 1 OR `id` NOT BETWEEN 1 AND 2", &[1, 3]),
+];
+
+#[tokio::test]
+async fn like_or_and_not_variants_render_per_driver_and_run_on_sqlite() {
+    let connection = seeded().await;
+    for (variant, where_clause, ids) in VARIANTS {
+        let query = variant(Post::query().where_op("id", "=", json!(1)));
+        let mysql = format!("SELECT * FROM posts WHERE {}", where_clause);
+        let others = mysql.replace('`', "\"");
+        assert_eq!(sql_per_driver(&query), [mysql, others.clone(), others]);
+
+        let posts = query.order_by("id", "asc").on_connection(connection.clone()).get().await.unwrap();
+        let found: Vec<i64> = posts.iter().filter_map(|post| post.id).collect();
+        assert_eq!(found, *ids, "{}", where_clause);
+    }
+}